@@ -4,12 +4,14 @@
 //!
 //! These are manually defined (not bindgen) for simplicity and fewer build dependencies.
 
-use std::os::raw::{c_char, c_int, c_uint, c_ulong, c_void};
+use std::os::raw::{c_char, c_int, c_uchar, c_uint, c_ulong, c_void};
 
 pub type FPDF_DOCUMENT = *mut c_void;
 pub type FPDF_PAGE = *mut c_void;
 pub type FPDF_TEXTPAGE = *mut c_void;
 pub type FPDF_BITMAP = *mut c_void;
+pub type FPDF_AVAIL = *mut c_void;
+pub type FPDF_FORMHANDLE = *mut c_void;
 pub type FPDF_BOOL = c_int;
 pub type FPDF_DWORD = c_ulong;
 pub type FPDF_STRING = *const c_char;
@@ -21,8 +23,39 @@ pub struct FPDF_LIBRARY_CONFIG {
     pub m_pIsolate: *mut c_void,
     pub m_v8EmbedderSlot: c_uint,
     pub m_pPlatform: *mut c_void,
+    pub m_RendererType: c_int,
 }
 
+#[repr(C)]
+pub struct FPDF_FILEACCESS {
+    pub m_FileLen: c_ulong,
+    pub m_GetBlock:
+        Option<unsafe extern "C" fn(param: *mut c_void, position: c_ulong, pBuf: *mut c_uchar, size: c_ulong) -> c_int>,
+    pub m_Param: *mut c_void,
+}
+
+#[repr(C)]
+pub struct FX_FILEAVAIL {
+    pub version: c_int,
+    pub IsDataAvail: Option<unsafe extern "C" fn(pThis: *mut FX_FILEAVAIL, offset: usize, size: usize) -> FPDF_BOOL>,
+}
+
+#[repr(C)]
+pub struct FX_DOWNLOADHINTS {
+    pub version: c_int,
+    pub AddSegment: Option<unsafe extern "C" fn(pThis: *mut FX_DOWNLOADHINTS, offset: usize, size: usize)>,
+}
+
+// FPDFAvail_IsDocAvail / IsPageAvail results (fpdf_dataavail.h)
+pub const PDF_DATA_ERROR: c_int = -1;
+pub const PDF_DATA_NOTAVAIL: c_int = 0;
+pub const PDF_DATA_AVAIL: c_int = 1;
+
+// FPDFAvail_IsLinearized results
+pub const PDF_LINEARIZATION_UNKNOWN: c_int = -1;
+pub const PDF_NOT_LINEARIZED: c_int = 0;
+pub const PDF_LINEARIZED: c_int = 1;
+
 // Function pointer type aliases for loaded symbols
 pub type FnInitLibraryWithConfig = unsafe extern "C" fn(*const FPDF_LIBRARY_CONFIG);
 pub type FnDestroyLibrary = unsafe extern "C" fn();
@@ -30,6 +63,18 @@ pub type FnGetLastError = unsafe extern "C" fn() -> c_ulong;
 
 pub type FnLoadMemDocument =
     unsafe extern "C" fn(*const c_void, c_int, FPDF_STRING) -> FPDF_DOCUMENT;
+pub type FnLoadMemDocument64 =
+    unsafe extern "C" fn(*const c_void, usize, FPDF_STRING) -> FPDF_DOCUMENT;
+pub type FnLoadDocument = unsafe extern "C" fn(FPDF_STRING, FPDF_STRING) -> FPDF_DOCUMENT;
+pub type FnLoadCustomDocument = unsafe extern "C" fn(*mut FPDF_FILEACCESS, FPDF_STRING) -> FPDF_DOCUMENT;
+
+pub type FnAvailCreate = unsafe extern "C" fn(*mut FX_FILEAVAIL, *mut FPDF_FILEACCESS) -> FPDF_AVAIL;
+pub type FnAvailDestroy = unsafe extern "C" fn(FPDF_AVAIL);
+pub type FnAvailIsDocAvail = unsafe extern "C" fn(FPDF_AVAIL, *mut FX_DOWNLOADHINTS) -> c_int;
+pub type FnAvailGetDocument = unsafe extern "C" fn(FPDF_AVAIL, FPDF_STRING) -> FPDF_DOCUMENT;
+pub type FnAvailGetFirstPageNum = unsafe extern "C" fn(FPDF_DOCUMENT) -> c_int;
+pub type FnAvailIsPageAvail = unsafe extern "C" fn(FPDF_AVAIL, c_int, *mut FX_DOWNLOADHINTS) -> c_int;
+pub type FnAvailIsLinearized = unsafe extern "C" fn(FPDF_AVAIL) -> c_int;
 pub type FnCloseDocument = unsafe extern "C" fn(FPDF_DOCUMENT);
 pub type FnGetPageCount = unsafe extern "C" fn(FPDF_DOCUMENT) -> c_int;
 
@@ -51,9 +96,113 @@ pub type FnBitmapFillRect =
 pub type FnBitmapDestroy = unsafe extern "C" fn(FPDF_BITMAP);
 pub type FnBitmapGetBuffer = unsafe extern "C" fn(FPDF_BITMAP) -> *mut c_void;
 pub type FnBitmapGetStride = unsafe extern "C" fn(FPDF_BITMAP) -> c_int;
+pub type FnBitmapGetWidth = unsafe extern "C" fn(FPDF_BITMAP) -> c_int;
+pub type FnBitmapGetHeight = unsafe extern "C" fn(FPDF_BITMAP) -> c_int;
+pub type FnBitmapGetFormat = unsafe extern "C" fn(FPDF_BITMAP) -> c_int;
+
+// Page objects (fpdf_edit.h)
+pub type FPDF_PAGEOBJECT = *mut c_void;
+
+pub const FPDF_PAGEOBJ_TEXT: c_int = 1;
+pub const FPDF_PAGEOBJ_IMAGE: c_int = 3;
+
+pub type FnCreateNewDocument = unsafe extern "C" fn() -> FPDF_DOCUMENT;
+pub type FnPageCountObjects = unsafe extern "C" fn(FPDF_PAGE) -> c_int;
+pub type FnPageGetObject = unsafe extern "C" fn(FPDF_PAGE, c_int) -> FPDF_PAGEOBJECT;
+pub type FnPageRemoveObject = unsafe extern "C" fn(FPDF_PAGE, FPDF_PAGEOBJECT) -> FPDF_BOOL;
+pub type FnPageObjDestroy = unsafe extern "C" fn(FPDF_PAGEOBJECT);
+pub type FnPageObjGetType = unsafe extern "C" fn(FPDF_PAGEOBJECT) -> c_int;
+pub type FnImageObjGetRenderedBitmap =
+    unsafe extern "C" fn(FPDF_DOCUMENT, FPDF_PAGE, FPDF_PAGEOBJECT) -> FPDF_BITMAP;
+pub type FnTextObjGetRenderedBitmap =
+    unsafe extern "C" fn(FPDF_DOCUMENT, FPDF_PAGE, FPDF_PAGEOBJECT, f32) -> FPDF_BITMAP;
+
+// Thumbnails (fpdf_thumbnail.h)
+pub type FnPageGetThumbnailData = unsafe extern "C" fn(FPDF_PAGE, *mut c_void, c_ulong) -> c_ulong;
+pub type FnPageGetThumbnailAsBitmap = unsafe extern "C" fn(FPDF_PAGE) -> FPDF_BITMAP;
+
+/// FPDF_RenderPageBitmap flag: draw annotations.
+pub const FPDF_ANNOT: c_int = 0x01;
 
 pub type FnRenderPageBitmap =
     unsafe extern "C" fn(FPDF_BITMAP, FPDF_PAGE, c_int, c_int, c_int, c_int, c_int, c_int);
+/// FS_MATRIX: { a, b, c, d, e, f }, mapping (x, y) to (ax + cy + e, bx + dy + f).
+#[repr(C)]
+pub struct FS_MATRIX {
+    pub a: f32,
+    pub b: f32,
+    pub c: f32,
+    pub d: f32,
+    pub e: f32,
+    pub f: f32,
+}
+
+pub type FnRenderPageBitmapWithMatrix =
+    unsafe extern "C" fn(FPDF_BITMAP, FPDF_PAGE, *const FS_MATRIX, *const FS_RECTF, c_int);
+// Form fill (fpdf_formfill.h)
+
+/// Number of fields in FPDF_FORMFILLINFO after `version`, up to and including
+/// the version 2 XFA callbacks.
+pub const FORMFILLINFO_FIELDS: usize = 34;
+
+/// FPDF_FORMFILLINFO. Every field after `version` is a nullable callback or
+/// pointer (or `xfa_disabled`, padded to pointer size), and PDFium checks each
+/// one before using it, so they are kept as a single zeroed block.
+#[repr(C)]
+pub struct FPDF_FORMFILLINFO {
+    pub version: c_int,
+    pub fields: [*mut c_void; FORMFILLINFO_FIELDS],
+}
+
+pub type FnInitFormFillEnvironment = unsafe extern "C" fn(FPDF_DOCUMENT, *mut FPDF_FORMFILLINFO) -> FPDF_FORMHANDLE;
+pub type FnExitFormFillEnvironment = unsafe extern "C" fn(FPDF_FORMHANDLE);
+pub type FnFFLDraw =
+    unsafe extern "C" fn(FPDF_FORMHANDLE, FPDF_BITMAP, FPDF_PAGE, c_int, c_int, c_int, c_int, c_int, c_int);
+pub type FnSetFormFieldHighlightColor = unsafe extern "C" fn(FPDF_FORMHANDLE, c_int, c_ulong);
+pub type FnSetFormFieldHighlightAlpha = unsafe extern "C" fn(FPDF_FORMHANDLE, c_uchar);
+
+// Progressive rendering (fpdf_progressive.h)
+pub const FPDF_RENDER_READY: c_int = 0;
+pub const FPDF_RENDER_TOBECONTINUED: c_int = 1;
+pub const FPDF_RENDER_DONE: c_int = 2;
+pub const FPDF_RENDER_FAILED: c_int = 3;
+
+/// IFSDK_PAUSE: asked periodically whether a progressive render should pause.
+#[repr(C)]
+pub struct IFSDK_PAUSE {
+    pub version: c_int,
+    pub NeedToPauseNow: Option<unsafe extern "C" fn(pThis: *mut IFSDK_PAUSE) -> FPDF_BOOL>,
+    pub user: *mut c_void,
+}
+
+pub type FnRenderPageBitmapStart =
+    unsafe extern "C" fn(FPDF_BITMAP, FPDF_PAGE, c_int, c_int, c_int, c_int, c_int, c_int, *mut IFSDK_PAUSE) -> c_int;
+/// FPDF_COLORSCHEME: colours, as 0xAARRGGBB, forced onto paths and text.
+#[repr(C)]
+pub struct FPDF_COLORSCHEME {
+    pub path_fill_color: FPDF_DWORD,
+    pub path_stroke_color: FPDF_DWORD,
+    pub text_fill_color: FPDF_DWORD,
+    pub text_stroke_color: FPDF_DWORD,
+}
+
+pub type FnRenderPageBitmapWithColorSchemeStart = unsafe extern "C" fn(
+    FPDF_BITMAP,
+    FPDF_PAGE,
+    c_int,
+    c_int,
+    c_int,
+    c_int,
+    c_int,
+    c_int,
+    *const FPDF_COLORSCHEME,
+    *mut IFSDK_PAUSE,
+) -> c_int;
+pub type FnRenderPageContinue = unsafe extern "C" fn(FPDF_PAGE, *mut IFSDK_PAUSE) -> c_int;
+pub type FnRenderPageClose = unsafe extern "C" fn(FPDF_PAGE);
+
+// Only exported by Skia-enabled builds; the first argument is an FPDF_SKIA_CANVAS.
+pub type FnRenderPageSkia = unsafe extern "C" fn(*mut c_void, FPDF_PAGE, c_int, c_int);
 
 // Metadata / Document info
 pub type FnGetMetaText =
@@ -122,13 +271,10 @@ pub type FnCopyViewerPreferences =
     unsafe extern "C" fn(FPDF_DOCUMENT, FPDF_DOCUMENT) -> FPDF_BOOL;
 
 // Save / export
-/// FPDF_FILEWRITE-compatible struct with an embedded buffer for collecting output.
-/// The first two fields must match the FPDF_FILEWRITE C struct layout.
 #[repr(C)]
-pub struct SaveContext {
+pub struct FPDF_FILEWRITE {
     pub version: c_int,
-    pub write_block: unsafe extern "C" fn(*mut SaveContext, *const c_void, c_ulong) -> c_int,
-    pub buffer: Vec<u8>,
+    pub WriteBlock: Option<unsafe extern "C" fn(pThis: *mut FPDF_FILEWRITE, pData: *const c_void, size: c_ulong) -> c_int>,
 }
 
 // Bookmarks / Links
@@ -281,6 +427,6 @@ pub type FnPageToDevice =
     unsafe extern "C" fn(FPDF_PAGE, c_int, c_int, c_int, c_int, c_int, f64, f64, *mut c_int, *mut c_int) -> c_int;
 
 pub type FnSaveAsCopy =
-    unsafe extern "C" fn(FPDF_DOCUMENT, *mut SaveContext, FPDF_DWORD) -> FPDF_BOOL;
+    unsafe extern "C" fn(FPDF_DOCUMENT, *mut FPDF_FILEWRITE, FPDF_DWORD) -> FPDF_BOOL;
 pub type FnSaveWithVersion =
-    unsafe extern "C" fn(FPDF_DOCUMENT, *mut SaveContext, FPDF_DWORD, c_int) -> FPDF_BOOL;
+    unsafe extern "C" fn(FPDF_DOCUMENT, *mut FPDF_FILEWRITE, FPDF_DWORD, c_int) -> FPDF_BOOL;
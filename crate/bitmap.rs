@@ -0,0 +1,173 @@
+//! `PdfBitmap`: a PDFium bitmap that outlives a single render.
+//!
+//! `render_page_with` allocates fresh pixels on every call. A viewer that
+//! redraws at a fixed size can create one `PdfBitmap` instead and render
+//! into it again and again. PDFium allocates the pixels and picks the
+//! stride, so rows may be padded.
+
+use crate::bindings::*;
+use crate::error::PdfiumError;
+use crate::library::{PdfiumLibrary, RenderTarget};
+use crate::render::{PixelFormat, RenderOptions};
+use std::ptr;
+
+/// The `FPDFBitmap_*` exports, resolved together when a bitmap is created.
+#[derive(Clone, Copy)]
+pub(crate) struct BitmapSymbols {
+    pub(crate) create_ex: FnBitmapCreateEx,
+    pub(crate) destroy: FnBitmapDestroy,
+    pub(crate) get_buffer: FnBitmapGetBuffer,
+    pub(crate) get_stride: FnBitmapGetStride,
+    pub(crate) get_width: FnBitmapGetWidth,
+    pub(crate) get_height: FnBitmapGetHeight,
+    pub(crate) get_format: FnBitmapGetFormat,
+}
+
+/// A bitmap owned by PDFium, destroyed on drop.
+///
+/// ```no_run
+/// # use pdfium::{PdfiumLibrary, PixelFormat, RenderFlags, RenderOptions};
+/// # let lib = PdfiumLibrary::load("libpdfium.so")?;
+/// # lib.init_library();
+/// # let doc = lib.load_document_from_path("input.pdf", None)?;
+/// let mut bitmap = lib.create_bitmap(800, 1000, PixelFormat::Bgra)?;
+/// let options = RenderOptions::new().render_flags(RenderFlags::ANNOTATIONS);
+/// for index in 0..lib.get_page_count(doc)? {
+///     let page = lib.load_page(doc, index)?;
+///     lib.render_page_to_bitmap(page, &mut bitmap, &options)?;
+///     // Show bitmap.data(), whose rows are bitmap.stride() bytes apart.
+///     lib.close_page(page)?;
+/// }
+/// # Ok::<(), pdfium::PdfiumError>(())
+/// ```
+pub struct PdfBitmap<'lib> {
+    syms: BitmapSymbols,
+    bitmap: FPDF_BITMAP,
+    // PDFium records RGBA bitmaps as BGRA; renders into them reverse the bytes.
+    rgba: bool,
+    _lib: &'lib PdfiumLibrary,
+}
+
+impl PdfiumLibrary {
+    /// Create a `width` x `height` bitmap in `format`.
+    pub fn create_bitmap(&self, width: i32, height: i32, format: PixelFormat) -> Result<PdfBitmap<'_>, PdfiumError> {
+        self.ensure_initialised()?;
+        if width <= 0 || height <= 0 {
+            return Err(PdfiumError::InvalidArgument(format!("bitmap size: {}x{}", width, height)));
+        }
+        let syms = self.bitmap_symbols()?;
+        // A null buffer asks PDFium to allocate the pixels and choose the stride.
+        let bitmap = unsafe { (syms.create_ex)(width, height, format.bitmap_format(), ptr::null_mut(), 0) };
+        if bitmap.is_null() {
+            return Err(PdfiumError::Render("Failed to create bitmap".to_string()));
+        }
+        Ok(PdfBitmap {
+            syms,
+            bitmap,
+            rgba: format == PixelFormat::Rgba,
+            _lib: self,
+        })
+    }
+
+    /// Render the page to fill `bitmap`, replacing what it held.
+    ///
+    /// The bitmap's format is used in place of `options`' format; everything
+    /// else in `options` applies as for `render_page_into`.
+    pub fn render_page_to_bitmap(
+        &self,
+        page_handle: u32,
+        bitmap: &mut PdfBitmap<'_>,
+        options: &RenderOptions,
+    ) -> Result<(), PdfiumError> {
+        let options = options.format(bitmap.format());
+        let (width, height) = (bitmap.width(), bitmap.height());
+        self.render_page_to(page_handle, width, height, &options, RenderTarget::Bitmap(bitmap.bitmap))
+    }
+}
+
+impl PdfBitmap<'_> {
+    pub fn width(&self) -> i32 {
+        unsafe { (self.syms.get_width)(self.bitmap) }
+    }
+
+    pub fn height(&self) -> i32 {
+        unsafe { (self.syms.get_height)(self.bitmap) }
+    }
+
+    /// Bytes from the start of one row to the next.
+    pub fn stride(&self) -> usize {
+        unsafe { (self.syms.get_stride)(self.bitmap) }.max(0) as usize
+    }
+
+    /// The layout renders write. PDFium itself records RGBA bitmaps as BGRA.
+    pub fn format(&self) -> PixelFormat {
+        let format = unsafe { (self.syms.get_format)(self.bitmap) };
+        match PixelFormat::from_bitmap_format(format) {
+            Some(PixelFormat::Bgra) if self.rgba => PixelFormat::Rgba,
+            Some(format) => format,
+            None => unreachable!("bitmap was created in format {}", format),
+        }
+    }
+
+    /// The pixels, `stride() * height()` bytes.
+    pub fn data(&self) -> &[u8] {
+        let buffer = unsafe { (self.syms.get_buffer)(self.bitmap) } as *const u8;
+        // SAFETY: the bitmap owns `stride * height` bytes until it is destroyed.
+        unsafe { std::slice::from_raw_parts(buffer, self.len()) }
+    }
+
+    pub fn data_mut(&mut self) -> &mut [u8] {
+        let buffer = unsafe { (self.syms.get_buffer)(self.bitmap) } as *mut u8;
+        // SAFETY: as for `data`, and `&mut self` makes this the only view.
+        unsafe { std::slice::from_raw_parts_mut(buffer, self.len()) }
+    }
+
+    fn len(&self) -> usize {
+        self.stride() * self.height().max(0) as usize
+    }
+}
+
+impl Drop for PdfBitmap<'_> {
+    fn drop(&mut self) {
+        unsafe { (self.syms.destroy)(self.bitmap) };
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::render::RenderFlags;
+    use crate::test_support::{skip_if_no_lib, test_pdf_path};
+
+    #[test]
+    fn bitmaps_are_reused_across_renders() {
+        let path = skip_if_no_lib!();
+        let lib = PdfiumLibrary::load(&path).unwrap();
+        lib.init_library();
+
+        let mut bitmap = lib.create_bitmap(101, 80, PixelFormat::Bgr).unwrap();
+        assert_eq!((bitmap.width(), bitmap.height(), bitmap.format()), (101, 80, PixelFormat::Bgr));
+        assert!(bitmap.stride() >= PixelFormat::Bgr.min_stride(101));
+        assert_eq!(bitmap.data().len(), bitmap.stride() * 80);
+
+        let doc = lib.load_document_from_path(test_pdf_path(), None).unwrap();
+        let page = lib.load_page(doc, 0).unwrap();
+        let options = RenderOptions::new()
+            .format(PixelFormat::Rgba)
+            .render_flags(RenderFlags::ANNOTATIONS | RenderFlags::GRAYSCALE);
+        lib.render_page_to_bitmap(page, &mut bitmap, &options.background(0xFF00_00FF)).unwrap();
+        let first = bitmap.data().to_vec();
+        lib.render_page_to_bitmap(page, &mut bitmap, &options.background(0xFFFF_0000)).unwrap();
+        // Same pixels, redrawn over a different background.
+        assert_eq!(bitmap.data().len(), first.len());
+        assert_ne!(bitmap.data(), &first[..]);
+
+        assert!(lib.create_bitmap(0, 80, PixelFormat::Bgra).is_err());
+        assert_eq!(lib.create_bitmap(4, 4, PixelFormat::Rgba).unwrap().format(), PixelFormat::Rgba);
+        assert_eq!(lib.create_bitmap(4, 4, PixelFormat::Gray8).unwrap().format(), PixelFormat::Gray8);
+
+        lib.close_document(doc).unwrap();
+        drop(bitmap);
+        lib.destroy_library();
+    }
+}
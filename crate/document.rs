@@ -0,0 +1,764 @@
+//! Safe, lifetime-checked wrappers around the handle-based `PdfiumLibrary` API.
+//!
+//! `Document`, `Page` and `TextPage` own their underlying handle and close it
+//! on drop. Lifetimes tie each object to its parent, so a page can never
+//! outlive its document and a text page can never outlive its page. The
+//! handle-based methods on `PdfiumLibrary` remain available as a low-level
+//! escape hatch.
+
+use crate::bitmap::PdfBitmap;
+#[cfg(feature = "encode")]
+use crate::encode::ImageFormat;
+use crate::error::PdfiumError;
+use crate::library::{AnnotationInfo, BookmarkNode, LinkInfo, PdfiumLibrary, Thumbnail};
+use crate::render::{ClipRect, Matrix, PageBox, RenderOptions, RenderedImage};
+use crate::save::SaveOptions;
+use crate::tiles::TilePyramid;
+use std::io::{Read, Seek, Write};
+use std::path::Path;
+
+/// An open PDF document. Closed with `FPDF_CloseDocument` on drop.
+pub struct Document<'lib> {
+    lib: &'lib PdfiumLibrary,
+    handle: u32,
+}
+
+/// A loaded page. Closed with `FPDF_ClosePage` on drop.
+pub struct Page<'doc> {
+    doc: &'doc Document<'doc>,
+    handle: u32,
+}
+
+/// The text layer of a page. Closed with `FPDFText_ClosePage` on drop.
+pub struct TextPage<'page> {
+    page: &'page Page<'page>,
+    handle: u32,
+}
+
+impl PdfiumLibrary {
+    /// Load a document from a byte buffer and wrap it in a `Document`.
+    /// The bytes are copied; pass an owned or `Arc<[u8]>` buffer to
+    /// `open_document_from_buffer` to avoid that.
+    pub fn open_document(&self, data: &[u8], password: Option<&str>) -> Result<Document<'_>, PdfiumError> {
+        let handle = self.load_document(data, password)?;
+        Ok(Document { lib: self, handle })
+    }
+
+    /// Load a document from an owned buffer without copying it.
+    /// See `PdfiumLibrary::load_document_from_buffer`.
+    pub fn open_document_from_buffer<B>(&self, buffer: B, password: Option<&str>) -> Result<Document<'_>, PdfiumError>
+    where
+        B: AsRef<[u8]> + Send + 'static,
+    {
+        let handle = self.load_document_from_buffer(buffer, password)?;
+        Ok(Document { lib: self, handle })
+    }
+
+    /// Load a document that reads from `reader` on demand.
+    /// See `PdfiumLibrary::load_document_from_reader`.
+    pub fn open_document_from_reader<R>(&self, reader: R, password: Option<&str>) -> Result<Document<'_>, PdfiumError>
+    where
+        R: Read + Seek + Send + 'static,
+    {
+        let handle = self.load_document_from_reader(reader, password)?;
+        Ok(Document { lib: self, handle })
+    }
+
+    /// Load a document from a file. See `PdfiumLibrary::load_document_from_path`.
+    pub fn open_document_from_path(&self, path: impl AsRef<Path>, password: Option<&str>) -> Result<Document<'_>, PdfiumError> {
+        let handle = self.load_document_from_path(path, password)?;
+        Ok(Document { lib: self, handle })
+    }
+}
+
+impl<'lib> Document<'lib> {
+    pub fn get_page_count(&self) -> Result<i32, PdfiumError> {
+        self.lib.get_page_count(self.handle)
+    }
+
+    pub fn load_page(&self, index: i32) -> Result<Page<'_>, PdfiumError> {
+        let handle = self.lib.load_page(self.handle, index)?;
+        Ok(Page { doc: self, handle })
+    }
+
+    // --- Metadata ---
+
+    pub fn get_meta_text(&self, tag: &str) -> Result<Option<String>, PdfiumError> {
+        self.lib.get_meta_text(self.handle, tag)
+    }
+
+    pub fn get_file_version(&self) -> Result<Option<i32>, PdfiumError> {
+        self.lib.get_file_version(self.handle)
+    }
+
+    pub fn get_doc_permissions(&self) -> Result<u32, PdfiumError> {
+        self.lib.get_doc_permissions(self.handle)
+    }
+
+    pub fn get_doc_user_permissions(&self) -> Result<u32, PdfiumError> {
+        self.lib.get_doc_user_permissions(self.handle)
+    }
+
+    pub fn get_page_mode(&self) -> Result<i32, PdfiumError> {
+        self.lib.get_page_mode(self.handle)
+    }
+
+    pub fn get_security_handler_revision(&self) -> Result<i32, PdfiumError> {
+        self.lib.get_security_handler_revision(self.handle)
+    }
+
+    pub fn is_tagged(&self) -> Result<bool, PdfiumError> {
+        self.lib.is_tagged(self.handle)
+    }
+
+    pub fn get_page_label(&self, page_index: i32) -> Result<Option<String>, PdfiumError> {
+        self.lib.get_page_label(self.handle, page_index)
+    }
+
+    // --- Save / Export ---
+
+    /// Save the document to a byte buffer.
+    pub fn save(&self, options: &SaveOptions) -> Result<Vec<u8>, PdfiumError> {
+        let mut buffer = Vec::new();
+        self.lib.save_to_writer(self.handle, &mut buffer, options)?;
+        Ok(buffer)
+    }
+
+    /// Stream the document to `writer`. See `PdfiumLibrary::save_to_writer`.
+    pub fn save_to_writer<W: Write>(&self, writer: W, options: &SaveOptions) -> Result<(), PdfiumError> {
+        self.lib.save_to_writer(self.handle, writer, options)
+    }
+
+    /// Save the document to a file atomically. See `PdfiumLibrary::save_to_path`.
+    pub fn save_to_path(&self, path: impl AsRef<Path>, options: &SaveOptions) -> Result<(), PdfiumError> {
+        self.lib.save_to_path(self.handle, path, options)
+    }
+
+    /// Whether the document has changed since it was loaded or last saved.
+    pub fn is_dirty(&self) -> Result<bool, PdfiumError> {
+        self.lib.is_document_dirty(self.handle)
+    }
+
+    // --- Forms ---
+
+    /// See `PdfiumLibrary::set_form_field_highlight_colour`.
+    pub fn set_form_field_highlight_colour(&self, field_type: i32, colour: u32) -> Result<(), PdfiumError> {
+        self.lib.set_form_field_highlight_colour(self.handle, field_type, colour)
+    }
+
+    pub fn set_form_field_highlight_alpha(&self, alpha: u8) -> Result<(), PdfiumError> {
+        self.lib.set_form_field_highlight_alpha(self.handle, alpha)
+    }
+
+    // --- Signatures ---
+
+    pub fn get_signature_count(&self) -> Result<i32, PdfiumError> {
+        self.lib.get_signature_count(self.handle)
+    }
+
+    /// Returns (contents, byte_range, sub_filter, reason, time, doc_mdp_permission).
+    #[allow(clippy::type_complexity)]
+    pub fn get_signature(
+        &self,
+        index: i32,
+    ) -> Result<(Option<Vec<u8>>, Option<Vec<i32>>, Option<String>, Option<String>, Option<String>, i32), PdfiumError> {
+        self.lib.get_signature(self.handle, index)
+    }
+
+    // --- Attachments ---
+
+    pub fn get_attachment_count(&self) -> Result<i32, PdfiumError> {
+        self.lib.get_attachment_count(self.handle)
+    }
+
+    pub fn get_attachment(&self, index: i32) -> Result<Option<(String, Vec<u8>)>, PdfiumError> {
+        self.lib.get_attachment(self.handle, index)
+    }
+
+    // --- Page Import ---
+
+    /// Import pages from `src` by page range string ("1-3,5", 1-based), or all pages.
+    pub fn import_pages(&self, src: &Document<'_>, page_range: Option<&str>, insert_index: i32) -> Result<(), PdfiumError> {
+        self.lib.import_pages(self.handle, src.handle, page_range, insert_index)
+    }
+
+    /// Import pages from `src` by 0-based index.
+    pub fn import_pages_by_index(&self, src: &Document<'_>, page_indices: &[i32], insert_index: i32) -> Result<(), PdfiumError> {
+        self.lib.import_pages_by_index(self.handle, src.handle, page_indices, insert_index)
+    }
+
+    /// Create a new N-up document from this one.
+    ///
+    /// The result is closed along with this document, so it borrows it:
+    ///
+    /// ```compile_fail,E0505
+    /// # fn nup(lib: &pdfium::PdfiumLibrary, data: &[u8]) {
+    /// let src = lib.open_document(data, None).unwrap();
+    /// let nup = src.import_n_pages_to_one(842.0, 595.0, 2, 1).unwrap();
+    /// drop(src);
+    /// nup.get_page_count().unwrap();
+    /// # }
+    /// ```
+    pub fn import_n_pages_to_one<'a>(
+        &'a self,
+        output_width: f32,
+        output_height: f32,
+        pages_per_row: usize,
+        pages_per_column: usize,
+    ) -> Result<Document<'a>, PdfiumError> {
+        let handle = self.lib.import_n_pages_to_one(
+            self.handle,
+            output_width,
+            output_height,
+            pages_per_row,
+            pages_per_column,
+        )?;
+        Ok(Document { lib: self.lib, handle })
+    }
+
+    pub fn copy_viewer_preferences(&self, src: &Document<'_>) -> Result<bool, PdfiumError> {
+        self.lib.copy_viewer_preferences(self.handle, src.handle)
+    }
+
+    // --- Bookmarks ---
+
+    pub fn get_bookmarks(&self) -> Result<Vec<BookmarkNode>, PdfiumError> {
+        self.lib.get_bookmarks(self.handle)
+    }
+}
+
+impl Drop for Document<'_> {
+    fn drop(&mut self) {
+        let _ = self.lib.close_document(self.handle);
+    }
+}
+
+impl<'doc> Page<'doc> {
+    fn lib(&self) -> &'doc PdfiumLibrary {
+        self.doc.lib
+    }
+
+    /// The document this page belongs to.
+    pub fn document(&self) -> &'doc Document<'doc> {
+        self.doc
+    }
+
+    pub fn get_page_width(&self) -> Result<f64, PdfiumError> {
+        self.lib().get_page_width(self.handle)
+    }
+
+    pub fn get_page_height(&self) -> Result<f64, PdfiumError> {
+        self.lib().get_page_height(self.handle)
+    }
+
+    pub fn load_text_page(&self) -> Result<TextPage<'_>, PdfiumError> {
+        let handle = self.lib().load_text_page(self.handle)?;
+        Ok(TextPage { page: self, handle })
+    }
+
+    // --- Rotation / Flatten / Transparency / Content ---
+
+    pub fn get_page_rotation(&self) -> Result<i32, PdfiumError> {
+        self.lib().get_page_rotation(self.handle)
+    }
+
+    pub fn set_page_rotation(&self, rotation: i32) -> Result<(), PdfiumError> {
+        self.lib().set_page_rotation(self.handle, rotation)
+    }
+
+    pub fn has_page_transparency(&self) -> Result<bool, PdfiumError> {
+        self.lib().has_page_transparency(self.handle)
+    }
+
+    /// See `PdfiumLibrary::flatten_page`.
+    pub fn flatten_page(&self, flags: i32) -> Result<i32, PdfiumError> {
+        self.lib().flatten_page(self.handle, flags)
+    }
+
+    pub fn generate_content(&self) -> Result<bool, PdfiumError> {
+        self.lib().generate_content(self.handle)
+    }
+
+    // --- Coordinate Conversion ---
+
+    #[allow(clippy::too_many_arguments)]
+    pub fn device_to_page(
+        &self,
+        start_x: i32,
+        start_y: i32,
+        size_x: i32,
+        size_y: i32,
+        rotation: i32,
+        device_x: i32,
+        device_y: i32,
+    ) -> Result<(f64, f64), PdfiumError> {
+        self.lib().device_to_page(self.handle, start_x, start_y, size_x, size_y, rotation, device_x, device_y)
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    pub fn page_to_device(
+        &self,
+        start_x: i32,
+        start_y: i32,
+        size_x: i32,
+        size_y: i32,
+        rotation: i32,
+        page_x: f64,
+        page_y: f64,
+    ) -> Result<(i32, i32), PdfiumError> {
+        self.lib().page_to_device(self.handle, start_x, start_y, size_x, size_y, rotation, page_x, page_y)
+    }
+
+    // --- Page Objects ---
+
+    pub fn count_objects(&self) -> Result<i32, PdfiumError> {
+        self.lib().count_page_objects(self.handle)
+    }
+
+    /// 1=Text, 2=Path, 3=Image, 4=Shading, 5=Form (0 if unknown).
+    pub fn get_object_type(&self, index: i32) -> Result<i32, PdfiumError> {
+        self.lib().get_page_object_type(self.handle, index)
+    }
+
+    // --- Page Boxes ---
+
+    /// `box_type`: 0=Media, 1=Crop, 2=Bleed, 3=Trim, 4=Art.
+    pub fn get_page_box(&self, box_type: i32) -> Result<Option<[f32; 4]>, PdfiumError> {
+        self.lib().get_page_box(self.handle, box_type)
+    }
+
+    /// `box_type`: 0=Media, 1=Crop, 2=Bleed, 3=Trim, 4=Art.
+    pub fn set_page_box(&self, box_type: i32, left: f32, bottom: f32, right: f32, top: f32) -> Result<(), PdfiumError> {
+        self.lib().set_page_box(self.handle, box_type, left, bottom, right, top)
+    }
+
+    // --- Render ---
+
+    /// Render the page to an RGBA buffer. See `PdfiumLibrary::render_page`.
+    pub fn render(&self, width: i32, height: i32, rotation: i32, flags: i32, bg_colour: u32) -> Result<Vec<u8>, PdfiumError> {
+        self.lib().render_page(self.handle, width, height, rotation, flags, bg_colour)
+    }
+
+    /// Render the page to a new buffer. See `PdfiumLibrary::render_page_with`.
+    pub fn render_with(&self, width: i32, height: i32, options: &RenderOptions) -> Result<Vec<u8>, PdfiumError> {
+        self.lib().render_page_with(self.handle, width, height, options)
+    }
+
+    /// Render the page as an encoded image. See `PdfiumLibrary::render_page_encoded`.
+    #[cfg(feature = "encode")]
+    pub fn render_encoded(
+        &self,
+        width: i32,
+        height: i32,
+        options: &RenderOptions,
+        format: ImageFormat,
+        dpi: Option<f32>,
+    ) -> Result<Vec<u8>, PdfiumError> {
+        self.lib().render_page_encoded(self.handle, width, height, options, format, dpi)
+    }
+
+    /// Render a page box at `dpi`. See `PdfiumLibrary::render_at_dpi`.
+    pub fn render_at_dpi(&self, dpi: f32, page_box: PageBox, options: &RenderOptions) -> Result<RenderedImage, PdfiumError> {
+        self.lib().render_at_dpi(self.handle, dpi, page_box, options)
+    }
+
+    /// Render the page to fit a size. See `PdfiumLibrary::render_fit`.
+    pub fn render_fit(&self, max_width: u32, max_height: u32, options: &RenderOptions) -> Result<RenderedImage, PdfiumError> {
+        self.lib().render_fit(self.handle, max_width, max_height, options)
+    }
+
+    /// Render one image or text object. See `PdfiumLibrary::render_page_object`.
+    pub fn render_object(&self, index: i32, scale: f32, options: &RenderOptions) -> Result<RenderedImage, PdfiumError> {
+        self.lib().render_page_object(self.handle, index, scale, options)
+    }
+
+    /// Render one annotation alone. See `PdfiumLibrary::render_annotation`.
+    pub fn render_annotation(&self, index: i32, scale: f32, options: &RenderOptions) -> Result<RenderedImage, PdfiumError> {
+        self.lib().render_annotation(self.handle, index, scale, options)
+    }
+
+    /// A preview no larger than `max_edge`. See `PdfiumLibrary::thumbnail`.
+    pub fn thumbnail(&self, max_edge: u32) -> Result<Thumbnail, PdfiumError> {
+        self.lib().thumbnail(self.handle, max_edge)
+    }
+
+    pub fn get_embedded_thumbnail(&self) -> Result<Option<Thumbnail>, PdfiumError> {
+        self.lib().get_embedded_thumbnail(self.handle)
+    }
+
+    pub fn get_decoded_thumbnail_data(&self) -> Result<Option<Vec<u8>>, PdfiumError> {
+        self.lib().get_decoded_thumbnail_data(self.handle)
+    }
+
+    pub fn get_raw_thumbnail_data(&self) -> Result<Option<Vec<u8>>, PdfiumError> {
+        self.lib().get_raw_thumbnail_data(self.handle)
+    }
+
+    /// Render the page into a reusable bitmap. See `PdfiumLibrary::render_page_to_bitmap`.
+    pub fn render_to_bitmap(&self, bitmap: &mut PdfBitmap<'_>, options: &RenderOptions) -> Result<(), PdfiumError> {
+        self.lib().render_page_to_bitmap(self.handle, bitmap, options)
+    }
+
+    /// Render the page into `buffer`. See `PdfiumLibrary::render_page_into`.
+    pub fn render_into(
+        &self,
+        width: i32,
+        height: i32,
+        options: &RenderOptions,
+        buffer: &mut [u8],
+        stride: usize,
+    ) -> Result<(), PdfiumError> {
+        self.lib().render_page_into(self.handle, width, height, options, buffer, stride)
+    }
+
+    /// Render the part of the page `matrix` maps into `clip`. See
+    /// `PdfiumLibrary::render_region`.
+    #[allow(clippy::too_many_arguments)]
+    pub fn render_region(
+        &self,
+        width: i32,
+        height: i32,
+        matrix: &Matrix,
+        clip: ClipRect,
+        options: &RenderOptions,
+        buffer: &mut [u8],
+        stride: usize,
+    ) -> Result<(), PdfiumError> {
+        self.lib()
+            .render_region(self.handle, width, height, matrix, clip, options, buffer, stride)
+    }
+
+    /// Render into `buffer`, stopping once `should_stop` returns true. See
+    /// `PdfiumLibrary::render_page_cancellable`.
+    pub fn render_cancellable(
+        &self,
+        width: i32,
+        height: i32,
+        options: &RenderOptions,
+        buffer: &mut [u8],
+        stride: usize,
+        should_stop: impl FnMut() -> bool,
+    ) -> Result<(), PdfiumError> {
+        self.lib()
+            .render_page_cancellable(self.handle, width, height, options, buffer, stride, should_stop)
+    }
+
+    /// Describe the page's deep-zoom tile pyramid. See `PdfiumLibrary::tile_pyramid`.
+    pub fn tile_pyramid(&self, tile_size: u32, max_zoom: f32, options: &RenderOptions) -> Result<TilePyramid<'_>, PdfiumError> {
+        self.lib().tile_pyramid(self.handle, tile_size, max_zoom, options)
+    }
+
+    // --- Links ---
+
+    pub fn get_links(&self) -> Result<Vec<LinkInfo>, PdfiumError> {
+        self.lib().get_links(self.handle, self.doc.handle)
+    }
+
+    // --- Annotations ---
+
+    pub fn get_annotations(&self) -> Result<Vec<AnnotationInfo>, PdfiumError> {
+        self.lib().get_annotations(self.handle)
+    }
+
+    pub fn create_annotation(&self, subtype: i32) -> Result<i32, PdfiumError> {
+        self.lib().create_annotation(self.handle, subtype)
+    }
+
+    pub fn remove_annotation(&self, index: i32) -> Result<bool, PdfiumError> {
+        self.lib().remove_annotation(self.handle, index)
+    }
+
+    pub fn set_annotation_rect(&self, index: i32, left: f32, top: f32, right: f32, bottom: f32) -> Result<bool, PdfiumError> {
+        self.lib().set_annotation_rect(self.handle, index, left, top, right, bottom)
+    }
+
+    /// `colour_type`: 0 = colour, 1 = interior colour.
+    pub fn set_annotation_colour(
+        &self,
+        index: i32,
+        colour_type: i32,
+        r: u32,
+        g: u32,
+        b: u32,
+        a: u32,
+    ) -> Result<bool, PdfiumError> {
+        self.lib().set_annotation_colour(self.handle, index, colour_type, r, g, b, a)
+    }
+
+    pub fn get_annotation_flags(&self, index: i32) -> Result<i32, PdfiumError> {
+        self.lib().get_annotation_flags(self.handle, index)
+    }
+
+    pub fn set_annotation_flags(&self, index: i32, flags: i32) -> Result<bool, PdfiumError> {
+        self.lib().set_annotation_flags(self.handle, index, flags)
+    }
+
+    pub fn set_annotation_string_value(&self, index: i32, key: &str, value: &str) -> Result<bool, PdfiumError> {
+        self.lib().set_annotation_string_value(self.handle, index, key, value)
+    }
+
+    pub fn set_annotation_border(
+        &self,
+        index: i32,
+        horizontal_radius: f32,
+        vertical_radius: f32,
+        border_width: f32,
+    ) -> Result<bool, PdfiumError> {
+        self.lib().set_annotation_border(self.handle, index, horizontal_radius, vertical_radius, border_width)
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    pub fn set_annotation_attachment_points(
+        &self,
+        annot_index: i32,
+        quad_index: usize,
+        x1: f32, y1: f32,
+        x2: f32, y2: f32,
+        x3: f32, y3: f32,
+        x4: f32, y4: f32,
+    ) -> Result<bool, PdfiumError> {
+        self.lib().set_annotation_attachment_points(
+            self.handle, annot_index, quad_index, x1, y1, x2, y2, x3, y3, x4, y4,
+        )
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    pub fn append_annotation_attachment_points(
+        &self,
+        annot_index: i32,
+        x1: f32, y1: f32,
+        x2: f32, y2: f32,
+        x3: f32, y3: f32,
+        x4: f32, y4: f32,
+    ) -> Result<bool, PdfiumError> {
+        self.lib().append_annotation_attachment_points(
+            self.handle, annot_index, x1, y1, x2, y2, x3, y3, x4, y4,
+        )
+    }
+
+    pub fn set_annotation_uri(&self, index: i32, uri: &str) -> Result<bool, PdfiumError> {
+        self.lib().set_annotation_uri(self.handle, index, uri)
+    }
+}
+
+impl Drop for Page<'_> {
+    fn drop(&mut self) {
+        let _ = self.lib().close_page(self.handle);
+    }
+}
+
+impl<'page> TextPage<'page> {
+    fn lib(&self) -> &'page PdfiumLibrary {
+        self.page.doc.lib
+    }
+
+    /// The page this text layer belongs to.
+    pub fn page(&self) -> &'page Page<'page> {
+        self.page
+    }
+
+    pub fn count_chars(&self) -> Result<i32, PdfiumError> {
+        self.lib().count_text_chars(self.handle)
+    }
+
+    pub fn get_full_text(&self) -> Result<String, PdfiumError> {
+        self.lib().get_full_text(self.handle)
+    }
+
+    // --- Character Font Info ---
+
+    pub fn get_char_font_size(&self, char_index: i32) -> Result<f64, PdfiumError> {
+        self.lib().get_char_font_size(self.handle, char_index)
+    }
+
+    pub fn get_char_font_weight(&self, char_index: i32) -> Result<i32, PdfiumError> {
+        self.lib().get_char_font_weight(self.handle, char_index)
+    }
+
+    /// Returns (fontName, flags) or None if unavailable.
+    pub fn get_char_font_info(&self, char_index: i32) -> Result<Option<(String, i32)>, PdfiumError> {
+        self.lib().get_char_font_info(self.handle, char_index)
+    }
+
+    pub fn get_char_render_mode(&self, char_index: i32) -> Result<i32, PdfiumError> {
+        self.lib().get_char_render_mode(self.handle, char_index)
+    }
+
+    // --- Character Extended Operations ---
+
+    pub fn get_char_unicode(&self, char_index: i32) -> Result<u32, PdfiumError> {
+        self.lib().get_char_unicode(self.handle, char_index)
+    }
+
+    pub fn is_char_generated(&self, char_index: i32) -> Result<bool, PdfiumError> {
+        self.lib().is_char_generated(self.handle, char_index)
+    }
+
+    pub fn is_char_hyphen(&self, char_index: i32) -> Result<bool, PdfiumError> {
+        self.lib().is_char_hyphen(self.handle, char_index)
+    }
+
+    pub fn has_char_unicode_map_error(&self, char_index: i32) -> Result<bool, PdfiumError> {
+        self.lib().has_char_unicode_map_error(self.handle, char_index)
+    }
+
+    pub fn get_char_angle(&self, char_index: i32) -> Result<f64, PdfiumError> {
+        self.lib().get_char_angle(self.handle, char_index)
+    }
+
+    pub fn get_char_origin(&self, char_index: i32) -> Result<Option<(f64, f64)>, PdfiumError> {
+        self.lib().get_char_origin(self.handle, char_index)
+    }
+
+    /// Returns (left, right, bottom, top) or None.
+    pub fn get_char_box(&self, char_index: i32) -> Result<Option<(f64, f64, f64, f64)>, PdfiumError> {
+        self.lib().get_char_box(self.handle, char_index)
+    }
+
+    /// Returns (left, top, right, bottom) or None.
+    pub fn get_char_loose_box(&self, char_index: i32) -> Result<Option<(f32, f32, f32, f32)>, PdfiumError> {
+        self.lib().get_char_loose_box(self.handle, char_index)
+    }
+
+    pub fn get_char_index_at_pos(&self, x: f64, y: f64, x_tolerance: f64, y_tolerance: f64) -> Result<i32, PdfiumError> {
+        self.lib().get_char_index_at_pos(self.handle, x, y, x_tolerance, y_tolerance)
+    }
+
+    pub fn get_char_fill_colour(&self, char_index: i32) -> Result<Option<(u32, u32, u32, u32)>, PdfiumError> {
+        self.lib().get_char_fill_colour(self.handle, char_index)
+    }
+
+    pub fn get_char_stroke_colour(&self, char_index: i32) -> Result<Option<(u32, u32, u32, u32)>, PdfiumError> {
+        self.lib().get_char_stroke_colour(self.handle, char_index)
+    }
+
+    pub fn get_char_matrix(&self, char_index: i32) -> Result<Option<[f64; 6]>, PdfiumError> {
+        self.lib().get_char_matrix(self.handle, char_index)
+    }
+
+    // --- Search / Rectangles ---
+
+    /// Find all matches of `query`. Returns (index, count) pairs.
+    pub fn find_text(&self, query: &str, flags: u32) -> Result<Vec<(i32, i32)>, PdfiumError> {
+        self.lib().find_text(self.handle, query, flags)
+    }
+
+    pub fn count_text_rects(&self, start_index: i32, count: i32) -> Result<i32, PdfiumError> {
+        self.lib().count_text_rects(self.handle, start_index, count)
+    }
+
+    /// Returns (left, top, right, bottom) or None.
+    pub fn get_text_rect(&self, rect_index: i32) -> Result<Option<(f64, f64, f64, f64)>, PdfiumError> {
+        self.lib().get_text_rect(self.handle, rect_index)
+    }
+
+    pub fn get_bounded_text(&self, left: f64, top: f64, right: f64, bottom: f64) -> Result<String, PdfiumError> {
+        self.lib().get_bounded_text(self.handle, left, top, right, bottom)
+    }
+}
+
+impl Drop for TextPage<'_> {
+    fn drop(&mut self) {
+        let _ = self.lib().close_text_page(self.handle);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{skip_if_no_lib, test_pdf_path};
+
+    #[test]
+    fn typed_api_round_trip() {
+        let path = skip_if_no_lib!();
+        let lib = PdfiumLibrary::load(&path).unwrap();
+        lib.init_library();
+
+        let pdf_data = std::fs::read(test_pdf_path()).expect("Failed to read test PDF");
+        {
+            let doc = lib.open_document(&pdf_data, None).unwrap();
+            assert!(doc.get_page_count().unwrap() > 0);
+
+            let page = doc.load_page(0).unwrap();
+            assert!(page.get_page_width().unwrap() > 0.0);
+
+            let text = page.load_text_page().unwrap();
+            let char_count = text.count_chars().unwrap();
+            assert_eq!(!text.get_full_text().unwrap().is_empty(), char_count > 0);
+        }
+
+        lib.destroy_library();
+    }
+
+    #[test]
+    fn open_without_copying() {
+        let path = skip_if_no_lib!();
+        let lib = PdfiumLibrary::load(&path).unwrap();
+        lib.init_library();
+
+        let pdf_data = std::fs::read(test_pdf_path()).expect("Failed to read test PDF");
+        let expected = lib.open_document(&pdf_data, None).unwrap().get_page_count().unwrap();
+
+        let owned = lib.open_document_from_buffer(std::sync::Arc::<[u8]>::from(pdf_data.clone()), None).unwrap();
+        assert_eq!(owned.get_page_count().unwrap(), expected);
+
+        let from_path = lib.open_document_from_path(test_pdf_path(), None).unwrap();
+        assert_eq!(from_path.get_page_count().unwrap(), expected);
+
+        let from_reader = lib.open_document_from_reader(std::fs::File::open(test_pdf_path()).unwrap(), None).unwrap();
+        assert_eq!(from_reader.get_page_count().unwrap(), expected);
+
+        drop((owned, from_path, from_reader));
+        lib.destroy_library();
+    }
+
+    #[test]
+    fn n_up_document_borrows_its_source() {
+        let path = skip_if_no_lib!();
+        let lib = PdfiumLibrary::load(&path).unwrap();
+        lib.init_library();
+
+        let pdf_data = std::fs::read(test_pdf_path()).expect("Failed to read test PDF");
+
+        // Closing the source first also closes the N-up document.
+        let src = lib.load_document(&pdf_data, None).unwrap();
+        let nup = lib.import_n_pages_to_one(src, 842.0, 595.0, 2, 1).unwrap();
+        lib.close_document(src).unwrap();
+        assert!(matches!(lib.get_page_count(nup), Err(PdfiumError::InvalidHandle { .. })));
+
+        // The typed API keeps the source open for as long as the result lives.
+        {
+            let src = lib.open_document(&pdf_data, None).unwrap();
+            let nup = src.import_n_pages_to_one(842.0, 595.0, 2, 1).unwrap();
+            assert!(nup.get_page_count().unwrap() > 0);
+            drop(nup);
+            assert!(src.get_page_count().unwrap() > 0);
+        }
+        assert_eq!(lib.live_handle_stats().documents, 0);
+
+        lib.destroy_library();
+    }
+
+    #[test]
+    fn drop_closes_handles() {
+        let path = skip_if_no_lib!();
+        let lib = PdfiumLibrary::load(&path).unwrap();
+        lib.init_library();
+
+        let pdf_data = std::fs::read(test_pdf_path()).expect("Failed to read test PDF");
+        let doc = lib.open_document(&pdf_data, None).unwrap();
+        let doc_handle = doc.handle;
+        let page_handle = {
+            let page = doc.load_page(0).unwrap();
+            page.handle
+        };
+
+        // Page was closed when it went out of scope
+        assert!(lib.get_page_width(page_handle).is_err());
+
+        drop(doc);
+        assert!(lib.get_page_count(doc_handle).is_err());
+
+        lib.destroy_library();
+    }
+}
@@ -0,0 +1,288 @@
+//! Encoding rendered pixels as PNG, JPEG or lossless WebP, so a thumbnail
+//! needs no separate image library. Only built with the `encode` feature.
+
+use crate::error::PdfiumError;
+use crate::library::PdfiumLibrary;
+use crate::render::{check_buffer, AlphaMode, PixelFormat, RenderOptions};
+use crate::tiles::Tile;
+
+/// File format produced by `render_page_encoded` and `Tile::encode`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ImageFormat {
+    Png,
+    /// Baseline JPEG at `quality` 1–100. Alpha is dropped.
+    Jpeg { quality: u8 },
+    WebpLossless,
+}
+
+impl ImageFormat {
+    /// Conventional file extension, e.g. for `TilePyramid::dzi_manifest`.
+    pub fn extension(self) -> &'static str {
+        match self {
+            ImageFormat::Png => "png",
+            ImageFormat::Jpeg { .. } => "jpg",
+            ImageFormat::WebpLossless => "webp",
+        }
+    }
+}
+
+impl PdfiumLibrary {
+    /// Render the page and encode it in `format`, e.g. for a thumbnail.
+    ///
+    /// The pixel format in `options` decides the encoded colour type (gray,
+    /// RGB or RGBA); alpha is always left straight. `dpi` is recorded in the
+    /// file as for `encode_image`.
+    ///
+    /// ```no_run
+    /// # use pdfium::{ImageFormat, PdfiumLibrary, RenderOptions};
+    /// # let lib = PdfiumLibrary::load("libpdfium.so")?;
+    /// # lib.init_library();
+    /// # let doc = lib.load_document_from_path("input.pdf", None)?;
+    /// # let page = lib.load_page(doc, 0)?;
+    /// let jpeg = lib.render_page_encoded(
+    ///     page,
+    ///     200,
+    ///     260,
+    ///     &RenderOptions::new(),
+    ///     ImageFormat::Jpeg { quality: 85 },
+    ///     None,
+    /// )?;
+    /// std::fs::write("thumbnail.jpg", jpeg)?;
+    /// # Ok::<(), Box<dyn std::error::Error>>(())
+    /// ```
+    pub fn render_page_encoded(
+        &self,
+        page_handle: u32,
+        width: i32,
+        height: i32,
+        options: &RenderOptions,
+        format: ImageFormat,
+        dpi: Option<f32>,
+    ) -> Result<Vec<u8>, PdfiumError> {
+        let options = options.alpha(AlphaMode::Straight);
+        let pixels = self.render_page_with(page_handle, width, height, &options)?;
+        let stride = options.get_format().min_stride(width);
+        encode_image(&pixels, width as u32, height as u32, stride, options.get_format(), format, dpi)
+    }
+}
+
+impl Tile {
+    /// Encode the tile in `format`, e.g. for a deep-zoom tile store.
+    pub fn encode(&self, format: ImageFormat) -> Result<Vec<u8>, PdfiumError> {
+        let stride = self.format.min_stride(self.width as i32);
+        encode_image(&self.data, self.width, self.height, stride, self.format, format, None)
+    }
+}
+
+/// Channel layout of the packed pixels handed to an encoder.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Channels {
+    Gray,
+    Rgb,
+    Rgba,
+}
+
+/// Encode a `width` x `height` image in `pixels`, rows `stride` apart.
+///
+/// `dpi` is stored where the container has room for it: the PNG `pHYs`
+/// chunk or the JPEG JFIF density. WebP has no such field, so it is ignored
+/// there. Alpha is taken as straight.
+pub fn encode_image(
+    data: &[u8],
+    width: u32,
+    height: u32,
+    stride: usize,
+    pixels: PixelFormat,
+    format: ImageFormat,
+    dpi: Option<f32>,
+) -> Result<Vec<u8>, PdfiumError> {
+    if width == 0 || height == 0 || width > i32::MAX as u32 || height > i32::MAX as u32 {
+        return Err(PdfiumError::InvalidArgument(format!("image size: {}x{}", width, height)));
+    }
+    check_buffer(pixels, width as i32, height as i32, stride, data.len())?;
+    if let Some(dpi) = dpi {
+        if !(dpi.is_finite() && dpi > 0.0) {
+            return Err(PdfiumError::InvalidArgument(format!("dpi: {}", dpi)));
+        }
+    }
+
+    let (packed, channels) = pack(data, width as usize, height as usize, stride, pixels);
+    match format {
+        ImageFormat::Png => encode_png(&packed, width, height, channels, dpi),
+        ImageFormat::Jpeg { quality } => encode_jpeg(&packed, width, height, channels, quality, dpi),
+        ImageFormat::WebpLossless => encode_webp(&packed, width, height, channels),
+    }
+}
+
+/// Copy the rows into a tight buffer of gray, RGB or RGBA bytes.
+fn pack(data: &[u8], width: usize, height: usize, stride: usize, pixels: PixelFormat) -> (Vec<u8>, Channels) {
+    let channels = match pixels {
+        PixelFormat::Gray8 => Channels::Gray,
+        PixelFormat::Bgr | PixelFormat::Bgrx => Channels::Rgb,
+        PixelFormat::Bgra | PixelFormat::Rgba => Channels::Rgba,
+    };
+    let bpp = pixels.bytes_per_pixel();
+    let mut packed = Vec::with_capacity(width * height * bpp);
+    for row in data.chunks(stride).take(height) {
+        let row = &row[..width * bpp];
+        match pixels {
+            PixelFormat::Gray8 | PixelFormat::Rgba => packed.extend_from_slice(row),
+            PixelFormat::Bgr | PixelFormat::Bgrx => {
+                for px in row.chunks_exact(bpp) {
+                    packed.extend_from_slice(&[px[2], px[1], px[0]]);
+                }
+            }
+            PixelFormat::Bgra => {
+                for px in row.chunks_exact(4) {
+                    packed.extend_from_slice(&[px[2], px[1], px[0], px[3]]);
+                }
+            }
+        }
+    }
+    (packed, channels)
+}
+
+fn encode_png(data: &[u8], width: u32, height: u32, channels: Channels, dpi: Option<f32>) -> Result<Vec<u8>, PdfiumError> {
+    let failed = |err: png::EncodingError| PdfiumError::Render(format!("PNG encoding failed: {}", err));
+    let mut out = Vec::new();
+    let mut encoder = png::Encoder::new(&mut out, width, height);
+    encoder.set_color(match channels {
+        Channels::Gray => png::ColorType::Grayscale,
+        Channels::Rgb => png::ColorType::Rgb,
+        Channels::Rgba => png::ColorType::Rgba,
+    });
+    encoder.set_depth(png::BitDepth::Eight);
+    if let Some(dpi) = dpi {
+        // pHYs counts pixels per metre.
+        let ppm = (dpi / 0.0254).round() as u32;
+        encoder.set_pixel_dims(Some(png::PixelDimensions {
+            xppu: ppm,
+            yppu: ppm,
+            unit: png::Unit::Meter,
+        }));
+    }
+    let mut writer = encoder.write_header().map_err(failed)?;
+    writer.write_image_data(data).map_err(failed)?;
+    writer.finish().map_err(failed)?;
+    Ok(out)
+}
+
+fn encode_jpeg(
+    data: &[u8],
+    width: u32,
+    height: u32,
+    channels: Channels,
+    quality: u8,
+    dpi: Option<f32>,
+) -> Result<Vec<u8>, PdfiumError> {
+    if !(1..=100).contains(&quality) {
+        return Err(PdfiumError::InvalidArgument(format!("JPEG quality: {}", quality)));
+    }
+    let (Ok(width), Ok(height)) = (u16::try_from(width), u16::try_from(height)) else {
+        return Err(PdfiumError::InvalidArgument(format!("JPEG size: {}x{}", width, height)));
+    };
+    let mut out = Vec::new();
+    let mut encoder = jpeg_encoder::Encoder::new(&mut out, quality);
+    if let Some(dpi) = dpi {
+        let dpi = dpi.round().clamp(1.0, u16::MAX as f32) as u16;
+        encoder.set_density(jpeg_encoder::Density::Inch { x: dpi, y: dpi });
+    }
+    let colour = match channels {
+        Channels::Gray => jpeg_encoder::ColorType::Luma,
+        Channels::Rgb => jpeg_encoder::ColorType::Rgb,
+        Channels::Rgba => jpeg_encoder::ColorType::Rgba,
+    };
+    encoder
+        .encode(data, width, height, colour)
+        .map_err(|err| PdfiumError::Render(format!("JPEG encoding failed: {}", err)))?;
+    Ok(out)
+}
+
+fn encode_webp(data: &[u8], width: u32, height: u32, channels: Channels) -> Result<Vec<u8>, PdfiumError> {
+    let mut out = Vec::new();
+    let colour = match channels {
+        Channels::Gray => image_webp::ColorType::L8,
+        Channels::Rgb => image_webp::ColorType::Rgb8,
+        Channels::Rgba => image_webp::ColorType::Rgba8,
+    };
+    image_webp::WebPEncoder::new(&mut out)
+        .encode(data, width, height, colour)
+        .map_err(|err| PdfiumError::Render(format!("WebP encoding failed: {}", err)))?;
+    Ok(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{skip_if_no_lib, test_pdf_path};
+
+    // 2x2 BGRA: red, green / blue, transparent, with 4 bytes of row padding.
+    const BGRA: [u8; 24] = [
+        0, 0, 255, 255, 0, 255, 0, 255, 0xAA, 0xAA, 0xAA, 0xAA, //
+        255, 0, 0, 255, 0, 0, 0, 0, 0xAA, 0xAA, 0xAA, 0xAA,
+    ];
+
+    #[test]
+    fn packing_drops_padding_and_swaps_to_rgb_order() {
+        let (packed, channels) = pack(&BGRA, 2, 2, 12, PixelFormat::Bgra);
+        assert_eq!(channels, Channels::Rgba);
+        assert_eq!(packed, [255, 0, 0, 255, 0, 255, 0, 255, 0, 0, 255, 255, 0, 0, 0, 0]);
+
+        let (packed, channels) = pack(&BGRA, 2, 2, 12, PixelFormat::Bgrx);
+        assert_eq!(channels, Channels::Rgb);
+        assert_eq!(packed, [255, 0, 0, 0, 255, 0, 0, 0, 255, 0, 0, 0]);
+    }
+
+    #[test]
+    fn each_format_writes_its_signature_and_density() {
+        let png = encode_image(&BGRA, 2, 2, 12, PixelFormat::Bgra, ImageFormat::Png, Some(300.0)).unwrap();
+        assert!(png.starts_with(b"\x89PNG\r\n\x1a\n"));
+        // 300 DPI = 11811 pixels per metre.
+        let phys = png.windows(4).position(|w| w == b"pHYs").unwrap();
+        assert_eq!(&png[phys + 4..phys + 8], &11811u32.to_be_bytes());
+
+        let jpeg = encode_image(&BGRA, 2, 2, 12, PixelFormat::Bgra, ImageFormat::Jpeg { quality: 80 }, Some(150.0))
+            .unwrap();
+        assert!(jpeg.starts_with(&[0xFF, 0xD8]));
+        let jfif = jpeg.windows(5).position(|w| w == b"JFIF\0").unwrap();
+        // Units (1 = dots per inch), then the X density.
+        assert_eq!(&jpeg[jfif + 7..jfif + 10], &[1, 0, 150]);
+
+        let webp = encode_image(&BGRA, 2, 2, 12, PixelFormat::Bgra, ImageFormat::WebpLossless, None).unwrap();
+        assert_eq!(&webp[..4], b"RIFF");
+        assert_eq!(&webp[8..12], b"WEBP");
+    }
+
+    #[test]
+    fn rendered_pages_and_tiles_encode() {
+        let path = skip_if_no_lib!();
+        let lib = PdfiumLibrary::load(&path).unwrap();
+        lib.init_library();
+
+        let doc = lib.load_document_from_path(test_pdf_path(), None).unwrap();
+        let page = lib.load_page(doc, 0).unwrap();
+        let gray = RenderOptions::new().format(PixelFormat::Gray8);
+        let png = lib.render_page_encoded(page, 100, 130, &gray, ImageFormat::Png, Some(72.0)).unwrap();
+        assert!(png.starts_with(b"\x89PNG"));
+        // IHDR: width, height, bit depth, then colour type 0 (grayscale).
+        assert_eq!(&png[16..26], &[0, 0, 0, 100, 0, 0, 0, 130, 8, 0]);
+
+        let pyramid = lib.tile_pyramid(page, 64, 0.25, &RenderOptions::new()).unwrap();
+        let tile = pyramid.render_tile(0, 0, 0).unwrap();
+        let webp = tile.encode(ImageFormat::WebpLossless).unwrap();
+        assert_eq!(&webp[8..12], b"WEBP");
+
+        lib.close_document(doc).unwrap();
+        lib.destroy_library();
+    }
+
+    #[test]
+    fn bad_arguments_are_rejected() {
+        let jpeg = |quality| ImageFormat::Jpeg { quality };
+        assert!(encode_image(&BGRA, 2, 2, 12, PixelFormat::Bgra, jpeg(0), None).is_err());
+        assert!(encode_image(&BGRA, 2, 2, 12, PixelFormat::Bgra, jpeg(101), None).is_err());
+        assert!(encode_image(&BGRA, 2, 2, 12, PixelFormat::Bgra, ImageFormat::Png, Some(0.0)).is_err());
+        assert!(encode_image(&BGRA, 2, 3, 12, PixelFormat::Bgra, ImageFormat::Png, None).is_err());
+        assert!(encode_image(&BGRA, 0, 2, 12, PixelFormat::Bgra, ImageFormat::Png, None).is_err());
+    }
+}
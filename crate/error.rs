@@ -0,0 +1,201 @@
+//! Structured error type for the PDFium wrapper.
+//!
+//! Document-level failures are decoded from `FPDF_GetLastError`; everything
+//! else (bad handles, missing symbols, failed renders) gets its own variant so
+//! callers can match on the cause instead of parsing messages.
+
+use std::fmt;
+
+// FPDF_GetLastError() codes (fpdfview.h)
+const FPDF_ERR_SUCCESS: u32 = 0;
+const FPDF_ERR_UNKNOWN: u32 = 1;
+const FPDF_ERR_FILE: u32 = 2;
+const FPDF_ERR_FORMAT: u32 = 3;
+const FPDF_ERR_PASSWORD: u32 = 4;
+const FPDF_ERR_SECURITY: u32 = 5;
+const FPDF_ERR_PAGE: u32 = 6;
+const FPDF_ERR_XFALOAD: u32 = 7;
+const FPDF_ERR_XFALAYOUT: u32 = 8;
+
+/// The kind of object a handle refers to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HandleKind {
+    Document,
+    Page,
+    TextPage,
+}
+
+impl fmt::Display for HandleKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            HandleKind::Document => "document",
+            HandleKind::Page => "page",
+            HandleKind::TextPage => "text page",
+        })
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum PdfiumError {
+    /// The shared library could not be opened.
+    LibraryLoad(String),
+    /// A method was called before `init_library` or after `destroy_library`.
+    NotInitialised,
+    /// A required PDFium export is missing from the loaded binary.
+    SymbolMissing(&'static str),
+    /// The handle does not refer to a live object.
+    InvalidHandle { expected: HandleKind },
+    /// The handle refers to a different kind of object.
+    WrongHandleKind { expected: HandleKind, actual: HandleKind },
+    /// The handle was valid once but has since been closed.
+    StaleHandle { kind: HandleKind },
+    /// FPDF_ERR_UNKNOWN, or a failure with no error code set.
+    Unknown,
+    /// FPDF_ERR_FILE: file not found or could not be opened.
+    File,
+    /// FPDF_ERR_FORMAT: not a PDF or corrupted.
+    Format,
+    /// FPDF_ERR_PASSWORD: password required or incorrect.
+    Password,
+    /// FPDF_ERR_SECURITY: unsupported security scheme.
+    Security,
+    /// FPDF_ERR_PAGE, or a page index that could not be loaded.
+    PageNotFound { index: Option<i32> },
+    /// FPDF_ERR_XFALOAD: XFA form could not be loaded.
+    XfaLoad,
+    /// FPDF_ERR_XFALAYOUT: XFA form layout failed.
+    XfaLayout,
+    /// FPDF_GetLastError returned a code this crate does not know about.
+    UnknownCode(u32),
+    /// Rendering failed (bitmap allocation or the render call itself).
+    Render(String),
+    /// FPDF_SaveAsCopy / FPDF_SaveWithVersion reported failure.
+    Save,
+    /// Reading or writing a caller-supplied stream or file failed.
+    Io(String),
+    /// A caller-supplied argument was rejected before reaching PDFium.
+    InvalidArgument(String),
+    /// PDFium returned text that could not be decoded.
+    Encoding(String),
+    /// Any other PDFium call that reported failure.
+    Failed(String),
+    /// The shared executor queue is full, or a non-blocking caller found it
+    /// in use.
+    Busy,
+    /// The caller stopped a cancellable operation before it finished.
+    Cancelled,
+}
+
+impl PdfiumError {
+    /// Decode an `FPDF_GetLastError` code.
+    pub fn from_error_code(code: u32) -> Self {
+        match code {
+            FPDF_ERR_SUCCESS | FPDF_ERR_UNKNOWN => PdfiumError::Unknown,
+            FPDF_ERR_FILE => PdfiumError::File,
+            FPDF_ERR_FORMAT => PdfiumError::Format,
+            FPDF_ERR_PASSWORD => PdfiumError::Password,
+            FPDF_ERR_SECURITY => PdfiumError::Security,
+            FPDF_ERR_PAGE => PdfiumError::PageNotFound { index: None },
+            FPDF_ERR_XFALOAD => PdfiumError::XfaLoad,
+            FPDF_ERR_XFALAYOUT => PdfiumError::XfaLayout,
+            other => PdfiumError::UnknownCode(other),
+        }
+    }
+
+    /// Stable, machine-readable identifier for this error, e.g. `ERR_PDFIUM_PASSWORD`.
+    pub fn code(&self) -> &'static str {
+        match self {
+            PdfiumError::LibraryLoad(_) => "ERR_PDFIUM_LIBRARY_LOAD",
+            PdfiumError::NotInitialised => "ERR_PDFIUM_NOT_INITIALISED",
+            PdfiumError::SymbolMissing(_) => "ERR_PDFIUM_SYMBOL_MISSING",
+            PdfiumError::InvalidHandle { .. } => "ERR_PDFIUM_INVALID_HANDLE",
+            PdfiumError::WrongHandleKind { .. } => "ERR_PDFIUM_WRONG_HANDLE_KIND",
+            PdfiumError::StaleHandle { .. } => "ERR_PDFIUM_STALE_HANDLE",
+            PdfiumError::Unknown | PdfiumError::UnknownCode(_) => "ERR_PDFIUM_UNKNOWN",
+            PdfiumError::File => "ERR_PDFIUM_FILE",
+            PdfiumError::Format => "ERR_PDFIUM_FORMAT",
+            PdfiumError::Password => "ERR_PDFIUM_PASSWORD",
+            PdfiumError::Security => "ERR_PDFIUM_SECURITY",
+            PdfiumError::PageNotFound { .. } => "ERR_PDFIUM_PAGE_NOT_FOUND",
+            PdfiumError::XfaLoad => "ERR_PDFIUM_XFA_LOAD",
+            PdfiumError::XfaLayout => "ERR_PDFIUM_XFA_LAYOUT",
+            PdfiumError::Render(_) => "ERR_PDFIUM_RENDER",
+            PdfiumError::Save => "ERR_PDFIUM_SAVE",
+            PdfiumError::Io(_) => "ERR_PDFIUM_IO",
+            PdfiumError::InvalidArgument(_) => "ERR_PDFIUM_INVALID_ARGUMENT",
+            PdfiumError::Encoding(_) => "ERR_PDFIUM_ENCODING",
+            PdfiumError::Failed(_) => "ERR_PDFIUM_FAILED",
+            PdfiumError::Busy => "ERR_PDFIUM_BUSY",
+            PdfiumError::Cancelled => "ERR_PDFIUM_CANCELLED",
+        }
+    }
+}
+
+impl fmt::Display for PdfiumError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PdfiumError::LibraryLoad(reason) => write!(f, "Failed to load library: {}", reason),
+            PdfiumError::NotInitialised => f.write_str("PDFium library is not initialised"),
+            PdfiumError::SymbolMissing(name) => write!(f, "Symbol {} is not available in this PDFium build", name),
+            PdfiumError::InvalidHandle { expected } => write!(f, "Invalid {} handle", expected),
+            PdfiumError::WrongHandleKind { expected, actual } => {
+                write!(f, "Handle is a {}, expected a {}", actual, expected)
+            }
+            PdfiumError::StaleHandle { kind } => write!(f, "The {} handle has already been closed", kind),
+            PdfiumError::Unknown => f.write_str("Unknown PDFium error"),
+            PdfiumError::File => f.write_str("File not found or could not be opened"),
+            PdfiumError::Format => f.write_str("File is not a PDF or is corrupted"),
+            PdfiumError::Password => f.write_str("Password required or incorrect"),
+            PdfiumError::Security => f.write_str("Unsupported security scheme"),
+            PdfiumError::PageNotFound { index: Some(index) } => write!(f, "Failed to load page {}", index),
+            PdfiumError::PageNotFound { index: None } => f.write_str("Page not found or content error"),
+            PdfiumError::XfaLoad => f.write_str("Failed to load XFA form"),
+            PdfiumError::XfaLayout => f.write_str("Failed to lay out XFA form"),
+            PdfiumError::UnknownCode(code) => write!(f, "Unknown PDFium error code: {}", code),
+            PdfiumError::Render(reason) => write!(f, "Render failed: {}", reason),
+            PdfiumError::Save => f.write_str("Failed to save document"),
+            PdfiumError::Io(reason) => write!(f, "I/O error: {}", reason),
+            PdfiumError::InvalidArgument(reason) => write!(f, "Invalid argument: {}", reason),
+            PdfiumError::Encoding(reason) => write!(f, "Decode error: {}", reason),
+            PdfiumError::Failed(reason) => f.write_str(reason),
+            PdfiumError::Busy => f.write_str("PDFium is busy"),
+            PdfiumError::Cancelled => f.write_str("Operation was cancelled"),
+        }
+    }
+}
+
+impl std::error::Error for PdfiumError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn decodes_last_error_codes() {
+        assert_eq!(PdfiumError::from_error_code(0), PdfiumError::Unknown);
+        assert_eq!(PdfiumError::from_error_code(2), PdfiumError::File);
+        assert_eq!(PdfiumError::from_error_code(3), PdfiumError::Format);
+        assert_eq!(PdfiumError::from_error_code(4), PdfiumError::Password);
+        assert_eq!(PdfiumError::from_error_code(5), PdfiumError::Security);
+        assert_eq!(PdfiumError::from_error_code(6), PdfiumError::PageNotFound { index: None });
+        assert_eq!(PdfiumError::from_error_code(42), PdfiumError::UnknownCode(42));
+    }
+
+    #[test]
+    fn codes_are_stable() {
+        assert_eq!(PdfiumError::Password.code(), "ERR_PDFIUM_PASSWORD");
+        assert_eq!(
+            PdfiumError::WrongHandleKind { expected: HandleKind::Page, actual: HandleKind::TextPage }.code(),
+            "ERR_PDFIUM_WRONG_HANDLE_KIND"
+        );
+        assert_eq!(PdfiumError::SymbolMissing("FPDF_LoadPage").code(), "ERR_PDFIUM_SYMBOL_MISSING");
+    }
+
+    #[test]
+    fn display_names_handle_kinds() {
+        let err = PdfiumError::WrongHandleKind { expected: HandleKind::Page, actual: HandleKind::TextPage };
+        assert_eq!(err.to_string(), "Handle is a text page, expected a page");
+        let err = PdfiumError::InvalidHandle { expected: HandleKind::Document };
+        assert_eq!(err.to_string(), "Invalid document handle");
+    }
+}
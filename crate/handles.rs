@@ -0,0 +1,502 @@
+//! Handle table mapping the `u32` handles given to callers onto PDFium pointers.
+//!
+//! Every entry records the handle that owns it (document → page → text page,
+//! and source document → N-up document), so closing a parent closes its
+//! descendants first instead of leaving them pointing at freed memory.
+//!
+//! Handles are generational: `[kind:2][generation:10][index:20]`. The kind tag
+//! catches a handle of the wrong type before the table is consulted, and the
+//! generation catches a handle whose slot has since been closed and reused. A
+//! slot whose generation would wrap is retired, and retired slots are only
+//! recycled (starting again at generation 0) once every index is in use and
+//! nothing else is free. Only then can a stale handle alias a live one, and
+//! only if it was issued exactly 1024 generations ago for that same slot.
+
+use crate::bindings::*;
+use crate::error::{HandleKind, PdfiumError};
+
+const INDEX_BITS: u32 = 20;
+const GENERATION_BITS: u32 = 10;
+const KIND_SHIFT: u32 = INDEX_BITS + GENERATION_BITS;
+const INDEX_MASK: u32 = (1 << INDEX_BITS) - 1;
+const GENERATION_MASK: u32 = (1 << GENERATION_BITS) - 1;
+const MAX_SLOTS: usize = 1 << INDEX_BITS;
+
+/// Handle map entry — maps a u32 handle to a raw pointer.
+#[derive(Clone, Copy)]
+pub(crate) enum HandleEntry {
+    Document(FPDF_DOCUMENT),
+    Page(FPDF_PAGE),
+    TextPage(FPDF_TEXTPAGE),
+}
+
+impl HandleEntry {
+    pub(crate) fn kind(&self) -> HandleKind {
+        match self {
+            HandleEntry::Document(_) => HandleKind::Document,
+            HandleEntry::Page(_) => HandleKind::Page,
+            HandleEntry::TextPage(_) => HandleKind::TextPage,
+        }
+    }
+}
+
+// Tag 0 is never issued, so 0 is never a valid handle.
+fn kind_tag(kind: HandleKind) -> u32 {
+    match kind {
+        HandleKind::Document => 1,
+        HandleKind::Page => 2,
+        HandleKind::TextPage => 3,
+    }
+}
+
+fn tag_kind(tag: u32) -> Option<HandleKind> {
+    match tag {
+        1 => Some(HandleKind::Document),
+        2 => Some(HandleKind::Page),
+        3 => Some(HandleKind::TextPage),
+        _ => None,
+    }
+}
+
+/// A decoded handle.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct RawHandle {
+    kind: HandleKind,
+    generation: u32,
+    index: usize,
+}
+
+impl RawHandle {
+    fn decode(handle: u32) -> Option<Self> {
+        Some(Self {
+            kind: tag_kind(handle >> KIND_SHIFT)?,
+            generation: (handle >> INDEX_BITS) & GENERATION_MASK,
+            index: (handle & INDEX_MASK) as usize,
+        })
+    }
+
+    fn encode(self) -> u32 {
+        (kind_tag(self.kind) << KIND_SHIFT) | (self.generation << INDEX_BITS) | self.index as u32
+    }
+}
+
+/// Counts of live handles by kind, for leak detection.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct HandleStats {
+    pub documents: u32,
+    pub pages: u32,
+    pub text_pages: u32,
+}
+
+impl HandleStats {
+    /// Total number of live handles.
+    pub fn total(&self) -> u32 {
+        self.documents + self.pages + self.text_pages
+    }
+}
+
+struct Entry {
+    entry: HandleEntry,
+    parent: Option<u32>,
+    children: Vec<u32>,
+    // Documents only: changed since it was loaded or last saved.
+    dirty: bool,
+}
+
+struct Slot {
+    generation: u32,
+    entry: Option<Entry>,
+}
+
+pub(crate) struct HandleTable {
+    slots: Vec<Slot>,
+    free: Vec<usize>,
+    // Slots whose generation wrapped, reused only when nothing else is free.
+    retired: Vec<usize>,
+    max_slots: usize,
+    stats: HandleStats,
+}
+
+impl HandleTable {
+    pub(crate) fn new() -> Self {
+        Self::with_max_slots(MAX_SLOTS)
+    }
+
+    fn with_max_slots(max_slots: usize) -> Self {
+        Self {
+            slots: Vec::new(),
+            free: Vec::new(),
+            retired: Vec::new(),
+            max_slots,
+            stats: HandleStats::default(),
+        }
+    }
+
+    /// Register a new entry owned by `parent`.
+    ///
+    /// Returns `PdfiumError::InvalidHandle` if `parent` is not live.
+    pub(crate) fn insert(&mut self, entry: HandleEntry, parent: Option<u32>) -> Result<u32, PdfiumError> {
+        if let Some(parent) = parent {
+            if self.entry(parent).is_none() {
+                let expected = match entry.kind() {
+                    HandleKind::TextPage => HandleKind::Page,
+                    HandleKind::Document | HandleKind::Page => HandleKind::Document,
+                };
+                return Err(PdfiumError::InvalidHandle { expected });
+            }
+        }
+
+        let index = match self.free.pop() {
+            Some(index) => index,
+            None if self.slots.len() < self.max_slots => {
+                self.slots.push(Slot { generation: 0, entry: None });
+                self.slots.len() - 1
+            }
+            None => match self.retired.pop() {
+                Some(index) => index,
+                None => return Err(PdfiumError::Failed("Too many open handles".to_string())),
+            },
+        };
+
+        let slot = &mut self.slots[index];
+        let handle = RawHandle { kind: entry.kind(), generation: slot.generation, index }.encode();
+        slot.entry = Some(Entry { entry, parent, children: Vec::new(), dirty: false });
+
+        if let Some(parent) = parent.and_then(|parent| self.entry_mut(parent)) {
+            parent.children.push(handle);
+        }
+        *self.count_mut(entry.kind()) += 1;
+        Ok(handle)
+    }
+
+    /// Look up a live handle of the expected kind.
+    pub(crate) fn get(&self, handle: u32, expected: HandleKind) -> Result<HandleEntry, PdfiumError> {
+        let raw = RawHandle::decode(handle).ok_or(PdfiumError::InvalidHandle { expected })?;
+        if raw.kind != expected {
+            return Err(PdfiumError::WrongHandleKind { expected, actual: raw.kind });
+        }
+        match self.slots.get(raw.index) {
+            Some(Slot { generation, entry: Some(entry) }) if *generation == raw.generation => Ok(entry.entry),
+            // The slot exists, so a handle for it was issued at some point.
+            Some(_) => Err(PdfiumError::StaleHandle { kind: raw.kind }),
+            None => Err(PdfiumError::InvalidHandle { expected }),
+        }
+    }
+
+    fn entry(&self, handle: u32) -> Option<&Entry> {
+        let raw = RawHandle::decode(handle)?;
+        let slot = self.slots.get(raw.index)?;
+        if slot.generation != raw.generation {
+            return None;
+        }
+        slot.entry.as_ref()
+    }
+
+    fn entry_mut(&mut self, handle: u32) -> Option<&mut Entry> {
+        let raw = RawHandle::decode(handle)?;
+        let slot = self.slots.get_mut(raw.index)?;
+        if slot.generation != raw.generation {
+            return None;
+        }
+        slot.entry.as_mut()
+    }
+
+    fn count_mut(&mut self, kind: HandleKind) -> &mut u32 {
+        match kind {
+            HandleKind::Document => &mut self.stats.documents,
+            HandleKind::Page => &mut self.stats.pages,
+            HandleKind::TextPage => &mut self.stats.text_pages,
+        }
+    }
+
+    /// Remove `handle` and everything it owns.
+    ///
+    /// Entries are returned in the order they must be closed: each child
+    /// before its parent, with `handle` itself last.
+    pub(crate) fn remove_tree(&mut self, handle: u32) -> Vec<(u32, HandleEntry)> {
+        let mut removed = Vec::new();
+        let Some(parent) = self.entry_mut(handle).map(|entry| entry.parent) else {
+            return removed;
+        };
+        if let Some(siblings) = parent.and_then(|p| self.entry_mut(p)) {
+            siblings.children.retain(|&child| child != handle);
+        }
+        self.remove_subtree(handle, &mut removed);
+        removed
+    }
+
+    fn remove_subtree(&mut self, handle: u32, removed: &mut Vec<(u32, HandleEntry)>) {
+        if self.entry_mut(handle).is_none() {
+            return;
+        }
+        let entry = self.release(handle);
+        // Newest children first, mirroring the order they were opened.
+        for child in entry.children.into_iter().rev() {
+            self.remove_subtree(child, removed);
+        }
+        *self.count_mut(entry.entry.kind()) -= 1;
+        removed.push((handle, entry.entry));
+    }
+
+    /// Empty a live handle's slot and bump its generation, retiring the slot
+    /// if the generation wraps.
+    fn release(&mut self, handle: u32) -> Entry {
+        let index = (handle & INDEX_MASK) as usize;
+        let slot = &mut self.slots[index];
+        let entry = slot.entry.take().expect("slot must be occupied");
+        slot.generation = (slot.generation + 1) & GENERATION_MASK;
+        if slot.generation == 0 {
+            self.retired.push(index);
+        } else {
+            self.free.push(index);
+        }
+        entry
+    }
+
+    /// The document `handle` belongs to: itself for a document, otherwise the
+    /// nearest document above it. An N-up document belongs to itself, not to
+    /// the document it was made from.
+    pub(crate) fn owning_document(&self, handle: u32) -> Option<u32> {
+        let mut current = handle;
+        loop {
+            let entry = self.entry(current)?;
+            if let HandleEntry::Document(_) = entry.entry {
+                return Some(current);
+            }
+            current = entry.parent?;
+        }
+    }
+
+    /// Set or clear the unsaved-changes flag of the document owning `handle`.
+    pub(crate) fn set_dirty(&mut self, handle: u32, dirty: bool) {
+        if let Some(entry) = self.owning_document(handle).and_then(|doc| self.entry_mut(doc)) {
+            entry.dirty = dirty;
+        }
+    }
+
+    pub(crate) fn is_dirty(&self, handle: u32) -> bool {
+        self.owning_document(handle)
+            .and_then(|doc| self.entry(doc))
+            .is_some_and(|entry| entry.dirty)
+    }
+
+    /// Live handles that have no owner, i.e. top-level documents.
+    pub(crate) fn roots(&self) -> Vec<u32> {
+        self.slots
+            .iter()
+            .enumerate()
+            .filter_map(|(index, slot)| {
+                let entry = slot.entry.as_ref()?;
+                let handle = RawHandle { kind: entry.entry.kind(), generation: slot.generation, index };
+                entry.parent.is_none().then(|| handle.encode())
+            })
+            .collect()
+    }
+
+    pub(crate) fn stats(&self) -> HandleStats {
+        self.stats
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::ptr;
+
+    fn doc() -> HandleEntry {
+        HandleEntry::Document(ptr::null_mut())
+    }
+
+    fn page() -> HandleEntry {
+        HandleEntry::Page(ptr::null_mut())
+    }
+
+    fn text_page() -> HandleEntry {
+        HandleEntry::TextPage(ptr::null_mut())
+    }
+
+    fn handles(removed: Vec<(u32, HandleEntry)>) -> Vec<u32> {
+        removed.into_iter().map(|(h, _)| h).collect()
+    }
+
+    #[test]
+    fn removing_a_document_closes_children_first() {
+        let mut table = HandleTable::new();
+        let d = table.insert(doc(), None).unwrap();
+        let p1 = table.insert(page(), Some(d)).unwrap();
+        let t1 = table.insert(text_page(), Some(p1)).unwrap();
+        let p2 = table.insert(page(), Some(d)).unwrap();
+        let nup = table.insert(doc(), Some(d)).unwrap();
+        let nup_page = table.insert(page(), Some(nup)).unwrap();
+
+        assert_eq!(handles(table.remove_tree(d)), vec![nup_page, nup, p2, t1, p1, d]);
+        assert_eq!(table.stats(), HandleStats::default());
+    }
+
+    #[test]
+    fn removing_a_child_detaches_it_from_its_parent() {
+        let mut table = HandleTable::new();
+        let d = table.insert(doc(), None).unwrap();
+        let p = table.insert(page(), Some(d)).unwrap();
+        let t = table.insert(text_page(), Some(p)).unwrap();
+
+        assert_eq!(handles(table.remove_tree(p)), vec![t, p]);
+        assert!(table.get(t, HandleKind::TextPage).is_err());
+        assert_eq!(handles(table.remove_tree(d)), vec![d]);
+    }
+
+    #[test]
+    fn dirty_flag_belongs_to_the_owning_document() {
+        let mut table = HandleTable::new();
+        let d = table.insert(doc(), None).unwrap();
+        let p = table.insert(page(), Some(d)).unwrap();
+        let t = table.insert(text_page(), Some(p)).unwrap();
+        let nup = table.insert(doc(), Some(d)).unwrap();
+
+        assert!(!table.is_dirty(d));
+        table.set_dirty(t, true);
+        assert!(table.is_dirty(d) && table.is_dirty(p));
+        assert!(!table.is_dirty(nup));
+
+        table.set_dirty(d, false);
+        table.set_dirty(nup, true);
+        assert!(!table.is_dirty(d));
+        assert!(table.is_dirty(nup));
+    }
+
+    #[test]
+    fn removing_an_unknown_handle_is_a_no_op() {
+        let mut table = HandleTable::new();
+        let d = table.insert(doc(), None).unwrap();
+        assert!(table.remove_tree(d + 1).is_empty());
+        assert_eq!(table.remove_tree(d).len(), 1);
+        assert!(table.remove_tree(d).is_empty());
+    }
+
+    #[test]
+    fn wrong_kind_is_detected_from_the_tag() {
+        let mut table = HandleTable::new();
+        let d = table.insert(doc(), None).unwrap();
+        let p = table.insert(page(), Some(d)).unwrap();
+        let t = table.insert(text_page(), Some(p)).unwrap();
+        let wrong_kind = Some(PdfiumError::WrongHandleKind { expected: HandleKind::Page, actual: HandleKind::TextPage });
+
+        assert!(table.get(p, HandleKind::Page).is_ok());
+        assert_eq!(table.get(t, HandleKind::Page).err(), wrong_kind);
+
+        // Still detected once the handle is stale.
+        table.remove_tree(d);
+        assert_eq!(table.get(t, HandleKind::Page).err(), wrong_kind);
+    }
+
+    #[test]
+    fn reused_slots_reject_stale_handles() {
+        let mut table = HandleTable::new();
+        let old = table.insert(doc(), None).unwrap();
+        table.remove_tree(old);
+        let new = table.insert(doc(), None).unwrap();
+
+        assert_ne!(old, new);
+        assert_eq!(RawHandle::decode(old).unwrap().index, RawHandle::decode(new).unwrap().index);
+        assert_eq!(
+            table.get(old, HandleKind::Document).err(),
+            Some(PdfiumError::StaleHandle { kind: HandleKind::Document })
+        );
+        assert!(table.get(new, HandleKind::Document).is_ok());
+    }
+
+    #[test]
+    fn slots_are_retired_before_generation_wraps() {
+        let mut table = HandleTable::new();
+        let first = table.insert(doc(), None).unwrap();
+        table.remove_tree(first);
+        for _ in 0..GENERATION_MASK {
+            let h = table.insert(doc(), None).unwrap();
+            assert_eq!(RawHandle::decode(h).unwrap().index, 0);
+            table.remove_tree(h);
+        }
+
+        // Slot 0 has used every generation, so a fresh slot is allocated.
+        let h = table.insert(doc(), None).unwrap();
+        assert_eq!(RawHandle::decode(h).unwrap().index, 1);
+        assert_eq!(
+            table.get(first, HandleKind::Document).err(),
+            Some(PdfiumError::StaleHandle { kind: HandleKind::Document })
+        );
+    }
+
+    #[test]
+    fn retired_slots_are_recycled_once_the_table_is_full() {
+        let mut table = HandleTable::with_max_slots(2);
+        let first = table.insert(doc(), None).unwrap();
+        table.remove_tree(first);
+        for _ in 0..GENERATION_MASK {
+            let h = table.insert(doc(), None).unwrap();
+            table.remove_tree(h);
+        }
+
+        // Slot 1 is still unused, so it is preferred over retired slot 0.
+        let live = table.insert(doc(), None).unwrap();
+        assert_eq!(RawHandle::decode(live).unwrap().index, 1);
+
+        // With every slot allocated, retired slot 0 restarts at generation 0.
+        let recycled = table.insert(doc(), None).unwrap();
+        assert_eq!(recycled, first);
+        assert!(matches!(table.insert(doc(), None), Err(PdfiumError::Failed(_))));
+    }
+
+    #[test]
+    fn dead_parents_are_rejected() {
+        let mut table = HandleTable::new();
+        let d = table.insert(doc(), None).unwrap();
+        let p = table.insert(page(), Some(d)).unwrap();
+        table.remove_tree(d);
+
+        assert_eq!(
+            table.insert(page(), Some(d)).err(),
+            Some(PdfiumError::InvalidHandle { expected: HandleKind::Document })
+        );
+        assert_eq!(
+            table.insert(text_page(), Some(p)).err(),
+            Some(PdfiumError::InvalidHandle { expected: HandleKind::Page })
+        );
+        assert_eq!(table.stats(), HandleStats::default());
+    }
+
+    #[test]
+    fn invalid_handles_are_rejected() {
+        let table = HandleTable::new();
+        let invalid = Some(PdfiumError::InvalidHandle { expected: HandleKind::Page });
+        assert_eq!(table.get(0, HandleKind::Page).err(), invalid);
+        let never_issued = RawHandle { kind: HandleKind::Page, generation: 0, index: 7 }.encode();
+        assert_eq!(table.get(never_issued, HandleKind::Page).err(), invalid);
+    }
+
+    #[test]
+    fn roots_are_unowned_handles() {
+        let mut table = HandleTable::new();
+        let d1 = table.insert(doc(), None).unwrap();
+        let p = table.insert(page(), Some(d1)).unwrap();
+        table.insert(doc(), Some(d1)).unwrap();
+        let d2 = table.insert(doc(), None).unwrap();
+        table.insert(text_page(), Some(p)).unwrap();
+
+        assert_eq!(table.roots(), vec![d1, d2]);
+    }
+
+    #[test]
+    fn stats_track_live_handles() {
+        let mut table = HandleTable::new();
+        let d = table.insert(doc(), None).unwrap();
+        let p = table.insert(page(), Some(d)).unwrap();
+        table.insert(text_page(), Some(p)).unwrap();
+        table.insert(page(), Some(d)).unwrap();
+
+        let stats = table.stats();
+        assert_eq!(stats, HandleStats { documents: 1, pages: 2, text_pages: 1 });
+        assert_eq!(stats.total(), 4);
+
+        table.remove_tree(p);
+        assert_eq!(table.stats(), HandleStats { documents: 1, pages: 1, text_pages: 0 });
+    }
+}
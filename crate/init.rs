@@ -0,0 +1,325 @@
+//! Library initialisation: `FPDF_InitLibraryWithConfig` options and the
+//! per-binary reference count that pairs it with `FPDF_DestroyLibrary`.
+
+use crate::error::PdfiumError;
+use std::ffi::CString;
+use std::os::raw::c_char;
+use std::path::{Path, PathBuf};
+use std::ptr;
+use std::sync::{Mutex, PoisonError};
+
+// FPDF_RENDERER_TYPE (fpdfview.h)
+const FPDF_RENDERERTYPE_AGG: i32 = 0;
+const FPDF_RENDERERTYPE_SKIA: i32 = 1;
+
+/// Rasteriser used for rendering.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RendererType {
+    Agg,
+    /// Only available in PDFium builds compiled with Skia support.
+    Skia,
+}
+
+impl RendererType {
+    pub(crate) fn to_raw(self) -> i32 {
+        match self {
+            RendererType::Agg => FPDF_RENDERERTYPE_AGG,
+            RendererType::Skia => FPDF_RENDERERTYPE_SKIA,
+        }
+    }
+
+    /// Decode an `FPDF_RENDERER_TYPE` value.
+    pub fn from_raw(value: i32) -> Result<Self, PdfiumError> {
+        match value {
+            FPDF_RENDERERTYPE_AGG => Ok(RendererType::Agg),
+            FPDF_RENDERERTYPE_SKIA => Ok(RendererType::Skia),
+            other => Err(PdfiumError::InvalidArgument(format!("renderer type: {}", other))),
+        }
+    }
+}
+
+/// Settings passed to PDFium when the library is initialised.
+///
+/// ```no_run
+/// # use pdfium::{InitOptions, PdfiumLibrary, RendererType};
+/// let lib = PdfiumLibrary::load("libpdfium.so")?;
+/// let options = InitOptions::new()
+///     .font_path("/opt/fonts")
+///     .renderer(RendererType::Agg);
+/// lib.init_library_with(&options)?;
+/// # Ok::<(), pdfium::PdfiumError>(())
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct InitOptions {
+    font_paths: Vec<PathBuf>,
+    renderer: Option<RendererType>,
+}
+
+impl InitOptions {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a directory to search for fonts, in addition to the system ones.
+    pub fn font_path(mut self, path: impl Into<PathBuf>) -> Self {
+        self.font_paths.push(path.into());
+        self
+    }
+
+    /// Add several font directories.
+    pub fn font_paths<I, P>(mut self, paths: I) -> Self
+    where
+        I: IntoIterator<Item = P>,
+        P: Into<PathBuf>,
+    {
+        self.font_paths.extend(paths.into_iter().map(Into::into));
+        self
+    }
+
+    /// Select the renderer. Left unset, PDFium uses its build default.
+    pub fn renderer(mut self, renderer: RendererType) -> Self {
+        self.renderer = Some(renderer);
+        self
+    }
+
+    pub fn get_font_paths(&self) -> &[PathBuf] {
+        &self.font_paths
+    }
+
+    pub fn get_renderer(&self) -> Option<RendererType> {
+        self.renderer
+    }
+}
+
+/// NUL-terminated array of font path C strings, in the layout
+/// `m_pUserFontPaths` expects.
+pub(crate) struct FontPathList {
+    // Owns the strings `pointers` refers to.
+    _strings: Vec<CString>,
+    pointers: Vec<*const c_char>,
+}
+
+impl FontPathList {
+    pub(crate) fn new(paths: &[PathBuf]) -> Result<Self, PdfiumError> {
+        let strings = paths
+            .iter()
+            .map(|path| path_to_cstring(path))
+            .collect::<Result<Vec<_>, _>>()?;
+        let mut pointers: Vec<*const c_char> = strings.iter().map(|s| s.as_ptr()).collect();
+        pointers.push(ptr::null());
+        Ok(Self {
+            _strings: strings,
+            pointers,
+        })
+    }
+
+    /// Pointer for `m_pUserFontPaths`, or null when there are no paths.
+    pub(crate) fn as_ptr(&self) -> *const *const c_char {
+        if self.pointers.len() == 1 {
+            ptr::null()
+        } else {
+            self.pointers.as_ptr()
+        }
+    }
+}
+
+// SAFETY: The pointers refer into `_strings`, which moves with the list.
+unsafe impl Send for FontPathList {}
+
+fn path_to_cstring(path: &Path) -> Result<CString, PdfiumError> {
+    let invalid = || PdfiumError::InvalidArgument(format!("font path: {}", path.display()));
+    let utf8 = path.to_str().ok_or_else(invalid)?;
+    CString::new(utf8).map_err(|_| invalid())
+}
+
+/// PDFium's state belongs to the loaded binary, so every `PdfiumLibrary`
+/// backed by the same binary shares one reference count: the first init
+/// initialises PDFium and the last release destroys it. Distinct binaries
+/// (say, two differently built copies of PDFium) are counted separately.
+pub(crate) static PDFIUM_INIT: InitRefCount = InitRefCount::new();
+
+/// Identifies a loaded PDFium binary, e.g. by the address of one of its
+/// exported functions.
+pub(crate) type BinaryKey = usize;
+
+struct InitState {
+    refs: usize,
+    // The settings PDFium was initialised with.
+    options: InitOptions,
+    // Held until PDFium is destroyed so the pointers it was given stay valid.
+    _font_paths: FontPathList,
+}
+
+pub(crate) struct InitRefCount {
+    state: Mutex<Vec<(BinaryKey, InitState)>>,
+}
+
+impl InitRefCount {
+    pub(crate) const fn new() -> Self {
+        Self { state: Mutex::new(Vec::new()) }
+    }
+
+    /// Take a reference on `binary`, calling `init` if it is not yet
+    /// initialised.
+    ///
+    /// `options` of `None` accept whatever settings the binary is already
+    /// running with (or the defaults, if `init` runs). Otherwise they must
+    /// match the active settings: PDFium cannot be reconfigured without being
+    /// destroyed, so differing options are an error rather than silently
+    /// ignored. Returns whether `init` ran.
+    pub(crate) fn retain(
+        &self,
+        binary: BinaryKey,
+        options: Option<&InitOptions>,
+        init: impl FnOnce(&InitOptions, &FontPathList),
+    ) -> Result<bool, PdfiumError> {
+        let mut states = self.state.lock().unwrap_or_else(PoisonError::into_inner);
+        if let Some((_, state)) = states.iter_mut().find(|(key, _)| *key == binary) {
+            check_options(&state.options, options)?;
+            state.refs += 1;
+            return Ok(false);
+        }
+
+        let options = options.cloned().unwrap_or_default();
+        let font_paths = FontPathList::new(options.get_font_paths())?;
+        init(&options, &font_paths);
+        states.push((binary, InitState { refs: 1, options, _font_paths: font_paths }));
+        Ok(true)
+    }
+
+    /// Check `options` against the settings `binary` is running with, without
+    /// taking a reference.
+    pub(crate) fn check(&self, binary: BinaryKey, options: Option<&InitOptions>) -> Result<(), PdfiumError> {
+        let states = self.state.lock().unwrap_or_else(PoisonError::into_inner);
+        match states.iter().find(|(key, _)| *key == binary) {
+            Some((_, state)) => check_options(&state.options, options),
+            None => Ok(()),
+        }
+    }
+
+    /// Drop a reference on `binary`, calling `destroy` if it was the last one.
+    pub(crate) fn release(&self, binary: BinaryKey, destroy: impl FnOnce()) {
+        let mut states = self.state.lock().unwrap_or_else(PoisonError::into_inner);
+        let position = states
+            .iter()
+            .position(|(key, _)| *key == binary)
+            .expect("release without matching retain");
+        let state = &mut states[position].1;
+        state.refs -= 1;
+        if state.refs == 0 {
+            destroy();
+            states.swap_remove(position);
+        }
+    }
+}
+
+fn check_options(active: &InitOptions, requested: Option<&InitOptions>) -> Result<(), PdfiumError> {
+    match requested {
+        Some(requested) if requested != active => Err(PdfiumError::InvalidArgument(format!(
+            "PDFium is already initialised with different options ({:?})",
+            active
+        ))),
+        _ => Ok(()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::ffi::CStr;
+
+    #[test]
+    fn builder_collects_settings() {
+        let options = InitOptions::new()
+            .font_path("/opt/fonts")
+            .font_paths(["/a", "/b"])
+            .renderer(RendererType::Skia);
+        assert_eq!(
+            options.get_font_paths(),
+            &[PathBuf::from("/opt/fonts"), PathBuf::from("/a"), PathBuf::from("/b")]
+        );
+        assert_eq!(options.get_renderer(), Some(RendererType::Skia));
+        assert_eq!(InitOptions::new().get_renderer(), None);
+    }
+
+    #[test]
+    fn font_path_list_is_null_terminated() {
+        let list = FontPathList::new(&[PathBuf::from("/opt/fonts"), PathBuf::from("/usr/local/fonts")]).unwrap();
+        let ptrs = unsafe { std::slice::from_raw_parts(list.as_ptr(), 3) };
+        assert_eq!(unsafe { CStr::from_ptr(ptrs[0]) }.to_str().unwrap(), "/opt/fonts");
+        assert_eq!(unsafe { CStr::from_ptr(ptrs[1]) }.to_str().unwrap(), "/usr/local/fonts");
+        assert!(ptrs[2].is_null());
+
+        assert!(FontPathList::new(&[]).unwrap().as_ptr().is_null());
+    }
+
+    #[test]
+    fn font_path_with_nul_is_rejected() {
+        let err = FontPathList::new(&[PathBuf::from("/bad\0path")]).err().unwrap();
+        assert!(matches!(err, PdfiumError::InvalidArgument(_)));
+    }
+
+    #[test]
+    fn init_ref_count_inits_once_and_destroys_once() {
+        let refs = InitRefCount::new();
+        let inits = std::cell::Cell::new(0);
+        let destroys = std::cell::Cell::new(0);
+
+        assert_eq!(refs.retain(1, None, |_, _| inits.set(inits.get() + 1)), Ok(true));
+        assert_eq!(refs.retain(1, None, |_, _| inits.set(inits.get() + 1)), Ok(false));
+        assert_eq!(inits.get(), 1);
+
+        refs.release(1, || destroys.set(destroys.get() + 1));
+        assert_eq!(destroys.get(), 0);
+        refs.release(1, || destroys.set(destroys.get() + 1));
+        assert_eq!(destroys.get(), 1);
+
+        // A later init starts a fresh cycle.
+        assert_eq!(refs.retain(1, None, |_, _| inits.set(inits.get() + 1)), Ok(true));
+        assert_eq!(inits.get(), 2);
+    }
+
+    #[test]
+    fn init_ref_count_is_per_binary() {
+        let refs = InitRefCount::new();
+        let destroyed = std::cell::RefCell::new(Vec::new());
+
+        assert_eq!(refs.retain(1, None, |_, _| ()), Ok(true));
+        assert_eq!(refs.retain(2, None, |_, _| ()), Ok(true));
+        refs.release(2, || destroyed.borrow_mut().push(2));
+        assert_eq!(*destroyed.borrow(), vec![2]);
+        refs.release(1, || destroyed.borrow_mut().push(1));
+        assert_eq!(*destroyed.borrow(), vec![2, 1]);
+    }
+
+    #[test]
+    fn differing_options_are_rejected() {
+        let refs = InitRefCount::new();
+        let fonts = InitOptions::new().font_path("/opt/fonts");
+
+        assert_eq!(refs.retain(1, Some(&fonts), |options, _| assert_eq!(options, &fonts)), Ok(true));
+        assert_eq!(refs.retain(1, Some(&fonts.clone()), |_, _| unreachable!()), Ok(false));
+        assert_eq!(refs.retain(1, None, |_, _| unreachable!()), Ok(false));
+        assert!(matches!(
+            refs.retain(1, Some(&InitOptions::new()), |_, _| unreachable!()),
+            Err(PdfiumError::InvalidArgument(_))
+        ));
+        assert!(refs.check(1, Some(&fonts)).is_ok());
+        assert!(refs.check(1, Some(&InitOptions::new())).is_err());
+        assert!(refs.check(2, Some(&InitOptions::new())).is_ok());
+
+        // The rejected caller did not take a reference.
+        let destroys = std::cell::Cell::new(0);
+        for _ in 0..3 {
+            refs.release(1, || destroys.set(destroys.get() + 1));
+        }
+        assert_eq!(destroys.get(), 1);
+    }
+
+    #[test]
+    fn renderer_round_trips_through_raw_value() {
+        assert_eq!(RendererType::from_raw(RendererType::Skia.to_raw()), Ok(RendererType::Skia));
+        assert_eq!(RendererType::from_raw(0), Ok(RendererType::Agg));
+        assert!(RendererType::from_raw(7).is_err());
+    }
+}
@@ -1,10 +1,64 @@
 mod bindings;
+mod bitmap;
+mod document;
+#[cfg(feature = "encode")]
+mod encode;
+mod error;
+mod handles;
+mod init;
 mod library;
+mod pause;
+mod progressive;
+mod reader;
+mod render;
+mod save;
+mod shared;
+mod symbols;
+mod tiles;
+mod writer;
 
 #[cfg(feature = "napi")]
 mod napi_bindings;
 
+#[cfg(test)]
+mod test_support;
+
+pub use bitmap::PdfBitmap;
+pub use document::Document;
+pub use document::Page;
+pub use document::TextPage;
+#[cfg(feature = "encode")]
+pub use encode::encode_image;
+#[cfg(feature = "encode")]
+pub use encode::ImageFormat;
+pub use error::HandleKind;
+pub use error::PdfiumError;
+pub use handles::HandleStats;
+pub use init::InitOptions;
+pub use init::RendererType;
 pub use library::AnnotationInfo;
 pub use library::BookmarkNode;
 pub use library::LinkInfo;
 pub use library::PdfiumLibrary;
+pub use library::Thumbnail;
+pub use library::ThumbnailSource;
+pub use progressive::ProgressiveLoader;
+pub use reader::DataAvailability;
+pub use render::AlphaMode;
+pub use render::ClipRect;
+pub use render::ColorScheme;
+pub use render::Matrix;
+pub use render::PageBox;
+pub use render::PixelFormat;
+pub use render::RenderFlags;
+pub use render::RenderedImage;
+pub use render::RenderOptions;
+pub use save::PdfVersion;
+pub use save::SaveMode;
+pub use save::SaveOptions;
+pub use shared::SharedPdfium;
+pub use shared::DEFAULT_QUEUE_CAPACITY;
+pub use symbols::Capabilities;
+pub use tiles::PyramidLevel;
+pub use tiles::Tile;
+pub use tiles::TilePyramid;
@@ -4,20 +4,29 @@
 //! safe wrappers around the raw FFI function pointers.
 
 use crate::bindings::*;
+use crate::bitmap::BitmapSymbols;
+use crate::error::{HandleKind, PdfiumError};
+use crate::handles::{HandleEntry, HandleStats, HandleTable};
+use crate::init::{BinaryKey, InitOptions, RendererType, PDFIUM_INIT};
+use crate::progressive::AvailSymbols;
+use crate::pause::PauseContext;
+use crate::reader::ReaderAccess;
+use crate::render::{
+    check_buffer, display_rect, downscale_rgba, from_rgba, premultiply, to_rgba, AlphaMode, ClipRect, Matrix, PageBox, PixelFormat,
+    RenderOptions, RenderedImage,
+};
+use crate::save::SaveOptions;
+use crate::writer::{write_atomically, WriterContext};
+use crate::symbols::{Capabilities, LazySymbol};
 use libloading::{Library, Symbol};
+use std::cell::{Cell, RefCell};
 use std::collections::HashMap;
 use std::ffi::CString;
+use std::io::{Read, Seek, Write};
 use std::os::raw::{c_char, c_int, c_uint, c_ulong, c_void};
 use std::path::Path;
 use std::ptr;
 
-/// Handle map entry — maps a u32 handle to a raw pointer.
-enum HandleEntry {
-    Document(FPDF_DOCUMENT),
-    Page(FPDF_PAGE),
-    TextPage(FPDF_TEXTPAGE),
-}
-
 /// A node in the bookmark (outline) tree.
 pub struct BookmarkNode {
     pub title: String,
@@ -66,10 +75,56 @@ pub struct LinkInfo {
     pub zoom: f32,
 }
 
+/// Where `thumbnail` took its image from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ThumbnailSource {
+    /// The page's embedded `/Thumb` image.
+    Embedded,
+    /// A scaled-down render of the page.
+    Rendered,
+}
+
+/// A page preview as tightly packed RGBA.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Thumbnail {
+    pub width: u32,
+    pub height: u32,
+    pub data: Vec<u8>,
+    pub source: ThumbnailSource,
+}
+
+/// How many times larger than requested an embedded thumbnail may be and
+/// still be shrunk to fit; larger ones give way to a render.
+const MAX_THUMBNAIL_SHRINK: u32 = 4;
+
+/// The size to show a `width` x `height` embedded thumbnail at so it fits
+/// `max_edge`, or `None` if it would have to shrink more than
+/// `MAX_THUMBNAIL_SHRINK` times.
+fn embedded_thumbnail_size(width: u32, height: u32, max_edge: u32) -> Option<(u32, u32)> {
+    let long_edge = width.max(height);
+    if long_edge <= max_edge {
+        return Some((width, height));
+    }
+    if long_edge > max_edge.saturating_mul(MAX_THUMBNAIL_SHRINK) {
+        return None;
+    }
+    let scale = max_edge as f64 / long_edge as f64;
+    let fit = |edge: u32| ((edge as f64 * scale).round() as u32).clamp(1, max_edge);
+    Some((fit(width), fit(height)))
+}
+
+/// What a render draws into.
+pub(crate) enum RenderTarget<'a> {
+    /// The caller's pixels, wrapped in a bitmap for the duration of the render.
+    Buffer { buffer: &'a mut [u8], stride: usize },
+    /// A bitmap that owns its pixels, such as a `PdfBitmap`'s.
+    Bitmap(FPDF_BITMAP),
+}
+
 /// Loaded PDFium library with dynamically resolved function pointers.
 #[allow(dead_code)]
 pub struct PdfiumLibrary {
-    _lib: Library,
+    lib: Library,
 
     // Core
     init_library_with_config: Symbol<'static, FnInitLibraryWithConfig>,
@@ -78,6 +133,9 @@ pub struct PdfiumLibrary {
 
     // Document
     load_mem_document: Symbol<'static, FnLoadMemDocument>,
+    load_mem_document64: LazySymbol<FnLoadMemDocument64>,
+    load_document_file: Symbol<'static, FnLoadDocument>,
+    load_custom_document: Symbol<'static, FnLoadCustomDocument>,
     close_document: Symbol<'static, FnCloseDocument>,
     get_page_count: Symbol<'static, FnGetPageCount>,
 
@@ -94,10 +152,10 @@ pub struct PdfiumLibrary {
     text_get_text: Symbol<'static, FnTextGetText>,
 
     // Text character font info
-    text_get_font_size: Symbol<'static, FnTextGetFontSize>,
-    text_get_font_weight: Symbol<'static, FnTextGetFontWeight>,
-    text_get_font_info: Symbol<'static, FnTextGetFontInfo>,
-    text_get_text_render_mode: Option<Symbol<'static, FnTextGetTextRenderMode>>,
+    text_get_font_size: LazySymbol<FnTextGetFontSize>,
+    text_get_font_weight: LazySymbol<FnTextGetFontWeight>,
+    text_get_font_info: LazySymbol<FnTextGetFontInfo>,
+    text_get_text_render_mode: LazySymbol<FnTextGetTextRenderMode>,
 
     // Bitmap / Render
     bitmap_create_ex: Symbol<'static, FnBitmapCreateEx>,
@@ -106,6 +164,37 @@ pub struct PdfiumLibrary {
     bitmap_get_buffer: Symbol<'static, FnBitmapGetBuffer>,
     bitmap_get_stride: Symbol<'static, FnBitmapGetStride>,
     render_page_bitmap: Symbol<'static, FnRenderPageBitmap>,
+    render_page_skia: LazySymbol<FnRenderPageSkia>,
+    render_page_bitmap_with_matrix: LazySymbol<FnRenderPageBitmapWithMatrix>,
+    bitmap_get_width: LazySymbol<FnBitmapGetWidth>,
+    bitmap_get_height: LazySymbol<FnBitmapGetHeight>,
+    bitmap_get_format: LazySymbol<FnBitmapGetFormat>,
+
+    // Page objects
+    create_new_document: LazySymbol<FnCreateNewDocument>,
+    page_count_objects: LazySymbol<FnPageCountObjects>,
+    page_get_object: LazySymbol<FnPageGetObject>,
+    page_remove_object: LazySymbol<FnPageRemoveObject>,
+    page_obj_destroy: LazySymbol<FnPageObjDestroy>,
+    page_obj_get_type: LazySymbol<FnPageObjGetType>,
+    image_obj_get_rendered_bitmap: LazySymbol<FnImageObjGetRenderedBitmap>,
+    text_obj_get_rendered_bitmap: LazySymbol<FnTextObjGetRenderedBitmap>,
+
+    // Thumbnails
+    page_get_decoded_thumbnail_data: LazySymbol<FnPageGetThumbnailData>,
+    page_get_raw_thumbnail_data: LazySymbol<FnPageGetThumbnailData>,
+    page_get_thumbnail_as_bitmap: LazySymbol<FnPageGetThumbnailAsBitmap>,
+    render_page_bitmap_start: LazySymbol<FnRenderPageBitmapStart>,
+    render_page_continue: LazySymbol<FnRenderPageContinue>,
+    render_page_close: LazySymbol<FnRenderPageClose>,
+    render_page_bitmap_with_color_scheme_start: LazySymbol<FnRenderPageBitmapWithColorSchemeStart>,
+
+    // Form fill
+    init_form_fill_environment: LazySymbol<FnInitFormFillEnvironment>,
+    exit_form_fill_environment: LazySymbol<FnExitFormFillEnvironment>,
+    ffl_draw: LazySymbol<FnFFLDraw>,
+    set_form_field_highlight_color: LazySymbol<FnSetFormFieldHighlightColor>,
+    set_form_field_highlight_alpha: LazySymbol<FnSetFormFieldHighlightAlpha>,
 
     // Metadata / Document info
     get_meta_text: Symbol<'static, FnGetMetaText>,
@@ -118,175 +207,195 @@ pub struct PdfiumLibrary {
     get_page_label: Symbol<'static, FnGetPageLabel>,
 
     // Page boxes
-    page_get_media_box: Symbol<'static, FnPageGetBox>,
-    page_get_crop_box: Symbol<'static, FnPageGetBox>,
-    page_get_bleed_box: Symbol<'static, FnPageGetBox>,
-    page_get_trim_box: Symbol<'static, FnPageGetBox>,
-    page_get_art_box: Symbol<'static, FnPageGetBox>,
-    page_set_media_box: Symbol<'static, FnPageSetBox>,
-    page_set_crop_box: Symbol<'static, FnPageSetBox>,
-    page_set_bleed_box: Symbol<'static, FnPageSetBox>,
-    page_set_trim_box: Symbol<'static, FnPageSetBox>,
-    page_set_art_box: Symbol<'static, FnPageSetBox>,
+    page_get_media_box: LazySymbol<FnPageGetBox>,
+    page_get_crop_box: LazySymbol<FnPageGetBox>,
+    page_get_bleed_box: LazySymbol<FnPageGetBox>,
+    page_get_trim_box: LazySymbol<FnPageGetBox>,
+    page_get_art_box: LazySymbol<FnPageGetBox>,
+    page_set_media_box: LazySymbol<FnPageSetBox>,
+    page_set_crop_box: LazySymbol<FnPageSetBox>,
+    page_set_bleed_box: LazySymbol<FnPageSetBox>,
+    page_set_trim_box: LazySymbol<FnPageSetBox>,
+    page_set_art_box: LazySymbol<FnPageSetBox>,
 
     // Signatures
-    get_signature_count: Symbol<'static, FnGetSignatureCount>,
-    get_signature_object: Symbol<'static, FnGetSignatureObject>,
-    signature_get_contents: Symbol<'static, FnSignatureGetContents>,
-    signature_get_byte_range: Symbol<'static, FnSignatureGetByteRange>,
-    signature_get_sub_filter: Symbol<'static, FnSignatureGetSubFilter>,
-    signature_get_reason: Symbol<'static, FnSignatureGetReason>,
-    signature_get_time: Symbol<'static, FnSignatureGetTime>,
-    signature_get_doc_mdp_permission: Symbol<'static, FnSignatureGetDocMDPPermission>,
+    get_signature_count: LazySymbol<FnGetSignatureCount>,
+    get_signature_object: LazySymbol<FnGetSignatureObject>,
+    signature_get_contents: LazySymbol<FnSignatureGetContents>,
+    signature_get_byte_range: LazySymbol<FnSignatureGetByteRange>,
+    signature_get_sub_filter: LazySymbol<FnSignatureGetSubFilter>,
+    signature_get_reason: LazySymbol<FnSignatureGetReason>,
+    signature_get_time: LazySymbol<FnSignatureGetTime>,
+    signature_get_doc_mdp_permission: LazySymbol<FnSignatureGetDocMDPPermission>,
 
     // Attachments
-    doc_get_attachment_count: Symbol<'static, FnDocGetAttachmentCount>,
-    doc_get_attachment: Symbol<'static, FnDocGetAttachment>,
-    attachment_get_name: Symbol<'static, FnAttachmentGetName>,
-    attachment_get_file: Symbol<'static, FnAttachmentGetFile>,
+    doc_get_attachment_count: LazySymbol<FnDocGetAttachmentCount>,
+    doc_get_attachment: LazySymbol<FnDocGetAttachment>,
+    attachment_get_name: LazySymbol<FnAttachmentGetName>,
+    attachment_get_file: LazySymbol<FnAttachmentGetFile>,
 
     // Page import
-    import_pages: Symbol<'static, FnImportPages>,
-    import_pages_by_index: Symbol<'static, FnImportPagesByIndex>,
-    import_n_pages_to_one: Symbol<'static, FnImportNPagesToOne>,
-    copy_viewer_preferences: Symbol<'static, FnCopyViewerPreferences>,
+    import_pages: LazySymbol<FnImportPages>,
+    import_pages_by_index: LazySymbol<FnImportPagesByIndex>,
+    import_n_pages_to_one: LazySymbol<FnImportNPagesToOne>,
+    copy_viewer_preferences: LazySymbol<FnCopyViewerPreferences>,
 
     // Bookmarks / Links
-    bookmark_get_first_child: Symbol<'static, FnBookmarkGetFirstChild>,
-    bookmark_get_next_sibling: Symbol<'static, FnBookmarkGetNextSibling>,
-    bookmark_get_title: Symbol<'static, FnBookmarkGetTitle>,
-    bookmark_get_count: Symbol<'static, FnBookmarkGetCount>,
-    bookmark_get_dest: Symbol<'static, FnBookmarkGetDest>,
-    bookmark_get_action: Symbol<'static, FnBookmarkGetAction>,
-    dest_get_dest_page_index: Symbol<'static, FnDestGetDestPageIndex>,
-    action_get_type: Symbol<'static, FnActionGetType>,
+    bookmark_get_first_child: LazySymbol<FnBookmarkGetFirstChild>,
+    bookmark_get_next_sibling: LazySymbol<FnBookmarkGetNextSibling>,
+    bookmark_get_title: LazySymbol<FnBookmarkGetTitle>,
+    bookmark_get_count: LazySymbol<FnBookmarkGetCount>,
+    bookmark_get_dest: LazySymbol<FnBookmarkGetDest>,
+    bookmark_get_action: LazySymbol<FnBookmarkGetAction>,
+    dest_get_dest_page_index: LazySymbol<FnDestGetDestPageIndex>,
+    action_get_type: LazySymbol<FnActionGetType>,
 
     // Links
-    link_enumerate: Symbol<'static, FnLinkEnumerate>,
-    link_get_annot_rect: Symbol<'static, FnLinkGetAnnotRect>,
-    link_get_action: Symbol<'static, FnLinkGetAction>,
-    link_get_dest: Symbol<'static, FnLinkGetDest>,
-    action_get_dest: Symbol<'static, FnActionGetDest>,
-    action_get_uri_path: Symbol<'static, FnActionGetURIPath>,
-    action_get_file_path: Symbol<'static, FnActionGetFilePath>,
-    dest_get_view: Symbol<'static, FnDestGetView>,
-    dest_get_location_in_page: Symbol<'static, FnDestGetLocationInPage>,
+    link_enumerate: LazySymbol<FnLinkEnumerate>,
+    link_get_annot_rect: LazySymbol<FnLinkGetAnnotRect>,
+    link_get_action: LazySymbol<FnLinkGetAction>,
+    link_get_dest: LazySymbol<FnLinkGetDest>,
+    action_get_dest: LazySymbol<FnActionGetDest>,
+    action_get_uri_path: LazySymbol<FnActionGetURIPath>,
+    action_get_file_path: LazySymbol<FnActionGetFilePath>,
+    dest_get_view: LazySymbol<FnDestGetView>,
+    dest_get_location_in_page: LazySymbol<FnDestGetLocationInPage>,
 
     // Annotations (read)
-    page_get_annot_count: Symbol<'static, FnPageGetAnnotCount>,
-    page_get_annot: Symbol<'static, FnPageGetAnnot>,
-    page_close_annot: Symbol<'static, FnPageCloseAnnot>,
-    annot_get_subtype: Symbol<'static, FnAnnotGetSubtype>,
-    annot_get_rect: Symbol<'static, FnAnnotGetRect>,
-    annot_get_color: Symbol<'static, FnAnnotGetColor>,
+    page_get_annot_count: LazySymbol<FnPageGetAnnotCount>,
+    page_get_annot: LazySymbol<FnPageGetAnnot>,
+    page_close_annot: LazySymbol<FnPageCloseAnnot>,
+    annot_get_subtype: LazySymbol<FnAnnotGetSubtype>,
+    annot_get_rect: LazySymbol<FnAnnotGetRect>,
+    annot_get_color: LazySymbol<FnAnnotGetColor>,
 
     // Annotations (mutation)
-    page_create_annot: Symbol<'static, FnPageCreateAnnot>,
-    page_remove_annot: Symbol<'static, FnPageRemoveAnnot>,
-    annot_set_rect: Symbol<'static, FnAnnotSetRect>,
-    annot_set_color: Symbol<'static, FnAnnotSetColor>,
-    annot_get_flags: Symbol<'static, FnAnnotGetFlags>,
-    annot_set_flags: Symbol<'static, FnAnnotSetFlags>,
-    annot_set_string_value: Symbol<'static, FnAnnotSetStringValue>,
-    annot_set_border: Symbol<'static, FnAnnotSetBorder>,
-    annot_set_attachment_points: Symbol<'static, FnAnnotSetAttachmentPoints>,
-    annot_append_attachment_points: Symbol<'static, FnAnnotAppendAttachmentPoints>,
-    annot_set_uri: Symbol<'static, FnAnnotSetURI>,
+    page_create_annot: LazySymbol<FnPageCreateAnnot>,
+    page_remove_annot: LazySymbol<FnPageRemoveAnnot>,
+    annot_set_rect: LazySymbol<FnAnnotSetRect>,
+    annot_set_color: LazySymbol<FnAnnotSetColor>,
+    annot_get_flags: LazySymbol<FnAnnotGetFlags>,
+    annot_set_flags: LazySymbol<FnAnnotSetFlags>,
+    annot_set_string_value: LazySymbol<FnAnnotSetStringValue>,
+    annot_set_border: LazySymbol<FnAnnotSetBorder>,
+    annot_set_attachment_points: LazySymbol<FnAnnotSetAttachmentPoints>,
+    annot_append_attachment_points: LazySymbol<FnAnnotAppendAttachmentPoints>,
+    annot_set_uri: LazySymbol<FnAnnotSetURI>,
 
     // Text character extended operations
-    text_get_unicode: Symbol<'static, FnTextGetUnicode>,
-    text_is_generated: Symbol<'static, FnTextIsGenerated>,
-    text_is_hyphen: Symbol<'static, FnTextIsHyphen>,
-    text_has_unicode_map_error: Symbol<'static, FnTextHasUnicodeMapError>,
-    text_get_char_angle: Symbol<'static, FnTextGetCharAngle>,
-    text_get_char_origin: Symbol<'static, FnTextGetCharOrigin>,
-    text_get_char_box: Symbol<'static, FnTextGetCharBox>,
-    text_get_loose_char_box: Symbol<'static, FnTextGetLooseCharBox>,
-    text_get_char_index_at_pos: Symbol<'static, FnTextGetCharIndexAtPos>,
-    text_get_fill_color: Symbol<'static, FnTextGetFillColor>,
-    text_get_stroke_color: Symbol<'static, FnTextGetStrokeColor>,
-    text_get_matrix: Symbol<'static, FnTextGetMatrix>,
+    text_get_unicode: LazySymbol<FnTextGetUnicode>,
+    text_is_generated: LazySymbol<FnTextIsGenerated>,
+    text_is_hyphen: LazySymbol<FnTextIsHyphen>,
+    text_has_unicode_map_error: LazySymbol<FnTextHasUnicodeMapError>,
+    text_get_char_angle: LazySymbol<FnTextGetCharAngle>,
+    text_get_char_origin: LazySymbol<FnTextGetCharOrigin>,
+    text_get_char_box: LazySymbol<FnTextGetCharBox>,
+    text_get_loose_char_box: LazySymbol<FnTextGetLooseCharBox>,
+    text_get_char_index_at_pos: LazySymbol<FnTextGetCharIndexAtPos>,
+    text_get_fill_color: LazySymbol<FnTextGetFillColor>,
+    text_get_stroke_color: LazySymbol<FnTextGetStrokeColor>,
+    text_get_matrix: LazySymbol<FnTextGetMatrix>,
 
     // Text search
-    text_find_start: Symbol<'static, FnTextFindStart>,
-    text_find_next: Symbol<'static, FnTextFindNext>,
-    text_find_prev: Symbol<'static, FnTextFindPrev>,
-    text_find_close: Symbol<'static, FnTextFindClose>,
-    text_get_sch_result_index: Symbol<'static, FnTextGetSchResultIndex>,
-    text_get_sch_count: Symbol<'static, FnTextGetSchCount>,
+    text_find_start: LazySymbol<FnTextFindStart>,
+    text_find_next: LazySymbol<FnTextFindNext>,
+    text_find_prev: LazySymbol<FnTextFindPrev>,
+    text_find_close: LazySymbol<FnTextFindClose>,
+    text_get_sch_result_index: LazySymbol<FnTextGetSchResultIndex>,
+    text_get_sch_count: LazySymbol<FnTextGetSchCount>,
 
     // Text rectangle / bounded text
-    text_count_rects: Symbol<'static, FnTextCountRects>,
-    text_get_rect: Symbol<'static, FnTextGetRect>,
-    text_get_bounded_text: Symbol<'static, FnTextGetBoundedText>,
+    text_count_rects: LazySymbol<FnTextCountRects>,
+    text_get_rect: LazySymbol<FnTextGetRect>,
+    text_get_bounded_text: LazySymbol<FnTextGetBoundedText>,
 
     // Page operations (rotation, flatten, transparency, content)
-    page_get_rotation: Symbol<'static, FnPageGetRotation>,
-    page_set_rotation: Symbol<'static, FnPageSetRotation>,
-    page_has_transparency: Symbol<'static, FnPageHasTransparency>,
-    page_flatten: Symbol<'static, FnPageFlatten>,
-    page_generate_content: Symbol<'static, FnPageGenerateContent>,
+    page_get_rotation: LazySymbol<FnPageGetRotation>,
+    page_set_rotation: LazySymbol<FnPageSetRotation>,
+    page_has_transparency: LazySymbol<FnPageHasTransparency>,
+    page_flatten: LazySymbol<FnPageFlatten>,
+    page_generate_content: LazySymbol<FnPageGenerateContent>,
+
+    // Progressive loading
+    avail_create: LazySymbol<FnAvailCreate>,
+    avail_destroy: LazySymbol<FnAvailDestroy>,
+    avail_is_doc_avail: LazySymbol<FnAvailIsDocAvail>,
+    avail_get_document: LazySymbol<FnAvailGetDocument>,
+    avail_get_first_page_num: LazySymbol<FnAvailGetFirstPageNum>,
+    avail_is_page_avail: LazySymbol<FnAvailIsPageAvail>,
+    avail_is_linearized: LazySymbol<FnAvailIsLinearized>,
 
     // Coordinate conversion
-    device_to_page: Symbol<'static, FnDeviceToPage>,
-    page_to_device: Symbol<'static, FnPageToDevice>,
+    device_to_page: LazySymbol<FnDeviceToPage>,
+    page_to_device: LazySymbol<FnPageToDevice>,
 
     // Save / export
     save_as_copy: Symbol<'static, FnSaveAsCopy>,
     save_with_version: Symbol<'static, FnSaveWithVersion>,
 
-    // Handle management
-    handles: HashMap<u32, HandleEntry>,
-    next_handle: u32,
+    // Whether this instance holds a reference on PDFium's global init
+    initialised: Cell<bool>,
+
+    // Handle management (interior mutability so typed wrappers can share `&self`)
+    handles: RefCell<HandleTable>,
+
+    // Whatever a document reads from (an owned buffer or a reader), kept
+    // alive until the document is closed
+    doc_sources: RefCell<HashMap<u32, Box<dyn Send>>>,
 
-    // Keep document data alive while documents are open
-    doc_data: HashMap<u32, Vec<u8>>,
+    // Form-fill environments, created per document on first use
+    forms: RefCell<HashMap<u32, FormFill>>,
+
+    // Index each page handle was loaded from
+    page_indices: RefCell<HashMap<u32, i32>>,
 }
 
-macro_rules! load_sym {
-    ($lib:expr, $name:literal, $ty:ty) => {{
-        let sym: Symbol<'_, $ty> = unsafe { $lib.get($name.as_bytes()) }
-            .map_err(|e| format!("Failed to load symbol {}: {}", $name, e))?;
-        // SAFETY: The library outlives the symbols because _lib is stored in the struct.
-        // We transmute to erase the borrow lifetime since _lib is stored in the same struct.
-        unsafe { std::mem::transmute(sym) }
-    }};
+/// A document's form-fill environment. PDFium keeps the info pointer, so it
+/// is boxed and lives as long as the handle.
+struct FormFill {
+    handle: FPDF_FORMHANDLE,
+    _info: Box<FPDF_FORMFILLINFO>,
 }
 
-macro_rules! try_load_sym {
-    ($lib:expr, $name:literal, $ty:ty) => {{
-        let result: std::result::Result<Symbol<'_, $ty>, _> = unsafe { $lib.get($name.as_bytes()) };
-        result.ok().map(|sym| unsafe { std::mem::transmute::<Symbol<'_, $ty>, Symbol<'static, $ty>>(sym) })
-    }};
+// SAFETY: The raw pointers in `handles` are only ever passed back to PDFium,
+// which has no thread affinity. Moving the library to another thread is sound
+// as long as calls are never made concurrently; the `RefCell`/`Cell` fields
+// keep it `!Sync`, so that is enforced by the type system (see `SharedPdfium`).
+unsafe impl Send for PdfiumLibrary {}
+
+impl Drop for PdfiumLibrary {
+    fn drop(&mut self) {
+        self.destroy_library();
+    }
 }
 
-/// C-compatible callback for FPDF_FILEWRITE.WriteBlock.
-/// Appends data to the Vec<u8> embedded in the SaveContext.
-unsafe extern "C" fn write_block_callback(
-    p_this: *mut SaveContext,
-    p_data: *const c_void,
-    size: c_ulong,
-) -> c_int {
-    if p_this.is_null() || p_data.is_null() || size == 0 {
-        return 1; // success (nothing to write)
-    }
-    let ctx = &mut *p_this;
-    let data = std::slice::from_raw_parts(p_data as *const u8, size as usize);
-    ctx.buffer.extend_from_slice(data);
-    1 // success
+macro_rules! load_sym {
+    ($lib:expr, $name:literal, $ty:ty) => {{
+        let sym: Symbol<'_, $ty> = unsafe { $lib.get($name.as_bytes()) }
+            .map_err(|_| PdfiumError::SymbolMissing($name))?;
+        // SAFETY: The library outlives the symbols because lib is stored in the struct.
+        // We transmute to erase the borrow lifetime since lib is stored in the same struct.
+        unsafe { std::mem::transmute::<Symbol<'_, $ty>, Symbol<'static, $ty>>(sym) }
+    }};
 }
 
 impl PdfiumLibrary {
     /// Load the PDFium shared library from the given path.
-    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self, String> {
+    ///
+    /// Only the core exports are required. Optional ones are resolved on
+    /// first use; see `capabilities()`.
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self, PdfiumError> {
         let lib = unsafe { Library::new(path.as_ref()) }
-            .map_err(|e| format!("Failed to load library: {}", e))?;
+            .map_err(|e| PdfiumError::LibraryLoad(e.to_string()))?;
 
         let library = Self {
             init_library_with_config: load_sym!(lib, "FPDF_InitLibraryWithConfig", FnInitLibraryWithConfig),
             destroy_library: load_sym!(lib, "FPDF_DestroyLibrary", FnDestroyLibrary),
             get_last_error: load_sym!(lib, "FPDF_GetLastError", FnGetLastError),
             load_mem_document: load_sym!(lib, "FPDF_LoadMemDocument", FnLoadMemDocument),
+            load_mem_document64: LazySymbol::new("FPDF_LoadMemDocument64"),
+            load_document_file: load_sym!(lib, "FPDF_LoadDocument", FnLoadDocument),
+            load_custom_document: load_sym!(lib, "FPDF_LoadCustomDocument", FnLoadCustomDocument),
             close_document: load_sym!(lib, "FPDF_CloseDocument", FnCloseDocument),
             get_page_count: load_sym!(lib, "FPDF_GetPageCount", FnGetPageCount),
             load_page: load_sym!(lib, "FPDF_LoadPage", FnLoadPage),
@@ -297,16 +406,43 @@ impl PdfiumLibrary {
             text_close_page: load_sym!(lib, "FPDFText_ClosePage", FnTextClosePage),
             text_count_chars: load_sym!(lib, "FPDFText_CountChars", FnTextCountChars),
             text_get_text: load_sym!(lib, "FPDFText_GetText", FnTextGetText),
-            text_get_font_size: load_sym!(lib, "FPDFText_GetFontSize", FnTextGetFontSize),
-            text_get_font_weight: load_sym!(lib, "FPDFText_GetFontWeight", FnTextGetFontWeight),
-            text_get_font_info: load_sym!(lib, "FPDFText_GetFontInfo", FnTextGetFontInfo),
-            text_get_text_render_mode: try_load_sym!(lib, "FPDFText_GetTextRenderMode", FnTextGetTextRenderMode),
+            text_get_font_size: LazySymbol::new("FPDFText_GetFontSize"),
+            text_get_font_weight: LazySymbol::new("FPDFText_GetFontWeight"),
+            text_get_font_info: LazySymbol::new("FPDFText_GetFontInfo"),
+            text_get_text_render_mode: LazySymbol::new("FPDFText_GetTextRenderMode"),
             bitmap_create_ex: load_sym!(lib, "FPDFBitmap_CreateEx", FnBitmapCreateEx),
             bitmap_fill_rect: load_sym!(lib, "FPDFBitmap_FillRect", FnBitmapFillRect),
             bitmap_destroy: load_sym!(lib, "FPDFBitmap_Destroy", FnBitmapDestroy),
             bitmap_get_buffer: load_sym!(lib, "FPDFBitmap_GetBuffer", FnBitmapGetBuffer),
             bitmap_get_stride: load_sym!(lib, "FPDFBitmap_GetStride", FnBitmapGetStride),
             render_page_bitmap: load_sym!(lib, "FPDF_RenderPageBitmap", FnRenderPageBitmap),
+            render_page_skia: LazySymbol::new("FPDF_RenderPageSkia"),
+            render_page_bitmap_with_matrix: LazySymbol::new("FPDF_RenderPageBitmapWithMatrix"),
+            bitmap_get_width: LazySymbol::new("FPDFBitmap_GetWidth"),
+            bitmap_get_height: LazySymbol::new("FPDFBitmap_GetHeight"),
+            bitmap_get_format: LazySymbol::new("FPDFBitmap_GetFormat"),
+            create_new_document: LazySymbol::new("FPDF_CreateNewDocument"),
+            page_count_objects: LazySymbol::new("FPDFPage_CountObjects"),
+            page_get_object: LazySymbol::new("FPDFPage_GetObject"),
+            page_remove_object: LazySymbol::new("FPDFPage_RemoveObject"),
+            page_obj_destroy: LazySymbol::new("FPDFPageObj_Destroy"),
+            page_obj_get_type: LazySymbol::new("FPDFPageObj_GetType"),
+            image_obj_get_rendered_bitmap: LazySymbol::new("FPDFImageObj_GetRenderedBitmap"),
+            text_obj_get_rendered_bitmap: LazySymbol::new("FPDFTextObj_GetRenderedBitmap"),
+            page_get_decoded_thumbnail_data: LazySymbol::new("FPDFPage_GetDecodedThumbnailData"),
+            page_get_raw_thumbnail_data: LazySymbol::new("FPDFPage_GetRawThumbnailData"),
+            page_get_thumbnail_as_bitmap: LazySymbol::new("FPDFPage_GetThumbnailAsBitmap"),
+            render_page_bitmap_start: LazySymbol::new("FPDF_RenderPageBitmap_Start"),
+            render_page_continue: LazySymbol::new("FPDF_RenderPage_Continue"),
+            render_page_close: LazySymbol::new("FPDF_RenderPage_Close"),
+            render_page_bitmap_with_color_scheme_start: LazySymbol::new(
+                "FPDF_RenderPageBitmapWithColorScheme_Start",
+            ),
+            init_form_fill_environment: LazySymbol::new("FPDFDOC_InitFormFillEnvironment"),
+            exit_form_fill_environment: LazySymbol::new("FPDFDOC_ExitFormFillEnvironment"),
+            ffl_draw: LazySymbol::new("FPDF_FFLDraw"),
+            set_form_field_highlight_color: LazySymbol::new("FPDF_SetFormFieldHighlightColor"),
+            set_form_field_highlight_alpha: LazySymbol::new("FPDF_SetFormFieldHighlightAlpha"),
 
             // Metadata / Document info
             get_meta_text: load_sym!(lib, "FPDF_GetMetaText", FnGetMetaText),
@@ -319,154 +455,492 @@ impl PdfiumLibrary {
             get_page_label: load_sym!(lib, "FPDF_GetPageLabel", FnGetPageLabel),
 
             // Page boxes
-            page_get_media_box: load_sym!(lib, "FPDFPage_GetMediaBox", FnPageGetBox),
-            page_get_crop_box: load_sym!(lib, "FPDFPage_GetCropBox", FnPageGetBox),
-            page_get_bleed_box: load_sym!(lib, "FPDFPage_GetBleedBox", FnPageGetBox),
-            page_get_trim_box: load_sym!(lib, "FPDFPage_GetTrimBox", FnPageGetBox),
-            page_get_art_box: load_sym!(lib, "FPDFPage_GetArtBox", FnPageGetBox),
-            page_set_media_box: load_sym!(lib, "FPDFPage_SetMediaBox", FnPageSetBox),
-            page_set_crop_box: load_sym!(lib, "FPDFPage_SetCropBox", FnPageSetBox),
-            page_set_bleed_box: load_sym!(lib, "FPDFPage_SetBleedBox", FnPageSetBox),
-            page_set_trim_box: load_sym!(lib, "FPDFPage_SetTrimBox", FnPageSetBox),
-            page_set_art_box: load_sym!(lib, "FPDFPage_SetArtBox", FnPageSetBox),
+            page_get_media_box: LazySymbol::new("FPDFPage_GetMediaBox"),
+            page_get_crop_box: LazySymbol::new("FPDFPage_GetCropBox"),
+            page_get_bleed_box: LazySymbol::new("FPDFPage_GetBleedBox"),
+            page_get_trim_box: LazySymbol::new("FPDFPage_GetTrimBox"),
+            page_get_art_box: LazySymbol::new("FPDFPage_GetArtBox"),
+            page_set_media_box: LazySymbol::new("FPDFPage_SetMediaBox"),
+            page_set_crop_box: LazySymbol::new("FPDFPage_SetCropBox"),
+            page_set_bleed_box: LazySymbol::new("FPDFPage_SetBleedBox"),
+            page_set_trim_box: LazySymbol::new("FPDFPage_SetTrimBox"),
+            page_set_art_box: LazySymbol::new("FPDFPage_SetArtBox"),
 
             // Signatures
-            get_signature_count: load_sym!(lib, "FPDF_GetSignatureCount", FnGetSignatureCount),
-            get_signature_object: load_sym!(lib, "FPDF_GetSignatureObject", FnGetSignatureObject),
-            signature_get_contents: load_sym!(lib, "FPDFSignatureObj_GetContents", FnSignatureGetContents),
-            signature_get_byte_range: load_sym!(lib, "FPDFSignatureObj_GetByteRange", FnSignatureGetByteRange),
-            signature_get_sub_filter: load_sym!(lib, "FPDFSignatureObj_GetSubFilter", FnSignatureGetSubFilter),
-            signature_get_reason: load_sym!(lib, "FPDFSignatureObj_GetReason", FnSignatureGetReason),
-            signature_get_time: load_sym!(lib, "FPDFSignatureObj_GetTime", FnSignatureGetTime),
-            signature_get_doc_mdp_permission: load_sym!(lib, "FPDFSignatureObj_GetDocMDPPermission", FnSignatureGetDocMDPPermission),
+            get_signature_count: LazySymbol::new("FPDF_GetSignatureCount"),
+            get_signature_object: LazySymbol::new("FPDF_GetSignatureObject"),
+            signature_get_contents: LazySymbol::new("FPDFSignatureObj_GetContents"),
+            signature_get_byte_range: LazySymbol::new("FPDFSignatureObj_GetByteRange"),
+            signature_get_sub_filter: LazySymbol::new("FPDFSignatureObj_GetSubFilter"),
+            signature_get_reason: LazySymbol::new("FPDFSignatureObj_GetReason"),
+            signature_get_time: LazySymbol::new("FPDFSignatureObj_GetTime"),
+            signature_get_doc_mdp_permission: LazySymbol::new("FPDFSignatureObj_GetDocMDPPermission"),
 
             // Attachments
-            doc_get_attachment_count: load_sym!(lib, "FPDFDoc_GetAttachmentCount", FnDocGetAttachmentCount),
-            doc_get_attachment: load_sym!(lib, "FPDFDoc_GetAttachment", FnDocGetAttachment),
-            attachment_get_name: load_sym!(lib, "FPDFAttachment_GetName", FnAttachmentGetName),
-            attachment_get_file: load_sym!(lib, "FPDFAttachment_GetFile", FnAttachmentGetFile),
+            doc_get_attachment_count: LazySymbol::new("FPDFDoc_GetAttachmentCount"),
+            doc_get_attachment: LazySymbol::new("FPDFDoc_GetAttachment"),
+            attachment_get_name: LazySymbol::new("FPDFAttachment_GetName"),
+            attachment_get_file: LazySymbol::new("FPDFAttachment_GetFile"),
 
             // Page import
-            import_pages: load_sym!(lib, "FPDF_ImportPages", FnImportPages),
-            import_pages_by_index: load_sym!(lib, "FPDF_ImportPagesByIndex", FnImportPagesByIndex),
-            import_n_pages_to_one: load_sym!(lib, "FPDF_ImportNPagesToOne", FnImportNPagesToOne),
-            copy_viewer_preferences: load_sym!(lib, "FPDF_CopyViewerPreferences", FnCopyViewerPreferences),
+            import_pages: LazySymbol::new("FPDF_ImportPages"),
+            import_pages_by_index: LazySymbol::new("FPDF_ImportPagesByIndex"),
+            import_n_pages_to_one: LazySymbol::new("FPDF_ImportNPagesToOne"),
+            copy_viewer_preferences: LazySymbol::new("FPDF_CopyViewerPreferences"),
 
             // Bookmarks / Links
-            bookmark_get_first_child: load_sym!(lib, "FPDFBookmark_GetFirstChild", FnBookmarkGetFirstChild),
-            bookmark_get_next_sibling: load_sym!(lib, "FPDFBookmark_GetNextSibling", FnBookmarkGetNextSibling),
-            bookmark_get_title: load_sym!(lib, "FPDFBookmark_GetTitle", FnBookmarkGetTitle),
-            bookmark_get_count: load_sym!(lib, "FPDFBookmark_GetCount", FnBookmarkGetCount),
-            bookmark_get_dest: load_sym!(lib, "FPDFBookmark_GetDest", FnBookmarkGetDest),
-            bookmark_get_action: load_sym!(lib, "FPDFBookmark_GetAction", FnBookmarkGetAction),
-            dest_get_dest_page_index: load_sym!(lib, "FPDFDest_GetDestPageIndex", FnDestGetDestPageIndex),
-            action_get_type: load_sym!(lib, "FPDFAction_GetType", FnActionGetType),
+            bookmark_get_first_child: LazySymbol::new("FPDFBookmark_GetFirstChild"),
+            bookmark_get_next_sibling: LazySymbol::new("FPDFBookmark_GetNextSibling"),
+            bookmark_get_title: LazySymbol::new("FPDFBookmark_GetTitle"),
+            bookmark_get_count: LazySymbol::new("FPDFBookmark_GetCount"),
+            bookmark_get_dest: LazySymbol::new("FPDFBookmark_GetDest"),
+            bookmark_get_action: LazySymbol::new("FPDFBookmark_GetAction"),
+            dest_get_dest_page_index: LazySymbol::new("FPDFDest_GetDestPageIndex"),
+            action_get_type: LazySymbol::new("FPDFAction_GetType"),
 
             // Links
-            link_enumerate: load_sym!(lib, "FPDFLink_Enumerate", FnLinkEnumerate),
-            link_get_annot_rect: load_sym!(lib, "FPDFLink_GetAnnotRect", FnLinkGetAnnotRect),
-            link_get_action: load_sym!(lib, "FPDFLink_GetAction", FnLinkGetAction),
-            link_get_dest: load_sym!(lib, "FPDFLink_GetDest", FnLinkGetDest),
-            action_get_dest: load_sym!(lib, "FPDFAction_GetDest", FnActionGetDest),
-            action_get_uri_path: load_sym!(lib, "FPDFAction_GetURIPath", FnActionGetURIPath),
-            action_get_file_path: load_sym!(lib, "FPDFAction_GetFilePath", FnActionGetFilePath),
-            dest_get_view: load_sym!(lib, "FPDFDest_GetView", FnDestGetView),
-            dest_get_location_in_page: load_sym!(lib, "FPDFDest_GetLocationInPage", FnDestGetLocationInPage),
+            link_enumerate: LazySymbol::new("FPDFLink_Enumerate"),
+            link_get_annot_rect: LazySymbol::new("FPDFLink_GetAnnotRect"),
+            link_get_action: LazySymbol::new("FPDFLink_GetAction"),
+            link_get_dest: LazySymbol::new("FPDFLink_GetDest"),
+            action_get_dest: LazySymbol::new("FPDFAction_GetDest"),
+            action_get_uri_path: LazySymbol::new("FPDFAction_GetURIPath"),
+            action_get_file_path: LazySymbol::new("FPDFAction_GetFilePath"),
+            dest_get_view: LazySymbol::new("FPDFDest_GetView"),
+            dest_get_location_in_page: LazySymbol::new("FPDFDest_GetLocationInPage"),
 
             // Annotations (read)
-            page_get_annot_count: load_sym!(lib, "FPDFPage_GetAnnotCount", FnPageGetAnnotCount),
-            page_get_annot: load_sym!(lib, "FPDFPage_GetAnnot", FnPageGetAnnot),
-            page_close_annot: load_sym!(lib, "FPDFPage_CloseAnnot", FnPageCloseAnnot),
-            annot_get_subtype: load_sym!(lib, "FPDFAnnot_GetSubtype", FnAnnotGetSubtype),
-            annot_get_rect: load_sym!(lib, "FPDFAnnot_GetRect", FnAnnotGetRect),
-            annot_get_color: load_sym!(lib, "FPDFAnnot_GetColor", FnAnnotGetColor),
+            page_get_annot_count: LazySymbol::new("FPDFPage_GetAnnotCount"),
+            page_get_annot: LazySymbol::new("FPDFPage_GetAnnot"),
+            page_close_annot: LazySymbol::new("FPDFPage_CloseAnnot"),
+            annot_get_subtype: LazySymbol::new("FPDFAnnot_GetSubtype"),
+            annot_get_rect: LazySymbol::new("FPDFAnnot_GetRect"),
+            annot_get_color: LazySymbol::new("FPDFAnnot_GetColor"),
 
             // Annotations (mutation)
-            page_create_annot: load_sym!(lib, "FPDFPage_CreateAnnot", FnPageCreateAnnot),
-            page_remove_annot: load_sym!(lib, "FPDFPage_RemoveAnnot", FnPageRemoveAnnot),
-            annot_set_rect: load_sym!(lib, "FPDFAnnot_SetRect", FnAnnotSetRect),
-            annot_set_color: load_sym!(lib, "FPDFAnnot_SetColor", FnAnnotSetColor),
-            annot_get_flags: load_sym!(lib, "FPDFAnnot_GetFlags", FnAnnotGetFlags),
-            annot_set_flags: load_sym!(lib, "FPDFAnnot_SetFlags", FnAnnotSetFlags),
-            annot_set_string_value: load_sym!(lib, "FPDFAnnot_SetStringValue", FnAnnotSetStringValue),
-            annot_set_border: load_sym!(lib, "FPDFAnnot_SetBorder", FnAnnotSetBorder),
-            annot_set_attachment_points: load_sym!(lib, "FPDFAnnot_SetAttachmentPoints", FnAnnotSetAttachmentPoints),
-            annot_append_attachment_points: load_sym!(lib, "FPDFAnnot_AppendAttachmentPoints", FnAnnotAppendAttachmentPoints),
-            annot_set_uri: load_sym!(lib, "FPDFAnnot_SetURI", FnAnnotSetURI),
+            page_create_annot: LazySymbol::new("FPDFPage_CreateAnnot"),
+            page_remove_annot: LazySymbol::new("FPDFPage_RemoveAnnot"),
+            annot_set_rect: LazySymbol::new("FPDFAnnot_SetRect"),
+            annot_set_color: LazySymbol::new("FPDFAnnot_SetColor"),
+            annot_get_flags: LazySymbol::new("FPDFAnnot_GetFlags"),
+            annot_set_flags: LazySymbol::new("FPDFAnnot_SetFlags"),
+            annot_set_string_value: LazySymbol::new("FPDFAnnot_SetStringValue"),
+            annot_set_border: LazySymbol::new("FPDFAnnot_SetBorder"),
+            annot_set_attachment_points: LazySymbol::new("FPDFAnnot_SetAttachmentPoints"),
+            annot_append_attachment_points: LazySymbol::new("FPDFAnnot_AppendAttachmentPoints"),
+            annot_set_uri: LazySymbol::new("FPDFAnnot_SetURI"),
 
             // Text character extended operations
-            text_get_unicode: load_sym!(lib, "FPDFText_GetUnicode", FnTextGetUnicode),
-            text_is_generated: load_sym!(lib, "FPDFText_IsGenerated", FnTextIsGenerated),
-            text_is_hyphen: load_sym!(lib, "FPDFText_IsHyphen", FnTextIsHyphen),
-            text_has_unicode_map_error: load_sym!(lib, "FPDFText_HasUnicodeMapError", FnTextHasUnicodeMapError),
-            text_get_char_angle: load_sym!(lib, "FPDFText_GetCharAngle", FnTextGetCharAngle),
-            text_get_char_origin: load_sym!(lib, "FPDFText_GetCharOrigin", FnTextGetCharOrigin),
-            text_get_char_box: load_sym!(lib, "FPDFText_GetCharBox", FnTextGetCharBox),
-            text_get_loose_char_box: load_sym!(lib, "FPDFText_GetLooseCharBox", FnTextGetLooseCharBox),
-            text_get_char_index_at_pos: load_sym!(lib, "FPDFText_GetCharIndexAtPos", FnTextGetCharIndexAtPos),
-            text_get_fill_color: load_sym!(lib, "FPDFText_GetFillColor", FnTextGetFillColor),
-            text_get_stroke_color: load_sym!(lib, "FPDFText_GetStrokeColor", FnTextGetStrokeColor),
-            text_get_matrix: load_sym!(lib, "FPDFText_GetMatrix", FnTextGetMatrix),
+            text_get_unicode: LazySymbol::new("FPDFText_GetUnicode"),
+            text_is_generated: LazySymbol::new("FPDFText_IsGenerated"),
+            text_is_hyphen: LazySymbol::new("FPDFText_IsHyphen"),
+            text_has_unicode_map_error: LazySymbol::new("FPDFText_HasUnicodeMapError"),
+            text_get_char_angle: LazySymbol::new("FPDFText_GetCharAngle"),
+            text_get_char_origin: LazySymbol::new("FPDFText_GetCharOrigin"),
+            text_get_char_box: LazySymbol::new("FPDFText_GetCharBox"),
+            text_get_loose_char_box: LazySymbol::new("FPDFText_GetLooseCharBox"),
+            text_get_char_index_at_pos: LazySymbol::new("FPDFText_GetCharIndexAtPos"),
+            text_get_fill_color: LazySymbol::new("FPDFText_GetFillColor"),
+            text_get_stroke_color: LazySymbol::new("FPDFText_GetStrokeColor"),
+            text_get_matrix: LazySymbol::new("FPDFText_GetMatrix"),
 
             // Text search
-            text_find_start: load_sym!(lib, "FPDFText_FindStart", FnTextFindStart),
-            text_find_next: load_sym!(lib, "FPDFText_FindNext", FnTextFindNext),
-            text_find_prev: load_sym!(lib, "FPDFText_FindPrev", FnTextFindPrev),
-            text_find_close: load_sym!(lib, "FPDFText_FindClose", FnTextFindClose),
-            text_get_sch_result_index: load_sym!(lib, "FPDFText_GetSchResultIndex", FnTextGetSchResultIndex),
-            text_get_sch_count: load_sym!(lib, "FPDFText_GetSchCount", FnTextGetSchCount),
+            text_find_start: LazySymbol::new("FPDFText_FindStart"),
+            text_find_next: LazySymbol::new("FPDFText_FindNext"),
+            text_find_prev: LazySymbol::new("FPDFText_FindPrev"),
+            text_find_close: LazySymbol::new("FPDFText_FindClose"),
+            text_get_sch_result_index: LazySymbol::new("FPDFText_GetSchResultIndex"),
+            text_get_sch_count: LazySymbol::new("FPDFText_GetSchCount"),
 
             // Text rectangle / bounded text
-            text_count_rects: load_sym!(lib, "FPDFText_CountRects", FnTextCountRects),
-            text_get_rect: load_sym!(lib, "FPDFText_GetRect", FnTextGetRect),
-            text_get_bounded_text: load_sym!(lib, "FPDFText_GetBoundedText", FnTextGetBoundedText),
+            text_count_rects: LazySymbol::new("FPDFText_CountRects"),
+            text_get_rect: LazySymbol::new("FPDFText_GetRect"),
+            text_get_bounded_text: LazySymbol::new("FPDFText_GetBoundedText"),
 
             // Page operations
-            page_get_rotation: load_sym!(lib, "FPDFPage_GetRotation", FnPageGetRotation),
-            page_set_rotation: load_sym!(lib, "FPDFPage_SetRotation", FnPageSetRotation),
-            page_has_transparency: load_sym!(lib, "FPDFPage_HasTransparency", FnPageHasTransparency),
-            page_flatten: load_sym!(lib, "FPDFPage_Flatten", FnPageFlatten),
-            page_generate_content: load_sym!(lib, "FPDFPage_GenerateContent", FnPageGenerateContent),
+            page_get_rotation: LazySymbol::new("FPDFPage_GetRotation"),
+            page_set_rotation: LazySymbol::new("FPDFPage_SetRotation"),
+            page_has_transparency: LazySymbol::new("FPDFPage_HasTransparency"),
+            page_flatten: LazySymbol::new("FPDFPage_Flatten"),
+            page_generate_content: LazySymbol::new("FPDFPage_GenerateContent"),
+
+            // Progressive loading
+            avail_create: LazySymbol::new("FPDFAvail_Create"),
+            avail_destroy: LazySymbol::new("FPDFAvail_Destroy"),
+            avail_is_doc_avail: LazySymbol::new("FPDFAvail_IsDocAvail"),
+            avail_get_document: LazySymbol::new("FPDFAvail_GetDocument"),
+            avail_get_first_page_num: LazySymbol::new("FPDFAvail_GetFirstPageNum"),
+            avail_is_page_avail: LazySymbol::new("FPDFAvail_IsPageAvail"),
+            avail_is_linearized: LazySymbol::new("FPDFAvail_IsLinearized"),
 
             // Coordinate conversion
-            device_to_page: load_sym!(lib, "FPDF_DeviceToPage", FnDeviceToPage),
-            page_to_device: load_sym!(lib, "FPDF_PageToDevice", FnPageToDevice),
+            device_to_page: LazySymbol::new("FPDF_DeviceToPage"),
+            page_to_device: LazySymbol::new("FPDF_PageToDevice"),
 
             // Save / export
             save_as_copy: load_sym!(lib, "FPDF_SaveAsCopy", FnSaveAsCopy),
             save_with_version: load_sym!(lib, "FPDF_SaveWithVersion", FnSaveWithVersion),
 
-            _lib: lib,
-            handles: HashMap::new(),
-            next_handle: 1,
-            doc_data: HashMap::new(),
+            lib,
+            initialised: Cell::new(false),
+            handles: RefCell::new(HandleTable::new()),
+            doc_sources: RefCell::new(HashMap::new()),
+            forms: RefCell::new(HashMap::new()),
+            page_indices: RefCell::new(HashMap::new()),
         };
 
         Ok(library)
     }
 
-    fn alloc_handle(&mut self, entry: HandleEntry) -> u32 {
-        let handle = self.next_handle;
-        self.next_handle += 1;
-        self.handles.insert(handle, entry);
-        handle
+    /// Resolve an optional symbol, or fail with `SymbolMissing`.
+    fn sym<T: Copy>(&self, symbol: &LazySymbol<T>) -> Result<T, PdfiumError> {
+        symbol.get(&self.lib)
+    }
+
+    fn has<T: Copy>(&self, symbol: &LazySymbol<T>) -> bool {
+        symbol.resolve(&self.lib).is_some()
+    }
+
+    /// Report which optional feature groups this PDFium build supports.
+    ///
+    /// Methods in an unavailable group return `PdfiumError::SymbolMissing`.
+    pub fn capabilities(&self) -> Capabilities {
+        let annotations = self.has(&self.page_get_annot_count)
+            && self.has(&self.page_get_annot)
+            && self.has(&self.page_close_annot)
+            && self.has(&self.annot_get_subtype)
+            && self.has(&self.annot_get_rect)
+            && self.has(&self.annot_get_color);
+
+        Capabilities {
+            text_font_info: self.has(&self.text_get_font_size)
+                && self.has(&self.text_get_font_weight)
+                && self.has(&self.text_get_font_info),
+            text_char_details: self.has(&self.text_get_unicode)
+                && self.has(&self.text_is_generated)
+                && self.has(&self.text_is_hyphen)
+                && self.has(&self.text_has_unicode_map_error)
+                && self.has(&self.text_get_char_angle)
+                && self.has(&self.text_get_char_origin)
+                && self.has(&self.text_get_char_box)
+                && self.has(&self.text_get_loose_char_box)
+                && self.has(&self.text_get_char_index_at_pos)
+                && self.has(&self.text_get_fill_color)
+                && self.has(&self.text_get_stroke_color)
+                && self.has(&self.text_get_matrix),
+            text_search: self.has(&self.text_find_start)
+                && self.has(&self.text_find_next)
+                && self.has(&self.text_find_close)
+                && self.has(&self.text_get_sch_result_index)
+                && self.has(&self.text_get_sch_count),
+            text_rects: self.has(&self.text_count_rects)
+                && self.has(&self.text_get_rect)
+                && self.has(&self.text_get_bounded_text),
+            page_boxes: self.has(&self.page_get_media_box)
+                && self.has(&self.page_get_crop_box)
+                && self.has(&self.page_get_bleed_box)
+                && self.has(&self.page_get_trim_box)
+                && self.has(&self.page_get_art_box)
+                && self.has(&self.page_set_media_box)
+                && self.has(&self.page_set_crop_box)
+                && self.has(&self.page_set_bleed_box)
+                && self.has(&self.page_set_trim_box)
+                && self.has(&self.page_set_art_box),
+            page_editing: self.has(&self.page_get_rotation)
+                && self.has(&self.page_set_rotation)
+                && self.has(&self.page_has_transparency)
+                && self.has(&self.page_flatten)
+                && self.has(&self.page_generate_content),
+            coordinate_conversion: self.has(&self.device_to_page) && self.has(&self.page_to_device),
+            progressive_rendering: self.has(&self.render_page_bitmap_start)
+                && self.has(&self.render_page_continue)
+                && self.has(&self.render_page_close),
+            progressive_loading: self.has(&self.avail_create)
+                && self.has(&self.avail_destroy)
+                && self.has(&self.avail_is_doc_avail)
+                && self.has(&self.avail_get_document)
+                && self.has(&self.avail_get_first_page_num)
+                && self.has(&self.avail_is_page_avail)
+                && self.has(&self.avail_is_linearized),
+            signatures: self.has(&self.get_signature_count)
+                && self.has(&self.get_signature_object)
+                && self.has(&self.signature_get_contents)
+                && self.has(&self.signature_get_byte_range)
+                && self.has(&self.signature_get_sub_filter)
+                && self.has(&self.signature_get_reason)
+                && self.has(&self.signature_get_time)
+                && self.has(&self.signature_get_doc_mdp_permission),
+            attachments: self.has(&self.doc_get_attachment_count)
+                && self.has(&self.doc_get_attachment)
+                && self.has(&self.attachment_get_name)
+                && self.has(&self.attachment_get_file),
+            page_import: self.has(&self.import_pages)
+                && self.has(&self.import_pages_by_index)
+                && self.has(&self.import_n_pages_to_one)
+                && self.has(&self.copy_viewer_preferences),
+            bookmarks: self.has(&self.bookmark_get_first_child)
+                && self.has(&self.bookmark_get_next_sibling)
+                && self.has(&self.bookmark_get_title)
+                && self.has(&self.bookmark_get_dest)
+                && self.has(&self.dest_get_dest_page_index),
+            links: self.has(&self.link_enumerate)
+                && self.has(&self.link_get_annot_rect)
+                && self.has(&self.link_get_action)
+                && self.has(&self.link_get_dest)
+                && self.has(&self.action_get_dest)
+                && self.has(&self.action_get_uri_path)
+                && self.has(&self.action_get_file_path)
+                && self.has(&self.action_get_type)
+                && self.has(&self.dest_get_dest_page_index)
+                && self.has(&self.dest_get_view)
+                && self.has(&self.dest_get_location_in_page),
+            annotations,
+            object_rendering: self.has(&self.image_obj_get_rendered_bitmap)
+                && self.has(&self.text_obj_get_rendered_bitmap)
+                && self.has(&self.page_count_objects)
+                && self.has(&self.page_get_object)
+                && self.has(&self.page_obj_get_type)
+                && self.has(&self.page_remove_object)
+                && self.has(&self.page_obj_destroy)
+                && self.has(&self.create_new_document)
+                && self.has(&self.import_pages_by_index)
+                && self.has(&self.bitmap_get_width)
+                && self.has(&self.bitmap_get_height)
+                && self.has(&self.bitmap_get_format),
+            thumbnails: self.has(&self.page_get_decoded_thumbnail_data)
+                && self.has(&self.page_get_raw_thumbnail_data)
+                && self.has(&self.page_get_thumbnail_as_bitmap)
+                && self.has(&self.bitmap_get_width)
+                && self.has(&self.bitmap_get_height)
+                && self.has(&self.bitmap_get_format),
+            color_schemes: self.has(&self.render_page_bitmap_with_color_scheme_start)
+                && self.has(&self.render_page_continue)
+                && self.has(&self.render_page_close),
+            forms: self.has(&self.init_form_fill_environment)
+                && self.has(&self.exit_form_fill_environment)
+                && self.has(&self.ffl_draw)
+                && self.has(&self.set_form_field_highlight_color)
+                && self.has(&self.set_form_field_highlight_alpha),
+            skia_renderer: self.has(&self.render_page_skia),
+            // Editing opens annotations through the read API.
+            annotation_editing: annotations
+                && self.has(&self.page_create_annot)
+                && self.has(&self.page_remove_annot)
+                && self.has(&self.annot_set_rect)
+                && self.has(&self.annot_set_color)
+                && self.has(&self.annot_get_flags)
+                && self.has(&self.annot_set_flags)
+                && self.has(&self.annot_set_string_value)
+                && self.has(&self.annot_set_border)
+                && self.has(&self.annot_set_attachment_points)
+                && self.has(&self.annot_append_attachment_points)
+                && self.has(&self.annot_set_uri),
+        }
+    }
+
+    /// Register a freshly opened object. If no handle can be issued the
+    /// object is closed again rather than leaked.
+    fn alloc_handle(&self, entry: HandleEntry, parent: Option<u32>) -> Result<u32, PdfiumError> {
+        let result = self.handles.borrow_mut().insert(entry, parent);
+        if result.is_err() {
+            self.close_entry(entry);
+        }
+        result
+    }
+
+    /// Allocate a handle for a newly opened document and keep `source`, the
+    /// bytes it reads from, alive until the document is closed.
+    pub(crate) fn register_document(&self, doc: FPDF_DOCUMENT, source: Box<dyn Send>) -> Result<u32, PdfiumError> {
+        let handle = self.alloc_handle(HandleEntry::Document(doc), None)?;
+        self.doc_sources.borrow_mut().insert(handle, source);
+        Ok(handle)
+    }
+
+    /// Record that the document owning `handle` has unsaved changes.
+    fn mark_dirty(&self, handle: u32) {
+        self.handles.borrow_mut().set_dirty(handle, true);
+    }
+
+    /// Mark the document dirty if a setter reported success, passing the
+    /// result through.
+    fn mark_dirty_if(&self, handle: u32, changed: bool) -> Result<bool, PdfiumError> {
+        if changed {
+            self.mark_dirty(handle);
+        }
+        Ok(changed)
+    }
+
+    /// Resolve the `FPDFAvail_*` exports used by `ProgressiveLoader`.
+    pub(crate) fn avail_symbols(&self) -> Result<AvailSymbols, PdfiumError> {
+        Ok(AvailSymbols {
+            create: self.sym(&self.avail_create)?,
+            destroy: self.sym(&self.avail_destroy)?,
+            is_doc_avail: self.sym(&self.avail_is_doc_avail)?,
+            get_document: self.sym(&self.avail_get_document)?,
+            get_first_page_num: self.sym(&self.avail_get_first_page_num)?,
+            is_page_avail: self.sym(&self.avail_is_page_avail)?,
+            is_linearized: self.sym(&self.avail_is_linearized)?,
+        })
+    }
+
+    /// Resolve the `FPDFBitmap_*` exports used by `PdfBitmap`.
+    pub(crate) fn bitmap_symbols(&self) -> Result<BitmapSymbols, PdfiumError> {
+        Ok(BitmapSymbols {
+            create_ex: *self.bitmap_create_ex,
+            destroy: *self.bitmap_destroy,
+            get_buffer: *self.bitmap_get_buffer,
+            get_stride: *self.bitmap_get_stride,
+            get_width: self.sym(&self.bitmap_get_width)?,
+            get_height: self.sym(&self.bitmap_get_height)?,
+            get_format: self.sym(&self.bitmap_get_format)?,
+        })
+    }
+
+    /// Close `handle` and every handle it owns, children first.
+    fn close_handle(&self, handle: u32) {
+        let closed = self.handles.borrow_mut().remove_tree(handle);
+        for (handle, entry) in closed {
+            if let HandleEntry::Document(_) = entry {
+                // The form-fill environment must go before its document.
+                self.exit_form_fill(handle);
+            }
+            self.close_entry(entry);
+            match entry {
+                HandleEntry::Document(_) => {
+                    self.doc_sources.borrow_mut().remove(&handle);
+                }
+                HandleEntry::Page(_) => {
+                    self.page_indices.borrow_mut().remove(&handle);
+                }
+                HandleEntry::TextPage(_) => {}
+            }
+        }
+    }
+
+    fn close_entry(&self, entry: HandleEntry) {
+        match entry {
+            HandleEntry::TextPage(tp) => unsafe { (self.text_close_page)(tp) },
+            HandleEntry::Page(page) => unsafe { (self.close_page)(page) },
+            HandleEntry::Document(doc) => unsafe { (self.close_document)(doc) },
+        }
+    }
+
+    pub(crate) fn document_ptr(&self, handle: u32) -> Result<FPDF_DOCUMENT, PdfiumError> {
+        self.ensure_initialised()?;
+        match self.handles.borrow().get(handle, HandleKind::Document)? {
+            HandleEntry::Document(doc) => Ok(doc),
+            _ => unreachable!("handle kind tag matches its entry"),
+        }
+    }
+
+    fn page_ptr(&self, handle: u32) -> Result<FPDF_PAGE, PdfiumError> {
+        self.ensure_initialised()?;
+        match self.handles.borrow().get(handle, HandleKind::Page)? {
+            HandleEntry::Page(page) => Ok(page),
+            _ => unreachable!("handle kind tag matches its entry"),
+        }
+    }
+
+    fn text_page_ptr(&self, handle: u32) -> Result<FPDF_TEXTPAGE, PdfiumError> {
+        self.ensure_initialised()?;
+        match self.handles.borrow().get(handle, HandleKind::TextPage)? {
+            HandleEntry::TextPage(tp) => Ok(tp),
+            _ => unreachable!("handle kind tag matches its entry"),
+        }
+    }
+
+    /// Number of live document, page and text page handles.
+    ///
+    /// Useful for leak detection in long-running processes: once every
+    /// document has been closed, all counts should be zero.
+    pub fn live_handle_stats(&self) -> HandleStats {
+        self.handles.borrow().stats()
     }
 
     // --- Core ---
 
+    /// Initialise PDFium with default settings.
+    ///
+    /// If another `PdfiumLibrary` backed by the same binary already
+    /// initialised PDFium, this instance shares that initialisation whatever
+    /// its settings.
     pub fn init_library(&self) {
-        let config = FPDF_LIBRARY_CONFIG {
-            version: 2,
-            m_pUserFontPaths: ptr::null(),
-            m_pIsolate: ptr::null_mut(),
-            m_v8EmbedderSlot: 0,
-            m_pPlatform: ptr::null_mut(),
-        };
-        unsafe { (self.init_library_with_config)(&config) };
+        self.init(None).expect("default init options are always valid");
+    }
+
+    /// Initialise PDFium with custom font paths and renderer.
+    ///
+    /// PDFium is initialised once per loaded binary. If another
+    /// `PdfiumLibrary` already initialised it, this instance shares that
+    /// initialisation, and `options` must match the ones it used: PDFium
+    /// cannot be reconfigured while running, so differing options return
+    /// `PdfiumError::InvalidArgument`. The same applies when calling this
+    /// again on an initialised instance, which otherwise does nothing.
+    pub fn init_library_with(&self, options: &InitOptions) -> Result<(), PdfiumError> {
+        self.init(Some(options))
+    }
+
+    fn init(&self, options: Option<&InitOptions>) -> Result<(), PdfiumError> {
+        if self.initialised.get() {
+            return PDFIUM_INIT.check(self.binary_key(), options);
+        }
+
+        // Selecting Skia on a build without it trips a CHECK inside PDFium.
+        let renderer = options.and_then(InitOptions::get_renderer);
+        if renderer == Some(RendererType::Skia) && !self.has(&self.render_page_skia) {
+            return Err(PdfiumError::InvalidArgument(
+                "Skia renderer is not available in this PDFium build".to_string(),
+            ));
+        }
+
+        PDFIUM_INIT.retain(self.binary_key(), options, |options, font_paths| {
+            let config = FPDF_LIBRARY_CONFIG {
+                // Version 4 adds m_RendererType; only ask for it when a renderer is chosen.
+                version: if options.get_renderer().is_some() { 4 } else { 2 },
+                m_pUserFontPaths: font_paths.as_ptr(),
+                m_pIsolate: ptr::null_mut(),
+                m_v8EmbedderSlot: 0,
+                m_pPlatform: ptr::null_mut(),
+                m_RendererType: options.get_renderer().unwrap_or(RendererType::Agg).to_raw(),
+            };
+            unsafe { (self.init_library_with_config)(&config) };
+        })?;
+        self.initialised.set(true);
+        Ok(())
+    }
+
+    /// Identifies the loaded binary. Loading the same file twice yields the
+    /// same module, and so the same address.
+    pub(crate) fn binary_key(&self) -> BinaryKey {
+        *self.init_library_with_config as usize
     }
 
+    /// Close every handle this instance still holds and release its
+    /// reference on PDFium, destroying the library if it was the last one.
+    ///
+    /// Does nothing if this instance is not initialised. Also runs on drop.
     pub fn destroy_library(&self) {
-        unsafe { (self.destroy_library)() };
+        if !self.initialised.get() {
+            return;
+        }
+        let roots = self.handles.borrow().roots();
+        for root in roots {
+            self.close_handle(root);
+        }
+        self.initialised.set(false);
+        PDFIUM_INIT.release(self.binary_key(), || unsafe { (self.destroy_library)() });
+    }
+
+    pub fn is_initialised(&self) -> bool {
+        self.initialised.get()
+    }
+
+    pub(crate) fn ensure_initialised(&self) -> Result<(), PdfiumError> {
+        if self.initialised.get() {
+            Ok(())
+        } else {
+            Err(PdfiumError::NotInitialised)
+        }
     }
 
     pub fn get_last_error(&self) -> u32 {
@@ -475,139 +949,196 @@ impl PdfiumLibrary {
 
     // --- Document ---
 
-    pub fn load_document(&mut self, data: &[u8], password: Option<&str>) -> Result<u32, String> {
-        let password_cstr = password
-            .map(|p| CString::new(p).map_err(|e| format!("Invalid password: {}", e)))
-            .transpose()?;
+    /// Load a document from a byte buffer.
+    ///
+    /// The bytes are copied; use `load_document_from_buffer` to hand over an
+    /// owned buffer instead, or `load_document_from_path` to let PDFium read
+    /// the file itself.
+    pub fn load_document(&self, data: &[u8], password: Option<&str>) -> Result<u32, PdfiumError> {
+        self.load_document_from_buffer(data.to_vec(), password)
+    }
 
-        let password_ptr = password_cstr
-            .as_ref()
-            .map_or(ptr::null(), |c| c.as_ptr());
+    /// Load a document from a buffer without copying it.
+    ///
+    /// PDFium reads from the buffer for as long as the document is open, so
+    /// the library takes ownership and drops it when the document is closed.
+    /// Any `AsRef<[u8]>` works, e.g. a `Vec<u8>`, an `Arc<[u8]>` or a
+    /// memory-mapped file.
+    pub fn load_document_from_buffer<B>(&self, buffer: B, password: Option<&str>) -> Result<u32, PdfiumError>
+    where
+        B: AsRef<[u8]> + Send + 'static,
+    {
+        self.ensure_initialised()?;
+
+        // Box first: `as_ref` may point into the value itself, which must not
+        // move once PDFium has the pointer.
+        let buffer = Box::new(buffer);
+        // SAFETY: the buffer is stored in `doc_sources` until the document closes.
+        let doc = unsafe { self.load_mem_document_raw((*buffer).as_ref(), password)? };
+
+        self.register_document(doc, buffer)
+    }
 
-        // Keep a copy of the data alive
-        let data_copy = data.to_vec();
+    /// Load a document through `FPDF_LoadCustomDocument`, pulling bytes from
+    /// `reader` on demand instead of buffering the whole file.
+    ///
+    /// The library owns the reader until the document is closed; PDFium may
+    /// read from it during any later call on the document or its pages. A
+    /// read error or panic in the reader is reported to PDFium as a failed
+    /// read, and a reader that panicked is not used again.
+    pub fn load_document_from_reader<R>(&self, reader: R, password: Option<&str>) -> Result<u32, PdfiumError>
+    where
+        R: Read + Seek + Send + 'static,
+    {
+        self.ensure_initialised()?;
+
+        let password_cstr = password_cstring(password)?;
+        let mut access = ReaderAccess::new(reader)?;
 
         let doc = unsafe {
-            (self.load_mem_document)(
-                data_copy.as_ptr() as *const c_void,
-                data_copy.len() as c_int,
-                password_ptr,
+            (self.load_custom_document)(
+                access.as_ptr(),
+                password_cstr.as_ref().map_or(ptr::null(), |c| c.as_ptr()),
             )
         };
 
         if doc.is_null() {
-            let error = self.get_last_error();
-            return Err(format!("Failed to load document, error code: {}", error));
+            return Err(match access.take_failure() {
+                Some(reason) => PdfiumError::Failed(format!("Reader failed: {}", reason)),
+                None => PdfiumError::from_error_code(self.get_last_error()),
+            });
         }
 
-        let handle = self.alloc_handle(HandleEntry::Document(doc));
-        self.doc_data.insert(handle, data_copy);
-        Ok(handle)
+        self.register_document(doc, Box::new(access))
     }
 
-    pub fn close_document(&mut self, handle: u32) -> Result<(), String> {
-        match self.handles.remove(&handle) {
-            Some(HandleEntry::Document(doc)) => {
-                unsafe { (self.close_document)(doc) };
-                self.doc_data.remove(&handle);
-                Ok(())
-            }
-            Some(_) => Err("Handle is not a document".to_string()),
-            None => Err("Invalid handle".to_string()),
+    /// Load a document directly from a file. PDFium reads the file on demand,
+    /// so it is never held in memory as a whole.
+    pub fn load_document_from_path(&self, path: impl AsRef<Path>, password: Option<&str>) -> Result<u32, PdfiumError> {
+        self.ensure_initialised()?;
+
+        let path = path.as_ref();
+        let path_cstr = path
+            .to_str()
+            .and_then(|p| CString::new(p).ok())
+            .ok_or_else(|| PdfiumError::InvalidArgument(format!("path: {}", path.display())))?;
+        let password_cstr = password_cstring(password)?;
+
+        let doc = unsafe {
+            (self.load_document_file)(
+                path_cstr.as_ptr(),
+                password_cstr.as_ref().map_or(ptr::null(), |c| c.as_ptr()),
+            )
+        };
+
+        if doc.is_null() {
+            return Err(PdfiumError::from_error_code(self.get_last_error()));
         }
+
+        self.alloc_handle(HandleEntry::Document(doc), None)
     }
 
-    pub fn get_page_count(&self, doc_handle: u32) -> Result<i32, String> {
-        match self.handles.get(&doc_handle) {
-            Some(HandleEntry::Document(doc)) => Ok(unsafe { (self.get_page_count)(*doc) }),
-            _ => Err("Invalid document handle".to_string()),
+    /// Open `data` with `FPDF_LoadMemDocument64`, falling back to
+    /// `FPDF_LoadMemDocument` on builds without it.
+    ///
+    /// # Safety
+    ///
+    /// `data` must outlive the returned document.
+    unsafe fn load_mem_document_raw(&self, data: &[u8], password: Option<&str>) -> Result<FPDF_DOCUMENT, PdfiumError> {
+        let password_cstr = password_cstring(password)?;
+        let password_ptr = password_cstr.as_ref().map_or(ptr::null(), |c| c.as_ptr());
+
+        let doc = match self.load_mem_document64.resolve(&self.lib) {
+            Some(load_mem_document64) => load_mem_document64(data.as_ptr() as *const c_void, data.len(), password_ptr),
+            None => {
+                let len = c_int::try_from(data.len()).map_err(|_| {
+                    PdfiumError::InvalidArgument(format!(
+                        "document is {} bytes; this PDFium build cannot load more than {} bytes from memory",
+                        data.len(),
+                        c_int::MAX
+                    ))
+                })?;
+                (self.load_mem_document)(data.as_ptr() as *const c_void, len, password_ptr)
+            }
+        };
+
+        if doc.is_null() {
+            return Err(PdfiumError::from_error_code(self.get_last_error()));
         }
+        Ok(doc)
+    }
+
+    /// Close a document along with any pages, text pages and N-up documents
+    /// created from it.
+    pub fn close_document(&self, handle: u32) -> Result<(), PdfiumError> {
+        self.document_ptr(handle)?;
+        self.close_handle(handle);
+        Ok(())
+    }
+
+    pub fn get_page_count(&self, doc_handle: u32) -> Result<i32, PdfiumError> {
+        let doc = self.document_ptr(doc_handle)?;
+        Ok(unsafe { (self.get_page_count)(doc) })
     }
 
     // --- Page ---
 
-    pub fn load_page(&mut self, doc_handle: u32, index: i32) -> Result<u32, String> {
-        let doc = match self.handles.get(&doc_handle) {
-            Some(HandleEntry::Document(doc)) => *doc,
-            _ => return Err("Invalid document handle".to_string()),
-        };
+    pub fn load_page(&self, doc_handle: u32, index: i32) -> Result<u32, PdfiumError> {
+        let doc = self.document_ptr(doc_handle)?;
 
         let page = unsafe { (self.load_page)(doc, index) };
         if page.is_null() {
-            return Err(format!("Failed to load page {}", index));
+            return Err(PdfiumError::PageNotFound { index: Some(index) });
         }
 
-        Ok(self.alloc_handle(HandleEntry::Page(page)))
+        let handle = self.alloc_handle(HandleEntry::Page(page), Some(doc_handle))?;
+        self.page_indices.borrow_mut().insert(handle, index);
+        Ok(handle)
     }
 
-    pub fn close_page(&mut self, handle: u32) -> Result<(), String> {
-        match self.handles.remove(&handle) {
-            Some(HandleEntry::Page(page)) => {
-                unsafe { (self.close_page)(page) };
-                Ok(())
-            }
-            Some(_) => Err("Handle is not a page".to_string()),
-            None => Err("Invalid handle".to_string()),
-        }
+    /// Close a page along with any text page loaded from it.
+    pub fn close_page(&self, handle: u32) -> Result<(), PdfiumError> {
+        self.page_ptr(handle)?;
+        self.close_handle(handle);
+        Ok(())
     }
 
-    pub fn get_page_width(&self, page_handle: u32) -> Result<f64, String> {
-        match self.handles.get(&page_handle) {
-            Some(HandleEntry::Page(page)) => {
-                Ok(unsafe { (self.get_page_width_f)(*page) } as f64)
-            }
-            _ => Err("Invalid page handle".to_string()),
-        }
+    pub fn get_page_width(&self, page_handle: u32) -> Result<f64, PdfiumError> {
+        let page = self.page_ptr(page_handle)?;
+        Ok(unsafe { (self.get_page_width_f)(page) } as f64)
     }
 
-    pub fn get_page_height(&self, page_handle: u32) -> Result<f64, String> {
-        match self.handles.get(&page_handle) {
-            Some(HandleEntry::Page(page)) => {
-                Ok(unsafe { (self.get_page_height_f)(*page) } as f64)
-            }
-            _ => Err("Invalid page handle".to_string()),
-        }
+    pub fn get_page_height(&self, page_handle: u32) -> Result<f64, PdfiumError> {
+        let page = self.page_ptr(page_handle)?;
+        Ok(unsafe { (self.get_page_height_f)(page) } as f64)
     }
 
     // --- Text ---
 
-    pub fn load_text_page(&mut self, page_handle: u32) -> Result<u32, String> {
-        let page = match self.handles.get(&page_handle) {
-            Some(HandleEntry::Page(page)) => *page,
-            _ => return Err("Invalid page handle".to_string()),
-        };
+    pub fn load_text_page(&self, page_handle: u32) -> Result<u32, PdfiumError> {
+        let page = self.page_ptr(page_handle)?;
 
         let text_page = unsafe { (self.text_load_page)(page) };
         if text_page.is_null() {
-            return Err("Failed to load text page".to_string());
+            return Err(PdfiumError::Failed("Failed to load text page".to_string()));
         }
 
-        Ok(self.alloc_handle(HandleEntry::TextPage(text_page)))
+        self.alloc_handle(HandleEntry::TextPage(text_page), Some(page_handle))
     }
 
-    pub fn close_text_page(&mut self, handle: u32) -> Result<(), String> {
-        match self.handles.remove(&handle) {
-            Some(HandleEntry::TextPage(tp)) => {
-                unsafe { (self.text_close_page)(tp) };
-                Ok(())
-            }
-            Some(_) => Err("Handle is not a text page".to_string()),
-            None => Err("Invalid handle".to_string()),
-        }
+    pub fn close_text_page(&self, handle: u32) -> Result<(), PdfiumError> {
+        self.text_page_ptr(handle)?;
+        self.close_handle(handle);
+        Ok(())
     }
 
-    pub fn count_text_chars(&self, text_page_handle: u32) -> Result<i32, String> {
-        match self.handles.get(&text_page_handle) {
-            Some(HandleEntry::TextPage(tp)) => Ok(unsafe { (self.text_count_chars)(*tp) }),
-            _ => Err("Invalid text page handle".to_string()),
-        }
+    pub fn count_text_chars(&self, text_page_handle: u32) -> Result<i32, PdfiumError> {
+        let tp = self.text_page_ptr(text_page_handle)?;
+        Ok(unsafe { (self.text_count_chars)(tp) })
     }
 
-    pub fn get_full_text(&self, text_page_handle: u32) -> Result<String, String> {
-        let tp = match self.handles.get(&text_page_handle) {
-            Some(HandleEntry::TextPage(tp)) => *tp,
-            _ => return Err("Invalid text page handle".to_string()),
-        };
+    pub fn get_full_text(&self, text_page_handle: u32) -> Result<String, PdfiumError> {
+        let tp = self.text_page_ptr(text_page_handle)?;
 
         let char_count = unsafe { (self.text_count_chars)(tp) };
         if char_count <= 0 {
@@ -622,40 +1153,31 @@ impl PdfiumLibrary {
 
         // Decode UTF-16LE, trimming the null terminator
         String::from_utf16(&buffer[..char_count as usize])
-            .map_err(|e| format!("UTF-16 decode error: {}", e))
+            .map_err(|e| PdfiumError::Encoding(e.to_string()))
     }
 
     // --- Text Character Font Info ---
 
-    pub fn get_char_font_size(&self, text_page_handle: u32, char_index: i32) -> Result<f64, String> {
-        let tp = match self.handles.get(&text_page_handle) {
-            Some(HandleEntry::TextPage(tp)) => *tp,
-            _ => return Err("Invalid text page handle".to_string()),
-        };
-        Ok(unsafe { (self.text_get_font_size)(tp, char_index) })
+    pub fn get_char_font_size(&self, text_page_handle: u32, char_index: i32) -> Result<f64, PdfiumError> {
+        let tp = self.text_page_ptr(text_page_handle)?;
+        Ok(unsafe { (self.sym(&self.text_get_font_size)?)(tp, char_index) })
     }
 
-    pub fn get_char_font_weight(&self, text_page_handle: u32, char_index: i32) -> Result<i32, String> {
-        let tp = match self.handles.get(&text_page_handle) {
-            Some(HandleEntry::TextPage(tp)) => *tp,
-            _ => return Err("Invalid text page handle".to_string()),
-        };
-        Ok(unsafe { (self.text_get_font_weight)(tp, char_index) })
+    pub fn get_char_font_weight(&self, text_page_handle: u32, char_index: i32) -> Result<i32, PdfiumError> {
+        let tp = self.text_page_ptr(text_page_handle)?;
+        Ok(unsafe { (self.sym(&self.text_get_font_weight)?)(tp, char_index) })
     }
 
     /// Get the font name and flags for a character.
     /// Returns (fontName, flags) or None if unavailable.
-    pub fn get_char_font_info(&self, text_page_handle: u32, char_index: i32) -> Result<Option<(String, i32)>, String> {
-        let tp = match self.handles.get(&text_page_handle) {
-            Some(HandleEntry::TextPage(tp)) => *tp,
-            _ => return Err("Invalid text page handle".to_string()),
-        };
+    pub fn get_char_font_info(&self, text_page_handle: u32, char_index: i32) -> Result<Option<(String, i32)>, PdfiumError> {
+        let tp = self.text_page_ptr(text_page_handle)?;
 
         let mut flags: c_int = 0;
 
         // First call: get required buffer size
         let size = unsafe {
-            (self.text_get_font_info)(tp, char_index, ptr::null_mut(), 0, &mut flags)
+            (self.sym(&self.text_get_font_info)?)(tp, char_index, ptr::null_mut(), 0, &mut flags)
         } as usize;
 
         if size == 0 {
@@ -665,7 +1187,7 @@ impl PdfiumLibrary {
         // Second call: fill buffer (UTF-8 encoded font name)
         let mut buf = vec![0u8; size];
         unsafe {
-            (self.text_get_font_info)(
+            (self.sym(&self.text_get_font_info)?)(
                 tp,
                 char_index,
                 buf.as_mut_ptr() as *mut c_void,
@@ -685,12 +1207,9 @@ impl PdfiumLibrary {
         }
     }
 
-    pub fn get_char_render_mode(&self, text_page_handle: u32, char_index: i32) -> Result<i32, String> {
-        let tp = match self.handles.get(&text_page_handle) {
-            Some(HandleEntry::TextPage(tp)) => *tp,
-            _ => return Err("Invalid text page handle".to_string()),
-        };
-        match &self.text_get_text_render_mode {
+    pub fn get_char_render_mode(&self, text_page_handle: u32, char_index: i32) -> Result<i32, PdfiumError> {
+        let tp = self.text_page_ptr(text_page_handle)?;
+        match self.text_get_text_render_mode.resolve(&self.lib) {
             Some(f) => Ok(unsafe { f(tp, char_index) }),
             None => Ok(0), // Default to Fill mode
         }
@@ -698,44 +1217,29 @@ impl PdfiumLibrary {
 
     // --- Text Character Extended Operations ---
 
-    pub fn get_char_unicode(&self, text_page_handle: u32, char_index: i32) -> Result<u32, String> {
-        let tp = match self.handles.get(&text_page_handle) {
-            Some(HandleEntry::TextPage(tp)) => *tp,
-            _ => return Err("Invalid text page handle".to_string()),
-        };
-        Ok(unsafe { (self.text_get_unicode)(tp, char_index) })
+    pub fn get_char_unicode(&self, text_page_handle: u32, char_index: i32) -> Result<u32, PdfiumError> {
+        let tp = self.text_page_ptr(text_page_handle)?;
+        Ok(unsafe { (self.sym(&self.text_get_unicode)?)(tp, char_index) })
     }
 
-    pub fn is_char_generated(&self, text_page_handle: u32, char_index: i32) -> Result<bool, String> {
-        let tp = match self.handles.get(&text_page_handle) {
-            Some(HandleEntry::TextPage(tp)) => *tp,
-            _ => return Err("Invalid text page handle".to_string()),
-        };
-        Ok(unsafe { (self.text_is_generated)(tp, char_index) } != 0)
+    pub fn is_char_generated(&self, text_page_handle: u32, char_index: i32) -> Result<bool, PdfiumError> {
+        let tp = self.text_page_ptr(text_page_handle)?;
+        Ok(unsafe { (self.sym(&self.text_is_generated)?)(tp, char_index) } != 0)
     }
 
-    pub fn is_char_hyphen(&self, text_page_handle: u32, char_index: i32) -> Result<bool, String> {
-        let tp = match self.handles.get(&text_page_handle) {
-            Some(HandleEntry::TextPage(tp)) => *tp,
-            _ => return Err("Invalid text page handle".to_string()),
-        };
-        Ok(unsafe { (self.text_is_hyphen)(tp, char_index) } != 0)
+    pub fn is_char_hyphen(&self, text_page_handle: u32, char_index: i32) -> Result<bool, PdfiumError> {
+        let tp = self.text_page_ptr(text_page_handle)?;
+        Ok(unsafe { (self.sym(&self.text_is_hyphen)?)(tp, char_index) } != 0)
     }
 
-    pub fn has_char_unicode_map_error(&self, text_page_handle: u32, char_index: i32) -> Result<bool, String> {
-        let tp = match self.handles.get(&text_page_handle) {
-            Some(HandleEntry::TextPage(tp)) => *tp,
-            _ => return Err("Invalid text page handle".to_string()),
-        };
-        Ok(unsafe { (self.text_has_unicode_map_error)(tp, char_index) } != 0)
+    pub fn has_char_unicode_map_error(&self, text_page_handle: u32, char_index: i32) -> Result<bool, PdfiumError> {
+        let tp = self.text_page_ptr(text_page_handle)?;
+        Ok(unsafe { (self.sym(&self.text_has_unicode_map_error)?)(tp, char_index) } != 0)
     }
 
-    pub fn get_char_angle(&self, text_page_handle: u32, char_index: i32) -> Result<f64, String> {
-        let tp = match self.handles.get(&text_page_handle) {
-            Some(HandleEntry::TextPage(tp)) => *tp,
-            _ => return Err("Invalid text page handle".to_string()),
-        };
-        Ok(unsafe { (self.text_get_char_angle)(tp, char_index) } as f64)
+    pub fn get_char_angle(&self, text_page_handle: u32, char_index: i32) -> Result<f64, PdfiumError> {
+        let tp = self.text_page_ptr(text_page_handle)?;
+        Ok(unsafe { (self.sym(&self.text_get_char_angle)?)(tp, char_index) } as f64)
     }
 
     /// Get the origin (x, y) of a character.
@@ -744,15 +1248,12 @@ impl PdfiumLibrary {
         &self,
         text_page_handle: u32,
         char_index: i32,
-    ) -> Result<Option<(f64, f64)>, String> {
-        let tp = match self.handles.get(&text_page_handle) {
-            Some(HandleEntry::TextPage(tp)) => *tp,
-            _ => return Err("Invalid text page handle".to_string()),
-        };
+    ) -> Result<Option<(f64, f64)>, PdfiumError> {
+        let tp = self.text_page_ptr(text_page_handle)?;
 
         let mut x: f64 = 0.0;
         let mut y: f64 = 0.0;
-        let ok = unsafe { (self.text_get_char_origin)(tp, char_index, &mut x, &mut y) };
+        let ok = unsafe { (self.sym(&self.text_get_char_origin)?)(tp, char_index, &mut x, &mut y) };
         if ok == 0 {
             Ok(None)
         } else {
@@ -766,18 +1267,15 @@ impl PdfiumLibrary {
         &self,
         text_page_handle: u32,
         char_index: i32,
-    ) -> Result<Option<(f64, f64, f64, f64)>, String> {
-        let tp = match self.handles.get(&text_page_handle) {
-            Some(HandleEntry::TextPage(tp)) => *tp,
-            _ => return Err("Invalid text page handle".to_string()),
-        };
+    ) -> Result<Option<(f64, f64, f64, f64)>, PdfiumError> {
+        let tp = self.text_page_ptr(text_page_handle)?;
 
         let mut left: f64 = 0.0;
         let mut right: f64 = 0.0;
         let mut bottom: f64 = 0.0;
         let mut top: f64 = 0.0;
         let ok = unsafe {
-            (self.text_get_char_box)(tp, char_index, &mut left, &mut right, &mut bottom, &mut top)
+            (self.sym(&self.text_get_char_box)?)(tp, char_index, &mut left, &mut right, &mut bottom, &mut top)
         };
         if ok == 0 {
             Ok(None)
@@ -792,14 +1290,11 @@ impl PdfiumLibrary {
         &self,
         text_page_handle: u32,
         char_index: i32,
-    ) -> Result<Option<(f32, f32, f32, f32)>, String> {
-        let tp = match self.handles.get(&text_page_handle) {
-            Some(HandleEntry::TextPage(tp)) => *tp,
-            _ => return Err("Invalid text page handle".to_string()),
-        };
+    ) -> Result<Option<(f32, f32, f32, f32)>, PdfiumError> {
+        let tp = self.text_page_ptr(text_page_handle)?;
 
         let mut rect = [0f32; 4]; // left, top, right, bottom
-        let ok = unsafe { (self.text_get_loose_char_box)(tp, char_index, rect.as_mut_ptr()) };
+        let ok = unsafe { (self.sym(&self.text_get_loose_char_box)?)(tp, char_index, rect.as_mut_ptr()) };
         if ok == 0 {
             Ok(None)
         } else {
@@ -814,12 +1309,9 @@ impl PdfiumLibrary {
         y: f64,
         x_tolerance: f64,
         y_tolerance: f64,
-    ) -> Result<i32, String> {
-        let tp = match self.handles.get(&text_page_handle) {
-            Some(HandleEntry::TextPage(tp)) => *tp,
-            _ => return Err("Invalid text page handle".to_string()),
-        };
-        Ok(unsafe { (self.text_get_char_index_at_pos)(tp, x, y, x_tolerance, y_tolerance) })
+    ) -> Result<i32, PdfiumError> {
+        let tp = self.text_page_ptr(text_page_handle)?;
+        Ok(unsafe { (self.sym(&self.text_get_char_index_at_pos)?)(tp, x, y, x_tolerance, y_tolerance) })
     }
 
     /// Get the fill colour (r, g, b, a) of a character.
@@ -828,17 +1320,14 @@ impl PdfiumLibrary {
         &self,
         text_page_handle: u32,
         char_index: i32,
-    ) -> Result<Option<(u32, u32, u32, u32)>, String> {
-        let tp = match self.handles.get(&text_page_handle) {
-            Some(HandleEntry::TextPage(tp)) => *tp,
-            _ => return Err("Invalid text page handle".to_string()),
-        };
+    ) -> Result<Option<(u32, u32, u32, u32)>, PdfiumError> {
+        let tp = self.text_page_ptr(text_page_handle)?;
 
         let mut r: c_uint = 0;
         let mut g: c_uint = 0;
         let mut b: c_uint = 0;
         let mut a: c_uint = 0;
-        let ok = unsafe { (self.text_get_fill_color)(tp, char_index, &mut r, &mut g, &mut b, &mut a) };
+        let ok = unsafe { (self.sym(&self.text_get_fill_color)?)(tp, char_index, &mut r, &mut g, &mut b, &mut a) };
         if ok == 0 {
             Ok(None)
         } else {
@@ -852,17 +1341,14 @@ impl PdfiumLibrary {
         &self,
         text_page_handle: u32,
         char_index: i32,
-    ) -> Result<Option<(u32, u32, u32, u32)>, String> {
-        let tp = match self.handles.get(&text_page_handle) {
-            Some(HandleEntry::TextPage(tp)) => *tp,
-            _ => return Err("Invalid text page handle".to_string()),
-        };
+    ) -> Result<Option<(u32, u32, u32, u32)>, PdfiumError> {
+        let tp = self.text_page_ptr(text_page_handle)?;
 
         let mut r: c_uint = 0;
         let mut g: c_uint = 0;
         let mut b: c_uint = 0;
         let mut a: c_uint = 0;
-        let ok = unsafe { (self.text_get_stroke_color)(tp, char_index, &mut r, &mut g, &mut b, &mut a) };
+        let ok = unsafe { (self.sym(&self.text_get_stroke_color)?)(tp, char_index, &mut r, &mut g, &mut b, &mut a) };
         if ok == 0 {
             Ok(None)
         } else {
@@ -876,14 +1362,11 @@ impl PdfiumLibrary {
         &self,
         text_page_handle: u32,
         char_index: i32,
-    ) -> Result<Option<[f64; 6]>, String> {
-        let tp = match self.handles.get(&text_page_handle) {
-            Some(HandleEntry::TextPage(tp)) => *tp,
-            _ => return Err("Invalid text page handle".to_string()),
-        };
+    ) -> Result<Option<[f64; 6]>, PdfiumError> {
+        let tp = self.text_page_ptr(text_page_handle)?;
 
         let mut matrix = [0f64; 6];
-        let ok = unsafe { (self.text_get_matrix)(tp, char_index, matrix.as_mut_ptr()) };
+        let ok = unsafe { (self.sym(&self.text_get_matrix)?)(tp, char_index, matrix.as_mut_ptr()) };
         if ok == 0 {
             Ok(None)
         } else {
@@ -902,31 +1385,28 @@ impl PdfiumLibrary {
         text_page_handle: u32,
         query: &str,
         flags: u32,
-    ) -> Result<Vec<(i32, i32)>, String> {
-        let tp = match self.handles.get(&text_page_handle) {
-            Some(HandleEntry::TextPage(tp)) => *tp,
-            _ => return Err("Invalid text page handle".to_string()),
-        };
+    ) -> Result<Vec<(i32, i32)>, PdfiumError> {
+        let tp = self.text_page_ptr(text_page_handle)?;
 
         // Encode query as UTF-16LE with null terminator
         let mut query_utf16: Vec<u16> = query.encode_utf16().collect();
         query_utf16.push(0);
 
         let handle = unsafe {
-            (self.text_find_start)(tp, query_utf16.as_ptr(), flags as c_ulong, 0)
+            (self.sym(&self.text_find_start)?)(tp, query_utf16.as_ptr(), flags as c_ulong, 0)
         };
         if handle.is_null() {
             return Ok(Vec::new());
         }
 
         let mut results = Vec::new();
-        while unsafe { (self.text_find_next)(handle) } != 0 {
-            let index = unsafe { (self.text_get_sch_result_index)(handle) };
-            let count = unsafe { (self.text_get_sch_count)(handle) };
+        while unsafe { (self.sym(&self.text_find_next)?)(handle) } != 0 {
+            let index = unsafe { (self.sym(&self.text_get_sch_result_index)?)(handle) };
+            let count = unsafe { (self.sym(&self.text_get_sch_count)?)(handle) };
             results.push((index, count));
         }
 
-        unsafe { (self.text_find_close)(handle) };
+        unsafe { (self.sym(&self.text_find_close)?)(handle) };
         Ok(results)
     }
 
@@ -938,12 +1418,9 @@ impl PdfiumLibrary {
         text_page_handle: u32,
         start_index: i32,
         count: i32,
-    ) -> Result<i32, String> {
-        let tp = match self.handles.get(&text_page_handle) {
-            Some(HandleEntry::TextPage(tp)) => *tp,
-            _ => return Err("Invalid text page handle".to_string()),
-        };
-        Ok(unsafe { (self.text_count_rects)(tp, start_index, count) })
+    ) -> Result<i32, PdfiumError> {
+        let tp = self.text_page_ptr(text_page_handle)?;
+        Ok(unsafe { (self.sym(&self.text_count_rects)?)(tp, start_index, count) })
     }
 
     /// Get a text rectangle by index.
@@ -952,18 +1429,15 @@ impl PdfiumLibrary {
         &self,
         text_page_handle: u32,
         rect_index: i32,
-    ) -> Result<Option<(f64, f64, f64, f64)>, String> {
-        let tp = match self.handles.get(&text_page_handle) {
-            Some(HandleEntry::TextPage(tp)) => *tp,
-            _ => return Err("Invalid text page handle".to_string()),
-        };
+    ) -> Result<Option<(f64, f64, f64, f64)>, PdfiumError> {
+        let tp = self.text_page_ptr(text_page_handle)?;
 
         let mut left: f64 = 0.0;
         let mut top: f64 = 0.0;
         let mut right: f64 = 0.0;
         let mut bottom: f64 = 0.0;
         let ok = unsafe {
-            (self.text_get_rect)(tp, rect_index, &mut left, &mut top, &mut right, &mut bottom)
+            (self.sym(&self.text_get_rect)?)(tp, rect_index, &mut left, &mut top, &mut right, &mut bottom)
         };
         if ok == 0 {
             Ok(None)
@@ -980,15 +1454,12 @@ impl PdfiumLibrary {
         top: f64,
         right: f64,
         bottom: f64,
-    ) -> Result<String, String> {
-        let tp = match self.handles.get(&text_page_handle) {
-            Some(HandleEntry::TextPage(tp)) => *tp,
-            _ => return Err("Invalid text page handle".to_string()),
-        };
+    ) -> Result<String, PdfiumError> {
+        let tp = self.text_page_ptr(text_page_handle)?;
 
         // First call: get required buffer size
         let size = unsafe {
-            (self.text_get_bounded_text)(tp, left, top, right, bottom, ptr::null_mut(), 0)
+            (self.sym(&self.text_get_bounded_text)?)(tp, left, top, right, bottom, ptr::null_mut(), 0)
         };
         if size <= 0 {
             return Ok(String::new());
@@ -996,7 +1467,7 @@ impl PdfiumLibrary {
 
         let mut buffer: Vec<u16> = vec![0u16; size as usize];
         let written = unsafe {
-            (self.text_get_bounded_text)(tp, left, top, right, bottom, buffer.as_mut_ptr(), size)
+            (self.sym(&self.text_get_bounded_text)?)(tp, left, top, right, bottom, buffer.as_mut_ptr(), size)
         };
 
         if written <= 0 {
@@ -1011,63 +1482,56 @@ impl PdfiumLibrary {
         };
 
         String::from_utf16(&buffer[..len])
-            .map_err(|e| format!("UTF-16 decode error: {}", e))
+            .map_err(|e| PdfiumError::Encoding(e.to_string()))
     }
 
     // --- Page Rotation / Flatten / Transparency / Content ---
 
-    pub fn get_page_rotation(&self, page_handle: u32) -> Result<i32, String> {
-        match self.handles.get(&page_handle) {
-            Some(HandleEntry::Page(page)) => Ok(unsafe { (self.page_get_rotation)(*page) }),
-            _ => Err("Invalid page handle".to_string()),
-        }
+    pub fn get_page_rotation(&self, page_handle: u32) -> Result<i32, PdfiumError> {
+        let page = self.page_ptr(page_handle)?;
+        Ok(unsafe { (self.sym(&self.page_get_rotation)?)(page) })
     }
 
-    pub fn set_page_rotation(&self, page_handle: u32, rotation: i32) -> Result<(), String> {
-        match self.handles.get(&page_handle) {
-            Some(HandleEntry::Page(page)) => {
-                unsafe { (self.page_set_rotation)(*page, rotation) };
-                Ok(())
-            }
-            _ => Err("Invalid page handle".to_string()),
-        }
+    pub fn set_page_rotation(&self, page_handle: u32, rotation: i32) -> Result<(), PdfiumError> {
+        let page = self.page_ptr(page_handle)?;
+        unsafe { (self.sym(&self.page_set_rotation)?)(page, rotation) };
+        self.mark_dirty(page_handle);
+        Ok(())
     }
 
-    pub fn has_page_transparency(&self, page_handle: u32) -> Result<bool, String> {
-        match self.handles.get(&page_handle) {
-            Some(HandleEntry::Page(page)) => {
-                Ok(unsafe { (self.page_has_transparency)(*page) } != 0)
-            }
-            _ => Err("Invalid page handle".to_string()),
-        }
+    pub fn has_page_transparency(&self, page_handle: u32) -> Result<bool, PdfiumError> {
+        let page = self.page_ptr(page_handle)?;
+        Ok(unsafe { (self.sym(&self.page_has_transparency)?)(page) } != 0)
     }
 
     /// Flatten a page (merge annotations/form fields into page content).
     ///
     /// `flags`: 0 = NormalDisplay, 1 = Print.
     /// Returns: 0 = could not flatten, 1 = success, 2 = nothing to flatten.
-    pub fn flatten_page(&self, page_handle: u32, flags: i32) -> Result<i32, String> {
-        match self.handles.get(&page_handle) {
-            Some(HandleEntry::Page(page)) => {
-                Ok(unsafe { (self.page_flatten)(*page, flags) })
-            }
-            _ => Err("Invalid page handle".to_string()),
+    pub fn flatten_page(&self, page_handle: u32, flags: i32) -> Result<i32, PdfiumError> {
+        let page = self.page_ptr(page_handle)?;
+        let result = unsafe { (self.sym(&self.page_flatten)?)(page, flags) };
+        // FLATTEN_SUCCESS
+        if result == 1 {
+            self.mark_dirty(page_handle);
         }
+        Ok(result)
     }
 
     /// Generate page content (update the content stream after modifications).
-    pub fn generate_content(&self, page_handle: u32) -> Result<bool, String> {
-        match self.handles.get(&page_handle) {
-            Some(HandleEntry::Page(page)) => {
-                Ok(unsafe { (self.page_generate_content)(*page) } != 0)
-            }
-            _ => Err("Invalid page handle".to_string()),
+    pub fn generate_content(&self, page_handle: u32) -> Result<bool, PdfiumError> {
+        let page = self.page_ptr(page_handle)?;
+        let ok = unsafe { (self.sym(&self.page_generate_content)?)(page) } != 0;
+        if ok {
+            self.mark_dirty(page_handle);
         }
+        Ok(ok)
     }
 
     // --- Coordinate Conversion ---
 
     /// Convert device coordinates to page coordinates.
+    #[allow(clippy::too_many_arguments)]
     pub fn device_to_page(
         &self,
         page_handle: u32,
@@ -1078,28 +1542,26 @@ impl PdfiumLibrary {
         rotation: i32,
         device_x: i32,
         device_y: i32,
-    ) -> Result<(f64, f64), String> {
-        let page = match self.handles.get(&page_handle) {
-            Some(HandleEntry::Page(page)) => *page,
-            _ => return Err("Invalid page handle".to_string()),
-        };
+    ) -> Result<(f64, f64), PdfiumError> {
+        let page = self.page_ptr(page_handle)?;
 
         let mut page_x: f64 = 0.0;
         let mut page_y: f64 = 0.0;
         let ok = unsafe {
-            (self.device_to_page)(
+            (self.sym(&self.device_to_page)?)(
                 page, start_x, start_y, size_x, size_y, rotation,
                 device_x, device_y, &mut page_x, &mut page_y,
             )
         };
         if ok == 0 {
-            Err("Device to page conversion failed".to_string())
+            Err(PdfiumError::Failed("Device to page conversion failed".to_string()))
         } else {
             Ok((page_x, page_y))
         }
     }
 
     /// Convert page coordinates to device coordinates.
+    #[allow(clippy::too_many_arguments)]
     pub fn page_to_device(
         &self,
         page_handle: u32,
@@ -1110,22 +1572,19 @@ impl PdfiumLibrary {
         rotation: i32,
         page_x: f64,
         page_y: f64,
-    ) -> Result<(i32, i32), String> {
-        let page = match self.handles.get(&page_handle) {
-            Some(HandleEntry::Page(page)) => *page,
-            _ => return Err("Invalid page handle".to_string()),
-        };
+    ) -> Result<(i32, i32), PdfiumError> {
+        let page = self.page_ptr(page_handle)?;
 
         let mut device_x: c_int = 0;
         let mut device_y: c_int = 0;
         let ok = unsafe {
-            (self.page_to_device)(
+            (self.sym(&self.page_to_device)?)(
                 page, start_x, start_y, size_x, size_y, rotation,
                 page_x, page_y, &mut device_x, &mut device_y,
             )
         };
         if ok == 0 {
-            Err("Page to device conversion failed".to_string())
+            Err(PdfiumError::Failed("Page to device conversion failed".to_string()))
         } else {
             Ok((device_x, device_y))
         }
@@ -1133,13 +1592,10 @@ impl PdfiumLibrary {
 
     // --- Metadata ---
 
-    pub fn get_meta_text(&self, doc_handle: u32, tag: &str) -> Result<Option<String>, String> {
-        let doc = match self.handles.get(&doc_handle) {
-            Some(HandleEntry::Document(doc)) => *doc,
-            _ => return Err("Invalid document handle".to_string()),
-        };
+    pub fn get_meta_text(&self, doc_handle: u32, tag: &str) -> Result<Option<String>, PdfiumError> {
+        let doc = self.document_ptr(doc_handle)?;
 
-        let tag_cstr = CString::new(tag).map_err(|e| format!("Invalid tag: {}", e))?;
+        let tag_cstr = CString::new(tag).map_err(|e| PdfiumError::InvalidArgument(format!("tag: {}", e)))?;
 
         // First call: get required buffer size (in bytes, including null terminator)
         let size = unsafe { (self.get_meta_text)(doc, tag_cstr.as_ptr(), ptr::null_mut(), 0) };
@@ -1173,14 +1629,11 @@ impl PdfiumLibrary {
 
         String::from_utf16(&buffer[..text_len])
             .map(Some)
-            .map_err(|e| format!("UTF-16 decode error: {}", e))
+            .map_err(|e| PdfiumError::Encoding(e.to_string()))
     }
 
-    pub fn get_file_version(&self, doc_handle: u32) -> Result<Option<i32>, String> {
-        let doc = match self.handles.get(&doc_handle) {
-            Some(HandleEntry::Document(doc)) => *doc,
-            _ => return Err("Invalid document handle".to_string()),
-        };
+    pub fn get_file_version(&self, doc_handle: u32) -> Result<Option<i32>, PdfiumError> {
+        let doc = self.document_ptr(doc_handle)?;
 
         let mut version: c_int = 0;
         let ok = unsafe { (self.get_file_version)(doc, &mut version) };
@@ -1191,56 +1644,33 @@ impl PdfiumLibrary {
         }
     }
 
-    pub fn get_doc_permissions(&self, doc_handle: u32) -> Result<u32, String> {
-        match self.handles.get(&doc_handle) {
-            Some(HandleEntry::Document(doc)) => {
-                Ok(unsafe { (self.get_doc_permissions)(*doc) } as u32)
-            }
-            _ => Err("Invalid document handle".to_string()),
-        }
+    pub fn get_doc_permissions(&self, doc_handle: u32) -> Result<u32, PdfiumError> {
+        let doc = self.document_ptr(doc_handle)?;
+        Ok(unsafe { (self.get_doc_permissions)(doc) } as u32)
     }
 
-    pub fn get_doc_user_permissions(&self, doc_handle: u32) -> Result<u32, String> {
-        match self.handles.get(&doc_handle) {
-            Some(HandleEntry::Document(doc)) => {
-                Ok(unsafe { (self.get_doc_user_permissions)(*doc) } as u32)
-            }
-            _ => Err("Invalid document handle".to_string()),
-        }
+    pub fn get_doc_user_permissions(&self, doc_handle: u32) -> Result<u32, PdfiumError> {
+        let doc = self.document_ptr(doc_handle)?;
+        Ok(unsafe { (self.get_doc_user_permissions)(doc) } as u32)
     }
 
-    pub fn get_page_mode(&self, doc_handle: u32) -> Result<i32, String> {
-        match self.handles.get(&doc_handle) {
-            Some(HandleEntry::Document(doc)) => {
-                Ok(unsafe { (self.doc_get_page_mode)(*doc) })
-            }
-            _ => Err("Invalid document handle".to_string()),
-        }
+    pub fn get_page_mode(&self, doc_handle: u32) -> Result<i32, PdfiumError> {
+        let doc = self.document_ptr(doc_handle)?;
+        Ok(unsafe { (self.doc_get_page_mode)(doc) })
     }
 
-    pub fn get_security_handler_revision(&self, doc_handle: u32) -> Result<i32, String> {
-        match self.handles.get(&doc_handle) {
-            Some(HandleEntry::Document(doc)) => {
-                Ok(unsafe { (self.get_security_handler_revision)(*doc) })
-            }
-            _ => Err("Invalid document handle".to_string()),
-        }
+    pub fn get_security_handler_revision(&self, doc_handle: u32) -> Result<i32, PdfiumError> {
+        let doc = self.document_ptr(doc_handle)?;
+        Ok(unsafe { (self.get_security_handler_revision)(doc) })
     }
 
-    pub fn is_tagged(&self, doc_handle: u32) -> Result<bool, String> {
-        match self.handles.get(&doc_handle) {
-            Some(HandleEntry::Document(doc)) => {
-                Ok(unsafe { (self.catalog_is_tagged)(*doc) } != 0)
-            }
-            _ => Err("Invalid document handle".to_string()),
-        }
+    pub fn is_tagged(&self, doc_handle: u32) -> Result<bool, PdfiumError> {
+        let doc = self.document_ptr(doc_handle)?;
+        Ok(unsafe { (self.catalog_is_tagged)(doc) } != 0)
     }
 
-    pub fn get_page_label(&self, doc_handle: u32, page_index: i32) -> Result<Option<String>, String> {
-        let doc = match self.handles.get(&doc_handle) {
-            Some(HandleEntry::Document(doc)) => *doc,
-            _ => return Err("Invalid document handle".to_string()),
-        };
+    pub fn get_page_label(&self, doc_handle: u32, page_index: i32) -> Result<Option<String>, PdfiumError> {
+        let doc = self.document_ptr(doc_handle)?;
 
         let size = unsafe { (self.get_page_label)(doc, page_index, ptr::null_mut(), 0) };
         if size == 0 {
@@ -1271,7 +1701,7 @@ impl PdfiumLibrary {
 
         String::from_utf16(&buffer[..text_len])
             .map(Some)
-            .map_err(|e| format!("UTF-16 decode error: {}", e))
+            .map_err(|e| PdfiumError::Encoding(e.to_string()))
     }
 
     // --- Page Boxes ---
@@ -1279,12 +1709,10 @@ impl PdfiumLibrary {
     fn get_page_box_inner(
         &self,
         page_handle: u32,
-        getter: &Symbol<'static, FnPageGetBox>,
-    ) -> Result<Option<[f32; 4]>, String> {
-        let page = match self.handles.get(&page_handle) {
-            Some(HandleEntry::Page(page)) => *page,
-            _ => return Err("Invalid page handle".to_string()),
-        };
+        getter: &LazySymbol<FnPageGetBox>,
+    ) -> Result<Option<[f32; 4]>, PdfiumError> {
+        let page = self.page_ptr(page_handle)?;
+        let getter = self.sym(getter)?;
 
         let mut left: f32 = 0.0;
         let mut bottom: f32 = 0.0;
@@ -1302,28 +1730,27 @@ impl PdfiumLibrary {
     fn set_page_box_inner(
         &self,
         page_handle: u32,
-        setter: &Symbol<'static, FnPageSetBox>,
+        setter: &LazySymbol<FnPageSetBox>,
         left: f32,
         bottom: f32,
         right: f32,
         top: f32,
-    ) -> Result<(), String> {
-        let page = match self.handles.get(&page_handle) {
-            Some(HandleEntry::Page(page)) => *page,
-            _ => return Err("Invalid page handle".to_string()),
-        };
+    ) -> Result<(), PdfiumError> {
+        let page = self.page_ptr(page_handle)?;
+        let setter = self.sym(setter)?;
         unsafe { setter(page, left, bottom, right, top) };
+        self.mark_dirty(page_handle);
         Ok(())
     }
 
-    pub fn get_page_box(&self, page_handle: u32, box_type: i32) -> Result<Option<[f32; 4]>, String> {
+    pub fn get_page_box(&self, page_handle: u32, box_type: i32) -> Result<Option<[f32; 4]>, PdfiumError> {
         let getter = match box_type {
             0 => &self.page_get_media_box,
             1 => &self.page_get_crop_box,
             2 => &self.page_get_bleed_box,
             3 => &self.page_get_trim_box,
             4 => &self.page_get_art_box,
-            _ => return Err(format!("Invalid box type: {}", box_type)),
+            _ => return Err(PdfiumError::InvalidArgument(format!("box type: {}", box_type))),
         };
         self.get_page_box_inner(page_handle, getter)
     }
@@ -1336,20 +1763,21 @@ impl PdfiumLibrary {
         bottom: f32,
         right: f32,
         top: f32,
-    ) -> Result<(), String> {
+    ) -> Result<(), PdfiumError> {
         let setter = match box_type {
             0 => &self.page_set_media_box,
             1 => &self.page_set_crop_box,
             2 => &self.page_set_bleed_box,
             3 => &self.page_set_trim_box,
             4 => &self.page_set_art_box,
-            _ => return Err(format!("Invalid box type: {}", box_type)),
+            _ => return Err(PdfiumError::InvalidArgument(format!("box type: {}", box_type))),
         };
         self.set_page_box_inner(page_handle, setter, left, bottom, right, top)
     }
 
     // --- Render ---
 
+    /// Render the page to a tightly packed RGBA buffer.
     pub fn render_page(
         &self,
         page_handle: u32,
@@ -1358,50 +1786,809 @@ impl PdfiumLibrary {
         rotation: i32,
         flags: i32,
         bg_colour: u32,
-    ) -> Result<Vec<u8>, String> {
-        let page = match self.handles.get(&page_handle) {
-            Some(HandleEntry::Page(page)) => *page,
-            _ => return Err("Invalid page handle".to_string()),
+    ) -> Result<Vec<u8>, PdfiumError> {
+        let options = RenderOptions::new().rotation(rotation).flags(flags).background(bg_colour);
+        self.render_page_with(page_handle, width, height, &options)
+    }
+
+    /// Render the page to a new, tightly packed buffer in `options`' format.
+    pub fn render_page_with(
+        &self,
+        page_handle: u32,
+        width: i32,
+        height: i32,
+        options: &RenderOptions,
+    ) -> Result<Vec<u8>, PdfiumError> {
+        self.page_ptr(page_handle)?;
+        let stride = options.get_format().min_stride(width);
+        let mut pixel_buf = vec![0u8; stride * height.max(0) as usize];
+        self.render_page_into(page_handle, width, height, options, &mut pixel_buf, stride)?;
+        Ok(pixel_buf)
+    }
+
+    /// Render the page into `buffer`, whose rows start `stride` bytes apart.
+    ///
+    /// `buffer` must hold at least `stride * height` bytes; bytes between the
+    /// end of a row and the next stride are left untouched.
+    pub fn render_page_into(
+        &self,
+        page_handle: u32,
+        width: i32,
+        height: i32,
+        options: &RenderOptions,
+        buffer: &mut [u8],
+        stride: usize,
+    ) -> Result<(), PdfiumError> {
+        self.render_page_to(page_handle, width, height, options, RenderTarget::Buffer { buffer, stride })
+    }
+
+    /// `render_page_into`, drawing into `target`.
+    pub(crate) fn render_page_to(
+        &self,
+        page_handle: u32,
+        width: i32,
+        height: i32,
+        options: &RenderOptions,
+        target: RenderTarget<'_>,
+    ) -> Result<(), PdfiumError> {
+        if options.get_color_scheme().is_some() {
+            // Only the progressive renderer takes a colour scheme.
+            return self.render_page_cancellable_to(page_handle, width, height, options, target, || false);
+        }
+        let page = self.page_ptr(page_handle)?;
+        let form = self.form_for_render(page_handle, options)?;
+        self.with_bitmap(width, height, options, target, |bitmap, flags| {
+            unsafe {
+                (self.render_page_bitmap)(bitmap, page, 0, 0, width, height, options.get_rotation(), flags);
+            };
+            if let Some((form, draw)) = form {
+                unsafe { draw(form, bitmap, page, 0, 0, width, height, options.get_rotation(), flags) };
+            }
+            Ok(())
+        })
+    }
+
+    /// Render the part of the page that `matrix` maps into `clip`, so a
+    /// viewer can draw just the visible viewport at any zoom without
+    /// allocating a bitmap for the whole page.
+    ///
+    /// `buffer` is laid out as for `render_page_into`. The rotation in
+    /// `options` must be 0; rotate with the matrix instead.
+    ///
+    /// ```no_run
+    /// # use pdfium::{ClipRect, Matrix, PdfiumLibrary, RenderOptions};
+    /// # let lib = PdfiumLibrary::load("libpdfium.so")?;
+    /// # lib.init_library();
+    /// # let doc = lib.load_document_from_path("input.pdf", None)?;
+    /// # let page = lib.load_page(doc, 0)?;
+    /// // An 800x600 window onto the page at 800%, scrolled to (2400, 1600).
+    /// let options = RenderOptions::new();
+    /// let mut buffer = vec![0u8; 800 * 4 * 600];
+    /// lib.render_region(
+    ///     page,
+    ///     800,
+    ///     600,
+    ///     &Matrix::viewport(8.0, 2400.0, 1600.0),
+    ///     ClipRect::bitmap(800, 600),
+    ///     &options,
+    ///     &mut buffer,
+    ///     800 * 4,
+    /// )?;
+    /// # Ok::<(), pdfium::PdfiumError>(())
+    /// ```
+    #[allow(clippy::too_many_arguments)]
+    pub fn render_region(
+        &self,
+        page_handle: u32,
+        width: i32,
+        height: i32,
+        matrix: &Matrix,
+        clip: ClipRect,
+        options: &RenderOptions,
+        buffer: &mut [u8],
+        stride: usize,
+    ) -> Result<(), PdfiumError> {
+        let page = self.page_ptr(page_handle)?;
+        if options.get_rotation() != 0 {
+            return Err(PdfiumError::InvalidArgument(
+                "render_region takes its rotation from the matrix".to_string(),
+            ));
+        }
+        if options.get_draw_forms() {
+            return Err(PdfiumError::InvalidArgument(
+                "render_region cannot draw form fields".to_string(),
+            ));
+        }
+        if options.get_color_scheme().is_some() {
+            return Err(PdfiumError::InvalidArgument(
+                "render_region cannot apply a colour scheme".to_string(),
+            ));
+        }
+        if !matrix.is_invertible() {
+            return Err(PdfiumError::InvalidArgument(format!("matrix is not invertible: {:?}", matrix)));
+        }
+        let render = self.sym(&self.render_page_bitmap_with_matrix)?;
+        let raw_matrix = matrix.to_raw();
+        let raw_clip = clip.to_raw();
+        self.with_bitmap(width, height, options, RenderTarget::Buffer { buffer, stride }, |bitmap, flags| {
+            unsafe { render(bitmap, page, &raw_matrix, &raw_clip, flags) };
+            Ok(())
+        })
+    }
+
+    /// Render the page like `render_page_into`, but in steps that can be
+    /// stopped: PDFium periodically calls `should_stop`, and once it returns
+    /// true the render is abandoned with `PdfiumError::Cancelled`, leaving
+    /// `buffer` partly drawn.
+    ///
+    /// ```no_run
+    /// # use pdfium::{PdfiumLibrary, RenderOptions};
+    /// # use std::time::{Duration, Instant};
+    /// # let lib = PdfiumLibrary::load("libpdfium.so")?;
+    /// # lib.init_library();
+    /// # let doc = lib.load_document_from_path("drawing.pdf", None)?;
+    /// # let page = lib.load_page(doc, 0)?;
+    /// let deadline = Instant::now() + Duration::from_secs(5);
+    /// let mut buffer = vec![0u8; 2000 * 4 * 1500];
+    /// lib.render_page_cancellable(page, 2000, 1500, &RenderOptions::new(), &mut buffer, 2000 * 4, || {
+    ///     Instant::now() >= deadline
+    /// })?;
+    /// # Ok::<(), pdfium::PdfiumError>(())
+    /// ```
+    #[allow(clippy::too_many_arguments)]
+    pub fn render_page_cancellable(
+        &self,
+        page_handle: u32,
+        width: i32,
+        height: i32,
+        options: &RenderOptions,
+        buffer: &mut [u8],
+        stride: usize,
+        should_stop: impl FnMut() -> bool,
+    ) -> Result<(), PdfiumError> {
+        let target = RenderTarget::Buffer { buffer, stride };
+        self.render_page_cancellable_to(page_handle, width, height, options, target, should_stop)
+    }
+
+    /// `render_page_cancellable`, drawing into `target`.
+    fn render_page_cancellable_to(
+        &self,
+        page_handle: u32,
+        width: i32,
+        height: i32,
+        options: &RenderOptions,
+        target: RenderTarget<'_>,
+        mut should_stop: impl FnMut() -> bool,
+    ) -> Result<(), PdfiumError> {
+        let page = self.page_ptr(page_handle)?;
+        let start = self.sym(&self.render_page_bitmap_start)?;
+        let resume = self.sym(&self.render_page_continue)?;
+        let close = self.sym(&self.render_page_close)?;
+        let scheme = match options.get_color_scheme() {
+            Some(scheme) => Some((self.sym(&self.render_page_bitmap_with_color_scheme_start)?, scheme.to_raw())),
+            None => None,
         };
+        let form = self.form_for_render(page_handle, options)?;
 
-        let stride = width * 4; // BGRA = 4 bytes per pixel
-        let buf_size = (stride * height) as usize;
-        let mut pixel_buf: Vec<u8> = vec![0u8; buf_size];
-
-        // FPDFBitmap_CreateEx with format 4 = BGRA
-        let bitmap = unsafe {
-            (self.bitmap_create_ex)(
-                width,
-                height,
-                4, // BGRA
-                pixel_buf.as_mut_ptr() as *mut c_void,
-                stride,
-            )
+        let mut pause = PauseContext::new(&mut should_stop);
+        // Already cancelled, e.g. while queued: don't start at all.
+        if pause.poll() {
+            pause.resume_panic();
+            return Err(PdfiumError::Cancelled);
+        }
+
+        let result = self.with_bitmap(width, height, options, target, |bitmap, flags| {
+            let rotation = options.get_rotation();
+            let mut status = match &scheme {
+                Some((start_with_scheme, raw)) => unsafe {
+                    start_with_scheme(bitmap, page, 0, 0, width, height, rotation, flags, raw, pause.as_ptr())
+                },
+                None => unsafe { start(bitmap, page, 0, 0, width, height, rotation, flags, pause.as_ptr()) },
+            };
+            while status == FPDF_RENDER_TOBECONTINUED && !pause.poll() {
+                status = unsafe { resume(page, pause.as_ptr()) };
+            }
+            // Releases the progressive renderer, finished or not.
+            unsafe { close(page) };
+            match status {
+                FPDF_RENDER_DONE => {
+                    if let Some((form, draw)) = form {
+                        unsafe { draw(form, bitmap, page, 0, 0, width, height, rotation, flags) };
+                    }
+                    Ok(())
+                }
+                FPDF_RENDER_TOBECONTINUED => Err(PdfiumError::Cancelled),
+                _ => Err(PdfiumError::Render("Progressive render failed".to_string())),
+            }
+        });
+        pause.resume_panic();
+        result
+    }
+
+    /// Render `page_box` at `dpi`, as the page is displayed: through its
+    /// /Rotate, then turned further by `options`' rotation. The pixel size
+    /// follows from the box and comes back with the pixels.
+    ///
+    /// A box the page does not define falls back as the PDF specification
+    /// says: the bleed, trim and art boxes to the CropBox, and the CropBox to
+    /// the visible area. The visible area renders as `render_page_with`
+    /// does; any other box goes through `render_region`, so cannot draw form
+    /// fields or take a colour scheme.
+    ///
+    /// ```no_run
+    /// # use pdfium::{PageBox, PdfiumLibrary, RenderOptions};
+    /// # let lib = PdfiumLibrary::load("libpdfium.so")?;
+    /// # lib.init_library();
+    /// # let doc = lib.load_document_from_path("input.pdf", None)?;
+    /// # let page = lib.load_page(doc, 0)?;
+    /// let image = lib.render_at_dpi(page, 300.0, PageBox::Trim, &RenderOptions::new())?;
+    /// println!("{}x{}", image.width, image.height);
+    /// # Ok::<(), pdfium::PdfiumError>(())
+    /// ```
+    pub fn render_at_dpi(
+        &self,
+        page_handle: u32,
+        dpi: f32,
+        page_box: PageBox,
+        options: &RenderOptions,
+    ) -> Result<RenderedImage, PdfiumError> {
+        if !(dpi.is_finite() && dpi > 0.0) {
+            return Err(PdfiumError::InvalidArgument(format!("dpi: {}", dpi)));
+        }
+        let visible = [0.0, 0.0, self.get_page_width(page_handle)? as f32, self.get_page_height(page_handle)? as f32];
+        let rect = match self.defined_page_box(page_handle, page_box)? {
+            Some(rect) => display_rect(rect, self.display_origin(page_handle)?, self.get_page_rotation(page_handle)?),
+            None => visible,
         };
+        let [left, top, right, bottom] = rect;
+        if right - left <= 0.0 || bottom - top <= 0.0 {
+            return Err(PdfiumError::InvalidArgument(format!("{:?} box is empty", page_box)));
+        }
 
-        if bitmap.is_null() {
-            return Err("Failed to create bitmap".to_string());
+        let zoom = dpi / 72.0;
+        let width = render_extent((right - left) * zoom)?;
+        let height = render_extent((bottom - top) * zoom)?;
+        let is_visible_area = rect.iter().zip(&visible).all(|(a, b)| (a - b).abs() < 0.01);
+        if is_visible_area {
+            return self.render_turned(page_handle, width, height, options);
+        }
+
+        let turns = options.get_rotation();
+        let matrix = Matrix::translate(-left, -top)
+            .then(Matrix::scale(width as f32 / (right - left), height as f32 / (bottom - top)))
+            .then(Matrix::quarter_turns(turns, width as f32, height as f32));
+        let (width, height) = if turns.rem_euclid(2) == 1 { (height, width) } else { (width, height) };
+        let options = options.rotation(0);
+        let format = options.get_format();
+        let stride = format.min_stride(width);
+        let mut data = vec![0u8; stride * height as usize];
+        self.render_region(
+            page_handle,
+            width,
+            height,
+            &matrix,
+            ClipRect::bitmap(width, height),
+            &options,
+            &mut data,
+            stride,
+        )?;
+        Ok(RenderedImage {
+            width: width as u32,
+            height: height as u32,
+            format,
+            data,
+        })
+    }
+
+    /// Render the page as large as fits within `max_width` x `max_height`
+    /// pixels, keeping its aspect ratio. The page is laid out as displayed,
+    /// through its CropBox and /Rotate, then turned by `options`' rotation.
+    pub fn render_fit(
+        &self,
+        page_handle: u32,
+        max_width: u32,
+        max_height: u32,
+        options: &RenderOptions,
+    ) -> Result<RenderedImage, PdfiumError> {
+        if max_width == 0 || max_width > i32::MAX as u32 || max_height == 0 || max_height > i32::MAX as u32 {
+            return Err(PdfiumError::InvalidArgument(format!("fit size: {}x{}", max_width, max_height)));
+        }
+        let page_width = self.get_page_width(page_handle)?;
+        let page_height = self.get_page_height(page_handle)?;
+        // Fit the page as it will be turned, then hand back the unturned size.
+        let (max_width, max_height) = if options.get_rotation().rem_euclid(2) == 1 {
+            (max_height, max_width)
+        } else {
+            (max_width, max_height)
+        };
+        let scale = (max_width as f64 / page_width).min(max_height as f64 / page_height);
+        let width = ((page_width * scale).round() as i32).clamp(1, max_width as i32);
+        let height = ((page_height * scale).round() as i32).clamp(1, max_height as i32);
+        self.render_turned(page_handle, width, height, options)
+    }
+
+    /// Render the visible area, `width` x `height` pixels before `options`'
+    /// rotation turns it.
+    fn render_turned(
+        &self,
+        page_handle: u32,
+        width: i32,
+        height: i32,
+        options: &RenderOptions,
+    ) -> Result<RenderedImage, PdfiumError> {
+        let (width, height) = if options.get_rotation().rem_euclid(2) == 1 {
+            (height, width)
+        } else {
+            (width, height)
+        };
+        let data = self.render_page_with(page_handle, width, height, options)?;
+        Ok(RenderedImage {
+            width: width as u32,
+            height: height as u32,
+            format: options.get_format(),
+            data,
+        })
+    }
+
+    /// `page_box` in page space, or `None` for the visible area.
+    fn defined_page_box(&self, page_handle: u32, page_box: PageBox) -> Result<Option<[f32; 4]>, PdfiumError> {
+        if let Some([left, bottom, right, top]) = self.get_page_box(page_handle, page_box.box_type())? {
+            return Ok(Some([left.min(right), bottom.min(top), left.max(right), bottom.max(top)]));
+        }
+        match page_box {
+            PageBox::Bleed | PageBox::Trim | PageBox::Art => self.defined_page_box(page_handle, PageBox::Crop),
+            // Also when the MediaBox is inherited from the page tree, which
+            // PDFium's box getters don't look at.
+            PageBox::Media | PageBox::Crop => Ok(None),
         }
+    }
+
+    /// The page-space point at the top-left corner of the displayed page.
+    fn display_origin(&self, page_handle: u32) -> Result<(f32, f32), PdfiumError> {
+        let (x, y) = self.device_to_page(page_handle, 0, 0, 1, 1, 0, 0, 0)?;
+        Ok((x as f32, y as f32))
+    }
+
+    /// Get a PDFium bitmap in `options`' format for `target`, fill it with
+    /// the background and hand it to `draw` with the render flags to use.
+    fn with_bitmap(
+        &self,
+        width: i32,
+        height: i32,
+        options: &RenderOptions,
+        target: RenderTarget<'_>,
+        draw: impl FnOnce(FPDF_BITMAP, c_int) -> Result<(), PdfiumError>,
+    ) -> Result<(), PdfiumError> {
+        let format = options.get_format();
+        let (bitmap, wrapped) = match target {
+            RenderTarget::Buffer { buffer, stride } => {
+                let stride_raw = check_buffer(format, width, height, stride, buffer.len())?;
+                let bitmap = unsafe {
+                    (self.bitmap_create_ex)(
+                        width,
+                        height,
+                        format.bitmap_format(),
+                        buffer.as_mut_ptr() as *mut c_void,
+                        stride_raw,
+                    )
+                };
+                if bitmap.is_null() {
+                    return Err(PdfiumError::Render("Failed to create bitmap".to_string()));
+                }
+                (bitmap, Some((buffer, stride)))
+            }
+            RenderTarget::Bitmap(bitmap) => (bitmap, None),
+        };
 
         // Fill background
         unsafe {
-            (self.bitmap_fill_rect)(bitmap, 0, 0, width, height, bg_colour as FPDF_DWORD);
+            (self.bitmap_fill_rect)(bitmap, 0, 0, width, height, format.fill_colour(options.get_background()) as FPDF_DWORD);
         };
 
-        // Render
-        unsafe {
-            (self.render_page_bitmap)(bitmap, page, 0, 0, width, height, rotation, flags);
+        let result = draw(bitmap, format.render_flags(options.get_flags()));
+
+        if wrapped.is_some() {
+            // Destroy the wrapper only; the pixel data is in the caller's buffer.
+            unsafe { (self.bitmap_destroy)(bitmap) };
+        }
+        result?;
+
+        if format.has_alpha() && options.get_alpha() == AlphaMode::Premultiplied {
+            let (buffer, stride) = match wrapped {
+                Some(wrapped) => wrapped,
+                None => {
+                    let stride = unsafe { (self.bitmap_get_stride)(bitmap) }.max(0) as usize;
+                    let pixels = unsafe { (self.bitmap_get_buffer)(bitmap) } as *mut u8;
+                    // SAFETY: the bitmap owns `stride * height` bytes and the
+                    // caller has exclusive use of it for the render.
+                    (unsafe { std::slice::from_raw_parts_mut(pixels, stride * height as usize) }, stride)
+                }
+            };
+            premultiply(buffer, width, height, stride);
+        }
+        Ok(())
+    }
+
+    // --- Page Objects ---
+
+    pub fn count_page_objects(&self, page_handle: u32) -> Result<i32, PdfiumError> {
+        let page = self.page_ptr(page_handle)?;
+        Ok(unsafe { (self.sym(&self.page_count_objects)?)(page) })
+    }
+
+    /// 1=Text, 2=Path, 3=Image, 4=Shading, 5=Form (0 if unknown).
+    pub fn get_page_object_type(&self, page_handle: u32, index: i32) -> Result<i32, PdfiumError> {
+        let page = self.page_ptr(page_handle)?;
+        let object = self.page_object(page, index)?;
+        Ok(unsafe { (self.sym(&self.page_obj_get_type)?)(object) })
+    }
+
+    fn page_object(&self, page: FPDF_PAGE, index: i32) -> Result<FPDF_PAGEOBJECT, PdfiumError> {
+        let object = unsafe { (self.sym(&self.page_get_object)?)(page, index) };
+        if object.is_null() {
+            return Err(PdfiumError::InvalidArgument(format!("page object index: {}", index)));
+        }
+        Ok(object)
+    }
+
+    /// Rasterise a single image or text object on its own, rather than
+    /// cropping it out of a page render.
+    ///
+    /// Images come out at their own pixel size, with their mask and the
+    /// transform from the page applied. Text is drawn at `scale` pixels per
+    /// point. The result is drawn over `options`' background in its format
+    /// and alpha mode; a rotation is rejected and the flags are ignored.
+    pub fn render_page_object(
+        &self,
+        page_handle: u32,
+        index: i32,
+        scale: f32,
+        options: &RenderOptions,
+    ) -> Result<RenderedImage, PdfiumError> {
+        let page = self.page_ptr(page_handle)?;
+        let doc_handle = self.page_document(page_handle)?;
+        let doc = self.document_ptr(doc_handle)?;
+        if options.get_rotation() != 0 {
+            return Err(PdfiumError::InvalidArgument(
+                "page objects are rendered without rotation".to_string(),
+            ));
+        }
+        if !(scale.is_finite() && scale > 0.0) {
+            return Err(PdfiumError::InvalidArgument(format!("scale: {}", scale)));
+        }
+
+        let object = self.page_object(page, index)?;
+        let bitmap = match unsafe { (self.sym(&self.page_obj_get_type)?)(object) } {
+            FPDF_PAGEOBJ_IMAGE => unsafe { (self.sym(&self.image_obj_get_rendered_bitmap)?)(doc, page, object) },
+            FPDF_PAGEOBJ_TEXT => unsafe { (self.sym(&self.text_obj_get_rendered_bitmap)?)(doc, page, object, scale) },
+            other => {
+                return Err(PdfiumError::InvalidArgument(format!(
+                    "page object {} has type {}; only text and images can be rendered",
+                    index, other
+                )))
+            }
         };
+        if bitmap.is_null() {
+            return Err(PdfiumError::Render("Failed to render page object".to_string()));
+        }
+
+        let (width, height, rgba) = self.take_bitmap_rgba(bitmap)?;
+        Ok(RenderedImage {
+            width,
+            height,
+            format: options.get_format(),
+            data: from_rgba(&rgba, options),
+        })
+    }
+
+    /// Render one annotation's normal appearance on its own, without the
+    /// page content or other annotations, at `scale` pixels per point.
+    ///
+    /// The page is copied into a scratch document, everything but the
+    /// annotation is stripped from the copy and it is cropped to the
+    /// annotation's rect, so the source document is never touched. That
+    /// makes each call cost about as much as importing the whole page plus a
+    /// small render; render the page with `FPDF_ANNOT` instead when drawing
+    /// many annotations at once. The copy is found by the page's index,
+    /// which follows later `import_pages` and `import_pages_by_index` calls
+    /// on the document; if the copy still doesn't match the page the call
+    /// fails rather than render the wrong annotation. `options` apply as for
+    /// `render_page_with`, except that annotations are always drawn and form
+    /// fields never are.
+    pub fn render_annotation(
+        &self,
+        page_handle: u32,
+        index: i32,
+        scale: f32,
+        options: &RenderOptions,
+    ) -> Result<RenderedImage, PdfiumError> {
+        let page = self.page_ptr(page_handle)?;
+        let doc_handle = self.page_document(page_handle)?;
+        if !(scale.is_finite() && scale > 0.0) {
+            return Err(PdfiumError::InvalidArgument(format!("scale: {}", scale)));
+        }
+        let count = unsafe { (self.sym(&self.page_get_annot_count)?)(page) };
+        let rect = self
+            .annotation_rect(page, index)?
+            .ok_or_else(|| PdfiumError::InvalidArgument(format!("annotation index: {}", index)))?;
+        let page_index = self.page_indices.borrow().get(&page_handle).copied().expect("pages record their index");
+
+        let scratch = unsafe { (self.sym(&self.create_new_document)?)() };
+        if scratch.is_null() {
+            return Err(PdfiumError::Failed("Failed to create scratch document".to_string()));
+        }
+        let scratch_handle = self.alloc_handle(HandleEntry::Document(scratch), None)?;
+        let result = self.render_isolated_annotation(scratch_handle, doc_handle, page_index, index, count, rect, scale, options);
+        self.close_handle(scratch_handle);
+        result
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn render_isolated_annotation(
+        &self,
+        scratch_handle: u32,
+        doc_handle: u32,
+        page_index: i32,
+        index: i32,
+        count: i32,
+        rect: [f32; 4],
+        scale: f32,
+        options: &RenderOptions,
+    ) -> Result<RenderedImage, PdfiumError> {
+        self.import_pages_by_index(scratch_handle, doc_handle, &[page_index], 0)?;
+        let copy_handle = self.load_page(scratch_handle, 0)?;
+        let copy = self.page_ptr(copy_handle)?;
+
+        let same_page = unsafe { (self.sym(&self.page_get_annot_count)?)(copy) } == count
+            && self.annotation_rect(copy, index)? == Some(rect);
+        if !same_page {
+            return Err(PdfiumError::Failed("Page has moved since it was loaded".to_string()));
+        }
 
-        // Destroy bitmap handle (pixel data is in our Vec)
+        let remove_object = self.sym(&self.page_remove_object)?;
+        let destroy_object = self.sym(&self.page_obj_destroy)?;
+        for i in (0..self.count_page_objects(copy_handle)?).rev() {
+            let object = self.page_object(copy, i)?;
+            if unsafe { remove_object(copy, object) } != 0 {
+                unsafe { destroy_object(object) };
+            }
+        }
+        let remove_annot = self.sym(&self.page_remove_annot)?;
+        for i in (0..count).rev().filter(|&i| i != index) {
+            unsafe { remove_annot(copy, i) };
+        }
+
+        let [left, bottom, right, top] = rect;
+        self.set_page_box(copy_handle, 1, left, bottom, right, top)?;
+        self.set_page_rotation(copy_handle, 0)?;
+
+        let width = ((right - left) * scale).ceil().clamp(1.0, i32::MAX as f32) as i32;
+        let height = ((top - bottom) * scale).ceil().clamp(1.0, i32::MAX as f32) as i32;
+        let options = options.flags(options.get_flags() | FPDF_ANNOT).draw_forms(false);
+        let data = self.render_page_with(copy_handle, width, height, &options)?;
+        Ok(RenderedImage {
+            width: width as u32,
+            height: height as u32,
+            format: options.get_format(),
+            data,
+        })
+    }
+
+    /// The annotation's rect as `[left, bottom, right, top]`, normalised.
+    fn annotation_rect(&self, page: FPDF_PAGE, index: i32) -> Result<Option<[f32; 4]>, PdfiumError> {
+        let annot = unsafe { (self.sym(&self.page_get_annot)?)(page, index) };
+        if annot.is_null() {
+            return Ok(None);
+        }
+        let mut rect = FS_RECTF {
+            left: 0.0,
+            top: 0.0,
+            right: 0.0,
+            bottom: 0.0,
+        };
+        let has_rect = unsafe { (self.sym(&self.annot_get_rect)?)(annot, &mut rect) } != 0;
+        unsafe { (self.sym(&self.page_close_annot)?)(annot) };
+        Ok(has_rect.then(|| {
+            [
+                rect.left.min(rect.right),
+                rect.bottom.min(rect.top),
+                rect.left.max(rect.right),
+                rect.bottom.max(rect.top),
+            ]
+        }))
+    }
+
+    fn page_document(&self, page_handle: u32) -> Result<u32, PdfiumError> {
+        self.handles
+            .borrow()
+            .owning_document(page_handle)
+            .ok_or(PdfiumError::InvalidHandle { expected: HandleKind::Page })
+    }
+
+    // --- Thumbnails ---
+
+    /// The page's embedded thumbnail image stream, with its filters applied.
+    pub fn get_decoded_thumbnail_data(&self, page_handle: u32) -> Result<Option<Vec<u8>>, PdfiumError> {
+        let page = self.page_ptr(page_handle)?;
+        self.thumbnail_data(page, self.sym(&self.page_get_decoded_thumbnail_data)?)
+    }
+
+    /// The page's embedded thumbnail image stream as stored, still encoded.
+    pub fn get_raw_thumbnail_data(&self, page_handle: u32) -> Result<Option<Vec<u8>>, PdfiumError> {
+        let page = self.page_ptr(page_handle)?;
+        self.thumbnail_data(page, self.sym(&self.page_get_raw_thumbnail_data)?)
+    }
+
+    fn thumbnail_data(&self, page: FPDF_PAGE, get: FnPageGetThumbnailData) -> Result<Option<Vec<u8>>, PdfiumError> {
+        // Two-call pattern: 0 means there is no thumbnail.
+        let size = unsafe { get(page, ptr::null_mut(), 0) } as usize;
+        if size == 0 {
+            return Ok(None);
+        }
+        let mut buf = vec![0u8; size];
+        let written = unsafe { get(page, buf.as_mut_ptr() as *mut c_void, size as c_ulong) } as usize;
+        buf.truncate(written);
+        Ok(Some(buf))
+    }
+
+    /// The page's embedded thumbnail decoded to an image, if it has one.
+    pub fn get_embedded_thumbnail(&self, page_handle: u32) -> Result<Option<Thumbnail>, PdfiumError> {
+        let page = self.page_ptr(page_handle)?;
+        let get_bitmap = self.sym(&self.page_get_thumbnail_as_bitmap)?;
+
+        let bitmap = unsafe { get_bitmap(page) };
+        if bitmap.is_null() {
+            return Ok(None);
+        }
+        let (width, height, data) = self.take_bitmap_rgba(bitmap)?;
+        Ok(Some(Thumbnail {
+            width,
+            height,
+            data,
+            source: ThumbnailSource::Embedded,
+        }))
+    }
+
+    /// Copy a bitmap PDFium handed over to tightly packed RGBA, then destroy it.
+    fn take_bitmap_rgba(&self, bitmap: FPDF_BITMAP) -> Result<(u32, u32, Vec<u8>), PdfiumError> {
+        let read = || {
+            let get_width = self.sym(&self.bitmap_get_width)?;
+            let get_height = self.sym(&self.bitmap_get_height)?;
+            let get_format = self.sym(&self.bitmap_get_format)?;
+            let (width, height, stride, format, buffer) = unsafe {
+                (
+                    get_width(bitmap),
+                    get_height(bitmap),
+                    (self.bitmap_get_stride)(bitmap),
+                    get_format(bitmap),
+                    (self.bitmap_get_buffer)(bitmap) as *const u8,
+                )
+            };
+            match PixelFormat::from_bitmap_format(format) {
+                Some(format) if width > 0 && height > 0 && stride > 0 && !buffer.is_null() => {
+                    let len = stride as usize * height as usize;
+                    // SAFETY: the bitmap owns `stride * height` bytes until destroyed below.
+                    let pixels = unsafe { std::slice::from_raw_parts(buffer, len) };
+                    let rgba = to_rgba(pixels, width as usize, height as usize, stride as usize, format);
+                    Ok((width as u32, height as u32, rgba))
+                }
+                _ => Err(PdfiumError::Render(format!("Unsupported bitmap format: {}", format))),
+            }
+        };
+        let result = read();
         unsafe { (self.bitmap_destroy)(bitmap) };
+        result
+    }
 
-        // Convert BGRA to RGBA in-place
-        for i in (0..buf_size).step_by(4) {
-            pixel_buf.swap(i, i + 2); // swap B and R
+    /// A preview of the page no larger than `max_edge` pixels on either side.
+    ///
+    /// Uses the embedded `/Thumb` image when the page has one, shrunk to fit
+    /// if it is up to `MAX_THUMBNAIL_SHRINK` times too large; otherwise
+    /// renders the page scaled so its longer edge is `max_edge`.
+    /// `Thumbnail::source` says which.
+    pub fn thumbnail(&self, page_handle: u32, max_edge: u32) -> Result<Thumbnail, PdfiumError> {
+        if max_edge == 0 || max_edge > i32::MAX as u32 {
+            return Err(PdfiumError::InvalidArgument(format!("thumbnail size: {}", max_edge)));
+        }
+        if self.has(&self.page_get_thumbnail_as_bitmap) {
+            // An unreadable embedded thumbnail is no reason to fail: render instead.
+            if let Ok(Some(embedded)) = self.get_embedded_thumbnail(page_handle) {
+                if let Some((width, height)) = embedded_thumbnail_size(embedded.width, embedded.height, max_edge) {
+                    let data = if (width, height) == (embedded.width, embedded.height) {
+                        embedded.data
+                    } else {
+                        let (from_width, from_height) = (embedded.width as usize, embedded.height as usize);
+                        downscale_rgba(&embedded.data, from_width, from_height, width as usize, height as usize)
+                    };
+                    return Ok(Thumbnail { width, height, data, source: embedded.source });
+                }
+            }
         }
 
-        Ok(pixel_buf)
+        let page_width = self.get_page_width(page_handle)?;
+        let page_height = self.get_page_height(page_handle)?;
+        let scale = max_edge as f64 / page_width.max(page_height);
+        let width = ((page_width * scale).round() as i32).clamp(1, max_edge as i32);
+        let height = ((page_height * scale).round() as i32).clamp(1, max_edge as i32);
+        let data = self.render_page_with(page_handle, width, height, &RenderOptions::new())?;
+        Ok(Thumbnail {
+            width: width as u32,
+            height: height as u32,
+            data,
+            source: ThumbnailSource::Rendered,
+        })
+    }
+
+    // --- Forms ---
+
+    /// Set the highlight colour (0xRRGGBB) of form fields of `field_type`,
+    /// or of every field for 0 (`FPDF_FORMFIELD_UNKNOWN`), as drawn by
+    /// renders with `RenderOptions::draw_forms`.
+    pub fn set_form_field_highlight_colour(
+        &self,
+        doc_handle: u32,
+        field_type: i32,
+        colour: u32,
+    ) -> Result<(), PdfiumError> {
+        let set = self.sym(&self.set_form_field_highlight_color)?;
+        let form = self.form_handle(doc_handle)?;
+        unsafe { set(form, field_type, colour as c_ulong) };
+        Ok(())
+    }
+
+    /// Set the opacity of the form field highlight, 0–255.
+    pub fn set_form_field_highlight_alpha(&self, doc_handle: u32, alpha: u8) -> Result<(), PdfiumError> {
+        let set = self.sym(&self.set_form_field_highlight_alpha)?;
+        let form = self.form_handle(doc_handle)?;
+        unsafe { set(form, alpha) };
+        Ok(())
+    }
+
+    /// The document's form-fill environment, created on first use.
+    fn form_handle(&self, doc_handle: u32) -> Result<FPDF_FORMHANDLE, PdfiumError> {
+        let doc = self.document_ptr(doc_handle)?;
+        if let Some(form) = self.forms.borrow().get(&doc_handle) {
+            return Ok(form.handle);
+        }
+
+        let init = self.sym(&self.init_form_fill_environment)?;
+        // Ensure it can be torn down again before creating it.
+        self.sym(&self.exit_form_fill_environment)?;
+        // No callbacks: nothing here is interactive, and JavaScript stays off.
+        let mut info = Box::new(FPDF_FORMFILLINFO {
+            version: 1,
+            fields: [ptr::null_mut(); FORMFILLINFO_FIELDS],
+        });
+        let handle = unsafe { init(doc, &mut *info) };
+        if handle.is_null() {
+            return Err(PdfiumError::Failed("Failed to initialise form fill environment".to_string()));
+        }
+        self.forms.borrow_mut().insert(doc_handle, FormFill { handle, _info: info });
+        Ok(handle)
+    }
+
+    /// The form handle and `FPDF_FFLDraw` for a render of `page_handle`, if
+    /// `options` asks for form fields.
+    fn form_for_render(
+        &self,
+        page_handle: u32,
+        options: &RenderOptions,
+    ) -> Result<Option<(FPDF_FORMHANDLE, FnFFLDraw)>, PdfiumError> {
+        if !options.get_draw_forms() {
+            return Ok(None);
+        }
+        let draw = self.sym(&self.ffl_draw)?;
+        let doc_handle = self.page_document(page_handle)?;
+        Ok(Some((self.form_handle(doc_handle)?, draw)))
+    }
+
+    fn exit_form_fill(&self, doc_handle: u32) {
+        if let Some(form) = self.forms.borrow_mut().remove(&doc_handle) {
+            // Resolved when the environment was created.
+            if let Ok(exit) = self.sym(&self.exit_form_fill_environment) {
+                unsafe { exit(form.handle) };
+            }
+        }
     }
 
     // --- Save / Export ---
@@ -1410,72 +2597,99 @@ impl PdfiumLibrary {
     ///
     /// `flags`: 0=None, 1=Incremental, 2=NoIncremental, 3=RemoveSecurity.
     /// `version`: optional PDF version (e.g. 17 for PDF 1.7). If None, uses FPDF_SaveAsCopy.
+    /// See `SaveOptions` for the typed equivalent.
     pub fn save_document(
         &self,
         doc_handle: u32,
         flags: u32,
         version: Option<i32>,
-    ) -> Result<Vec<u8>, String> {
-        let doc = match self.handles.get(&doc_handle) {
-            Some(HandleEntry::Document(doc)) => *doc,
-            _ => return Err("Invalid document handle".to_string()),
-        };
+    ) -> Result<Vec<u8>, PdfiumError> {
+        let options = SaveOptions::from_raw(flags, version)?;
+        let mut buffer = Vec::new();
+        self.save_to_writer(doc_handle, &mut buffer, &options)?;
+        Ok(buffer)
+    }
 
-        let mut ctx = SaveContext {
-            version: 1,
-            write_block: write_block_callback,
-            buffer: Vec::new(),
-        };
+    /// Save a document, streaming it to `writer` block by block as PDFium
+    /// produces it. PDFium issues many small writes, so wrap unbuffered
+    /// writers in a `BufWriter`.
+    ///
+    /// A writer error aborts the save and is returned as `PdfiumError::Io`;
+    /// a panic in the writer is resumed once PDFium has returned. A
+    /// successful save clears the document's unsaved-changes flag.
+    pub fn save_to_writer<W: Write>(
+        &self,
+        doc_handle: u32,
+        mut writer: W,
+        options: &SaveOptions,
+    ) -> Result<(), PdfiumError> {
+        let doc = self.document_ptr(doc_handle)?;
+        let (flags, version) = options.to_raw();
 
+        let mut ctx = WriterContext::new(&mut writer);
         let ok = match version {
             Some(ver) => unsafe {
-                (self.save_with_version)(doc, &mut ctx, flags as FPDF_DWORD, ver)
+                (self.save_with_version)(doc, ctx.as_ptr(), flags as FPDF_DWORD, ver)
             },
             None => unsafe {
-                (self.save_as_copy)(doc, &mut ctx, flags as FPDF_DWORD)
+                (self.save_as_copy)(doc, ctx.as_ptr(), flags as FPDF_DWORD)
             },
         };
 
-        if ok == 0 {
-            return Err("Failed to save document".to_string());
-        }
+        ctx.finish(ok != 0)?;
+        self.handles.borrow_mut().set_dirty(doc_handle, false);
+        Ok(())
+    }
 
-        Ok(ctx.buffer)
+    /// Save a document to `path`. The output goes to a temporary file in the
+    /// same directory that is renamed over `path` only once it is complete,
+    /// so an existing file is never left half-written.
+    pub fn save_to_path(
+        &self,
+        doc_handle: u32,
+        path: impl AsRef<Path>,
+        options: &SaveOptions,
+    ) -> Result<(), PdfiumError> {
+        self.document_ptr(doc_handle)?;
+        write_atomically(path.as_ref(), |out| self.save_to_writer(doc_handle, out, options))
+    }
+
+    /// Whether the document has been changed since it was loaded or last
+    /// saved. Every method that modifies a document or one of its pages
+    /// (annotations, rotation, boxes, flattening, imports) sets it.
+    pub fn is_document_dirty(&self, doc_handle: u32) -> Result<bool, PdfiumError> {
+        self.document_ptr(doc_handle)?;
+        Ok(self.handles.borrow().is_dirty(doc_handle))
     }
 
     // --- Signatures ---
 
-    pub fn get_signature_count(&self, doc_handle: u32) -> Result<i32, String> {
-        let doc = match self.handles.get(&doc_handle) {
-            Some(HandleEntry::Document(doc)) => *doc,
-            _ => return Err("Invalid document handle".to_string()),
-        };
-        Ok(unsafe { (self.get_signature_count)(doc) })
+    pub fn get_signature_count(&self, doc_handle: u32) -> Result<i32, PdfiumError> {
+        let doc = self.document_ptr(doc_handle)?;
+        Ok(unsafe { (self.sym(&self.get_signature_count)?)(doc) })
     }
 
     /// Get signature data at the given index.
     /// Returns (contents, byte_range, sub_filter, reason, time, doc_mdp_permission).
+    #[allow(clippy::type_complexity)]
     pub fn get_signature(
         &self,
         doc_handle: u32,
         index: i32,
-    ) -> Result<(Option<Vec<u8>>, Option<Vec<i32>>, Option<String>, Option<String>, Option<String>, i32), String> {
-        let doc = match self.handles.get(&doc_handle) {
-            Some(HandleEntry::Document(doc)) => *doc,
-            _ => return Err("Invalid document handle".to_string()),
-        };
+    ) -> Result<(Option<Vec<u8>>, Option<Vec<i32>>, Option<String>, Option<String>, Option<String>, i32), PdfiumError> {
+        let doc = self.document_ptr(doc_handle)?;
 
-        let sig = unsafe { (self.get_signature_object)(doc, index) };
+        let sig = unsafe { (self.sym(&self.get_signature_object)?)(doc, index) };
         if sig.is_null() {
-            return Err(format!("Failed to get signature at index {}", index));
+            return Err(PdfiumError::Failed(format!("Failed to get signature at index {}", index)));
         }
 
         // Contents
         let contents = {
-            let size = unsafe { (self.signature_get_contents)(sig, ptr::null_mut(), 0) } as usize;
+            let size = unsafe { (self.sym(&self.signature_get_contents)?)(sig, ptr::null_mut(), 0) } as usize;
             if size > 0 {
                 let mut buf = vec![0u8; size];
-                unsafe { (self.signature_get_contents)(sig, buf.as_mut_ptr() as *mut c_void, size as c_ulong) };
+                unsafe { (self.sym(&self.signature_get_contents)?)(sig, buf.as_mut_ptr() as *mut c_void, size as c_ulong) };
                 Some(buf)
             } else {
                 None
@@ -1484,10 +2698,10 @@ impl PdfiumLibrary {
 
         // Byte range
         let byte_range = {
-            let count = unsafe { (self.signature_get_byte_range)(sig, ptr::null_mut(), 0) } as usize;
+            let count = unsafe { (self.sym(&self.signature_get_byte_range)?)(sig, ptr::null_mut(), 0) } as usize;
             if count > 0 {
                 let mut buf = vec![0i32; count];
-                unsafe { (self.signature_get_byte_range)(sig, buf.as_mut_ptr(), count as c_ulong) };
+                unsafe { (self.sym(&self.signature_get_byte_range)?)(sig, buf.as_mut_ptr(), count as c_ulong) };
                 Some(buf)
             } else {
                 None
@@ -1496,10 +2710,10 @@ impl PdfiumLibrary {
 
         // Sub-filter (ASCII)
         let sub_filter = {
-            let size = unsafe { (self.signature_get_sub_filter)(sig, ptr::null_mut(), 0) } as usize;
+            let size = unsafe { (self.sym(&self.signature_get_sub_filter)?)(sig, ptr::null_mut(), 0) } as usize;
             if size > 1 {
                 let mut buf = vec![0u8; size];
-                unsafe { (self.signature_get_sub_filter)(sig, buf.as_mut_ptr() as *mut c_char, size as c_ulong) };
+                unsafe { (self.sym(&self.signature_get_sub_filter)?)(sig, buf.as_mut_ptr() as *mut c_char, size as c_ulong) };
                 // Trim null terminator
                 if buf.last() == Some(&0) { buf.pop(); }
                 String::from_utf8(buf).ok()
@@ -1510,10 +2724,10 @@ impl PdfiumLibrary {
 
         // Reason (UTF-16LE)
         let reason = {
-            let size = unsafe { (self.signature_get_reason)(sig, ptr::null_mut(), 0) } as usize;
+            let size = unsafe { (self.sym(&self.signature_get_reason)?)(sig, ptr::null_mut(), 0) } as usize;
             if size > 2 {
                 let mut buf = vec![0u8; size];
-                unsafe { (self.signature_get_reason)(sig, buf.as_mut_ptr() as *mut c_void, size as c_ulong) };
+                unsafe { (self.sym(&self.signature_get_reason)?)(sig, buf.as_mut_ptr() as *mut c_void, size as c_ulong) };
                 // UTF-16LE: strip null terminator (2 bytes), decode
                 let len = if size >= 2 { size - 2 } else { size };
                 let u16_slice: Vec<u16> = buf[..len].chunks_exact(2)
@@ -1527,10 +2741,10 @@ impl PdfiumLibrary {
 
         // Time (ASCII)
         let time = {
-            let size = unsafe { (self.signature_get_time)(sig, ptr::null_mut(), 0) } as usize;
+            let size = unsafe { (self.sym(&self.signature_get_time)?)(sig, ptr::null_mut(), 0) } as usize;
             if size > 1 {
                 let mut buf = vec![0u8; size];
-                unsafe { (self.signature_get_time)(sig, buf.as_mut_ptr() as *mut c_char, size as c_ulong) };
+                unsafe { (self.sym(&self.signature_get_time)?)(sig, buf.as_mut_ptr() as *mut c_char, size as c_ulong) };
                 if buf.last() == Some(&0) { buf.pop(); }
                 String::from_utf8(buf).ok()
             } else {
@@ -1539,41 +2753,35 @@ impl PdfiumLibrary {
         };
 
         // DocMDP permission
-        let doc_mdp = unsafe { (self.signature_get_doc_mdp_permission)(sig) };
+        let doc_mdp = unsafe { (self.sym(&self.signature_get_doc_mdp_permission)?)(sig) };
 
         Ok((contents, byte_range, sub_filter, reason, time, doc_mdp))
     }
 
     // --- Attachments ---
 
-    pub fn get_attachment_count(&self, doc_handle: u32) -> Result<i32, String> {
-        let doc = match self.handles.get(&doc_handle) {
-            Some(HandleEntry::Document(doc)) => *doc,
-            _ => return Err("Invalid document handle".to_string()),
-        };
-        Ok(unsafe { (self.doc_get_attachment_count)(doc) })
+    pub fn get_attachment_count(&self, doc_handle: u32) -> Result<i32, PdfiumError> {
+        let doc = self.document_ptr(doc_handle)?;
+        Ok(unsafe { (self.sym(&self.doc_get_attachment_count)?)(doc) })
     }
 
     /// Get an attachment by index.
     /// Returns (name, data) or None if the attachment doesn't exist or has no data.
-    pub fn get_attachment(&self, doc_handle: u32, index: i32) -> Result<Option<(String, Vec<u8>)>, String> {
-        let doc = match self.handles.get(&doc_handle) {
-            Some(HandleEntry::Document(doc)) => *doc,
-            _ => return Err("Invalid document handle".to_string()),
-        };
+    pub fn get_attachment(&self, doc_handle: u32, index: i32) -> Result<Option<(String, Vec<u8>)>, PdfiumError> {
+        let doc = self.document_ptr(doc_handle)?;
 
-        let attachment = unsafe { (self.doc_get_attachment)(doc, index) };
+        let attachment = unsafe { (self.sym(&self.doc_get_attachment)?)(doc, index) };
         if attachment.is_null() {
             return Ok(None);
         }
 
         // Get name (UTF-16LE, two-call pattern)
-        let name_size = unsafe { (self.attachment_get_name)(attachment, ptr::null_mut(), 0) } as usize;
+        let name_size = unsafe { (self.sym(&self.attachment_get_name)?)(attachment, ptr::null_mut(), 0) } as usize;
         let name = if name_size > 0 {
             let u16_len = name_size / 2;
             let mut buf: Vec<u16> = vec![0u16; u16_len];
             unsafe {
-                (self.attachment_get_name)(attachment, buf.as_mut_ptr() as *mut c_void, name_size as c_ulong);
+                (self.sym(&self.attachment_get_name)?)(attachment, buf.as_mut_ptr() as *mut c_void, name_size as c_ulong);
             }
             // Trim null terminator
             let text_len = if u16_len > 0 && buf[u16_len - 1] == 0 { u16_len - 1 } else { u16_len };
@@ -1584,13 +2792,13 @@ impl PdfiumLibrary {
 
         // Get file data (two-call pattern)
         let mut out_len: c_ulong = 0;
-        let ok = unsafe { (self.attachment_get_file)(attachment, ptr::null_mut(), 0, &mut out_len) };
+        let ok = unsafe { (self.sym(&self.attachment_get_file)?)(attachment, ptr::null_mut(), 0, &mut out_len) };
         let data = if ok != 0 && out_len > 0 {
             let size = out_len as usize;
             let mut buf = vec![0u8; size];
             let mut actual_len: c_ulong = 0;
             unsafe {
-                (self.attachment_get_file)(
+                (self.sym(&self.attachment_get_file)?)(
                     attachment,
                     buf.as_mut_ptr() as *mut c_void,
                     size as c_ulong,
@@ -1618,30 +2826,27 @@ impl PdfiumLibrary {
         src_handle: u32,
         page_range: Option<&str>,
         insert_index: i32,
-    ) -> Result<(), String> {
-        let dest_doc = match self.handles.get(&dest_handle) {
-            Some(HandleEntry::Document(doc)) => *doc,
-            _ => return Err("Invalid destination document handle".to_string()),
-        };
-        let src_doc = match self.handles.get(&src_handle) {
-            Some(HandleEntry::Document(doc)) => *doc,
-            _ => return Err("Invalid source document handle".to_string()),
-        };
+    ) -> Result<(), PdfiumError> {
+        let dest_doc = self.document_ptr(dest_handle)?;
+        let src_doc = self.document_ptr(src_handle)?;
+        let before = unsafe { (self.get_page_count)(dest_doc) };
 
         let result = match page_range {
             Some(range) => {
                 let c_range = CString::new(range)
-                    .map_err(|_| "Invalid page range string".to_string())?;
-                unsafe { (self.import_pages)(dest_doc, src_doc, c_range.as_ptr(), insert_index) }
+                    .map_err(|e| PdfiumError::InvalidArgument(format!("page range: {}", e)))?;
+                unsafe { (self.sym(&self.import_pages)?)(dest_doc, src_doc, c_range.as_ptr(), insert_index) }
             }
             None => {
-                unsafe { (self.import_pages)(dest_doc, src_doc, ptr::null(), insert_index) }
+                unsafe { (self.sym(&self.import_pages)?)(dest_doc, src_doc, ptr::null(), insert_index) }
             }
         };
 
         if result == 0 {
-            return Err("Failed to import pages".to_string());
+            return Err(PdfiumError::Failed("Failed to import pages".to_string()));
         }
+        self.pages_inserted(dest_handle, insert_index, unsafe { (self.get_page_count)(dest_doc) } - before);
+        self.mark_dirty(dest_handle);
         Ok(())
     }
 
@@ -1655,18 +2860,13 @@ impl PdfiumLibrary {
         src_handle: u32,
         page_indices: &[i32],
         insert_index: i32,
-    ) -> Result<(), String> {
-        let dest_doc = match self.handles.get(&dest_handle) {
-            Some(HandleEntry::Document(doc)) => *doc,
-            _ => return Err("Invalid destination document handle".to_string()),
-        };
-        let src_doc = match self.handles.get(&src_handle) {
-            Some(HandleEntry::Document(doc)) => *doc,
-            _ => return Err("Invalid source document handle".to_string()),
-        };
+    ) -> Result<(), PdfiumError> {
+        let dest_doc = self.document_ptr(dest_handle)?;
+        let src_doc = self.document_ptr(src_handle)?;
+        let before = unsafe { (self.get_page_count)(dest_doc) };
 
         let result = unsafe {
-            (self.import_pages_by_index)(
+            (self.sym(&self.import_pages_by_index)?)(
                 dest_doc,
                 src_doc,
                 page_indices.as_ptr(),
@@ -1676,30 +2876,41 @@ impl PdfiumLibrary {
         };
 
         if result == 0 {
-            return Err("Failed to import pages by index".to_string());
+            return Err(PdfiumError::Failed("Failed to import pages by index".to_string()));
         }
+        self.pages_inserted(dest_handle, insert_index, unsafe { (self.get_page_count)(dest_doc) } - before);
+        self.mark_dirty(dest_handle);
         Ok(())
     }
 
+    /// Move the recorded index of `doc_handle`'s loaded pages past `added`
+    /// pages just inserted at `insert_index`.
+    fn pages_inserted(&self, doc_handle: u32, insert_index: i32, added: i32) {
+        let handles = self.handles.borrow();
+        for (&page_handle, index) in self.page_indices.borrow_mut().iter_mut() {
+            if *index >= insert_index && handles.owning_document(page_handle) == Some(doc_handle) {
+                *index += added;
+            }
+        }
+    }
+
     /// Create a new document with N-up layout.
     ///
     /// Returns a new document handle where multiple source pages are
-    /// arranged on each output page.
+    /// arranged on each output page. The new document is closed automatically
+    /// when the source document is closed.
     pub fn import_n_pages_to_one(
-        &mut self,
+        &self,
         src_handle: u32,
         output_width: f32,
         output_height: f32,
         pages_per_row: usize,
         pages_per_column: usize,
-    ) -> Result<u32, String> {
-        let src_doc = match self.handles.get(&src_handle) {
-            Some(HandleEntry::Document(doc)) => *doc,
-            _ => return Err("Invalid source document handle".to_string()),
-        };
+    ) -> Result<u32, PdfiumError> {
+        let src_doc = self.document_ptr(src_handle)?;
 
         let new_doc = unsafe {
-            (self.import_n_pages_to_one)(
+            (self.sym(&self.import_n_pages_to_one)?)(
                 src_doc,
                 output_width,
                 output_height,
@@ -1709,10 +2920,13 @@ impl PdfiumLibrary {
         };
 
         if new_doc.is_null() {
-            return Err("Failed to create N-up document".to_string());
+            return Err(PdfiumError::Failed("Failed to create N-up document".to_string()));
         }
 
-        let handle = self.alloc_handle(HandleEntry::Document(new_doc));
+        // The N-up document is owned by its source: closing the source closes it too.
+        let handle = self.alloc_handle(HandleEntry::Document(new_doc), Some(src_handle))?;
+        // It exists only in memory until it is saved.
+        self.mark_dirty(handle);
         Ok(handle)
     }
 
@@ -1721,17 +2935,14 @@ impl PdfiumLibrary {
         &self,
         dest_handle: u32,
         src_handle: u32,
-    ) -> Result<bool, String> {
-        let dest_doc = match self.handles.get(&dest_handle) {
-            Some(HandleEntry::Document(doc)) => *doc,
-            _ => return Err("Invalid destination document handle".to_string()),
-        };
-        let src_doc = match self.handles.get(&src_handle) {
-            Some(HandleEntry::Document(doc)) => *doc,
-            _ => return Err("Invalid source document handle".to_string()),
-        };
+    ) -> Result<bool, PdfiumError> {
+        let dest_doc = self.document_ptr(dest_handle)?;
+        let src_doc = self.document_ptr(src_handle)?;
 
-        let result = unsafe { (self.copy_viewer_preferences)(dest_doc, src_doc) };
+        let result = unsafe { (self.sym(&self.copy_viewer_preferences)?)(dest_doc, src_doc) };
+        if result != 0 {
+            self.mark_dirty(dest_handle);
+        }
         Ok(result != 0)
     }
 
@@ -1744,13 +2955,10 @@ impl PdfiumLibrary {
     ///
     /// Returns a flat list of top-level bookmarks, each with nested children.
     /// `page_index` is -1 when the bookmark has no in-document destination.
-    pub fn get_bookmarks(&self, doc_handle: u32) -> Result<Vec<BookmarkNode>, String> {
-        let doc = match self.handles.get(&doc_handle) {
-            Some(HandleEntry::Document(doc)) => *doc,
-            _ => return Err("Invalid document handle".to_string()),
-        };
+    pub fn get_bookmarks(&self, doc_handle: u32) -> Result<Vec<BookmarkNode>, PdfiumError> {
+        let doc = self.document_ptr(doc_handle)?;
 
-        Ok(self.collect_bookmarks(doc, ptr::null_mut(), 0))
+        self.collect_bookmarks(doc, ptr::null_mut(), 0)
     }
 
     fn collect_bookmarks(
@@ -1758,35 +2966,35 @@ impl PdfiumLibrary {
         doc: FPDF_DOCUMENT,
         parent: FPDF_BOOKMARK,
         depth: usize,
-    ) -> Vec<BookmarkNode> {
+    ) -> Result<Vec<BookmarkNode>, PdfiumError> {
         if depth > Self::MAX_BOOKMARK_DEPTH {
-            return Vec::new();
+            return Ok(Vec::new());
         }
 
         let mut result = Vec::new();
-        let mut current = unsafe { (self.bookmark_get_first_child)(doc, parent) };
+        let mut current = unsafe { (self.sym(&self.bookmark_get_first_child)?)(doc, parent) };
 
         while !current.is_null() {
-            let title = self.read_bookmark_title(current);
+            let title = self.read_bookmark_title(current)?;
 
-            let dest = unsafe { (self.bookmark_get_dest)(doc, current) };
+            let dest = unsafe { (self.sym(&self.bookmark_get_dest)?)(doc, current) };
             let page_index = if !dest.is_null() {
-                unsafe { (self.dest_get_dest_page_index)(doc, dest) }
+                unsafe { (self.sym(&self.dest_get_dest_page_index)?)(doc, dest) }
             } else {
                 -1
             };
 
-            let children = self.collect_bookmarks(doc, current, depth + 1);
+            let children = self.collect_bookmarks(doc, current, depth + 1)?;
 
             result.push(BookmarkNode {
                 title,
                 page_index,
                 children,
             });
-            current = unsafe { (self.bookmark_get_next_sibling)(doc, current) };
+            current = unsafe { (self.sym(&self.bookmark_get_next_sibling)?)(doc, current) };
         }
 
-        result
+        Ok(result)
     }
 
     // --- Annotations ---
@@ -1795,13 +3003,10 @@ impl PdfiumLibrary {
     ///
     /// Opens each annotation, reads its subtype, bounding rect, and colour,
     /// then closes it. The caller never sees raw annotation handles.
-    pub fn get_annotations(&self, page_handle: u32) -> Result<Vec<AnnotationInfo>, String> {
-        let page = match self.handles.get(&page_handle) {
-            Some(HandleEntry::Page(page)) => *page,
-            _ => return Err("Invalid page handle".to_string()),
-        };
+    pub fn get_annotations(&self, page_handle: u32) -> Result<Vec<AnnotationInfo>, PdfiumError> {
+        let page = self.page_ptr(page_handle)?;
 
-        let count = unsafe { (self.page_get_annot_count)(page) };
+        let count = unsafe { (self.sym(&self.page_get_annot_count)?)(page) };
         if count <= 0 {
             return Ok(Vec::new());
         }
@@ -1809,12 +3014,12 @@ impl PdfiumLibrary {
         let mut result = Vec::with_capacity(count as usize);
 
         for i in 0..count {
-            let annot = unsafe { (self.page_get_annot)(page, i) };
+            let annot = unsafe { (self.sym(&self.page_get_annot)?)(page, i) };
             if annot.is_null() {
                 continue;
             }
 
-            let subtype = unsafe { (self.annot_get_subtype)(annot) };
+            let subtype = unsafe { (self.sym(&self.annot_get_subtype)?)(annot) };
 
             // Read bounding rect
             let mut rect = FS_RECTF {
@@ -1823,7 +3028,7 @@ impl PdfiumLibrary {
                 right: 0.0,
                 bottom: 0.0,
             };
-            let has_rect = unsafe { (self.annot_get_rect)(annot, &mut rect) };
+            let has_rect = unsafe { (self.sym(&self.annot_get_rect)?)(annot, &mut rect) };
             let (left, top, right, bottom) = if has_rect != 0 {
                 (rect.left, rect.top, rect.right, rect.bottom)
             } else {
@@ -1835,9 +3040,9 @@ impl PdfiumLibrary {
             let mut g: c_uint = 0;
             let mut b: c_uint = 0;
             let mut a: c_uint = 0;
-            let has_colour = unsafe { (self.annot_get_color)(annot, 0, &mut r, &mut g, &mut b, &mut a) } != 0;
+            let has_colour = unsafe { (self.sym(&self.annot_get_color)?)(annot, 0, &mut r, &mut g, &mut b, &mut a) } != 0;
 
-            unsafe { (self.page_close_annot)(annot) };
+            unsafe { (self.sym(&self.page_close_annot)?)(annot) };
 
             result.push(AnnotationInfo {
                 index: i,
@@ -1862,32 +3067,30 @@ impl PdfiumLibrary {
     /// Create a new annotation on a page.
     ///
     /// Returns the index of the newly created annotation, or an error.
-    pub fn create_annotation(&self, page_handle: u32, subtype: i32) -> Result<i32, String> {
-        let page = match self.handles.get(&page_handle) {
-            Some(HandleEntry::Page(page)) => *page,
-            _ => return Err("Invalid page handle".to_string()),
-        };
+    pub fn create_annotation(&self, page_handle: u32, subtype: i32) -> Result<i32, PdfiumError> {
+        let page = self.page_ptr(page_handle)?;
 
-        let annot = unsafe { (self.page_create_annot)(page, subtype) };
+        let annot = unsafe { (self.sym(&self.page_create_annot)?)(page, subtype) };
         if annot.is_null() {
-            return Err("Failed to create annotation".to_string());
+            return Err(PdfiumError::Failed("Failed to create annotation".to_string()));
         }
 
-        unsafe { (self.page_close_annot)(annot) };
+        unsafe { (self.sym(&self.page_close_annot)?)(annot) };
+        self.mark_dirty(page_handle);
 
         // Return the index of the new annotation (last one)
-        let count = unsafe { (self.page_get_annot_count)(page) };
+        let count = unsafe { (self.sym(&self.page_get_annot_count)?)(page) };
         Ok(count - 1)
     }
 
     /// Remove an annotation from a page by index.
-    pub fn remove_annotation(&self, page_handle: u32, index: i32) -> Result<bool, String> {
-        let page = match self.handles.get(&page_handle) {
-            Some(HandleEntry::Page(page)) => *page,
-            _ => return Err("Invalid page handle".to_string()),
-        };
+    pub fn remove_annotation(&self, page_handle: u32, index: i32) -> Result<bool, PdfiumError> {
+        let page = self.page_ptr(page_handle)?;
 
-        let result = unsafe { (self.page_remove_annot)(page, index) };
+        let result = unsafe { (self.sym(&self.page_remove_annot)?)(page, index) };
+        if result != 0 {
+            self.mark_dirty(page_handle);
+        }
         Ok(result != 0)
     }
 
@@ -1900,26 +3103,24 @@ impl PdfiumLibrary {
         top: f32,
         right: f32,
         bottom: f32,
-    ) -> Result<bool, String> {
-        let page = match self.handles.get(&page_handle) {
-            Some(HandleEntry::Page(page)) => *page,
-            _ => return Err("Invalid page handle".to_string()),
-        };
+    ) -> Result<bool, PdfiumError> {
+        let page = self.page_ptr(page_handle)?;
 
-        let annot = unsafe { (self.page_get_annot)(page, index) };
+        let annot = unsafe { (self.sym(&self.page_get_annot)?)(page, index) };
         if annot.is_null() {
             return Ok(false);
         }
 
         let rect = FS_RECTF { left, top, right, bottom };
-        let ok = unsafe { (self.annot_set_rect)(annot, &rect) };
-        unsafe { (self.page_close_annot)(annot) };
-        Ok(ok != 0)
+        let ok = unsafe { (self.sym(&self.annot_set_rect)?)(annot, &rect) };
+        unsafe { (self.sym(&self.page_close_annot)?)(annot) };
+        self.mark_dirty_if(page_handle, ok != 0)
     }
 
     /// Set the colour of an annotation.
     ///
     /// `colour_type`: 0 = colour, 1 = interior colour.
+    #[allow(clippy::too_many_arguments)]
     pub fn set_annotation_colour(
         &self,
         page_handle: u32,
@@ -1929,38 +3130,32 @@ impl PdfiumLibrary {
         g: u32,
         b: u32,
         a: u32,
-    ) -> Result<bool, String> {
-        let page = match self.handles.get(&page_handle) {
-            Some(HandleEntry::Page(page)) => *page,
-            _ => return Err("Invalid page handle".to_string()),
-        };
+    ) -> Result<bool, PdfiumError> {
+        let page = self.page_ptr(page_handle)?;
 
-        let annot = unsafe { (self.page_get_annot)(page, index) };
+        let annot = unsafe { (self.sym(&self.page_get_annot)?)(page, index) };
         if annot.is_null() {
             return Ok(false);
         }
 
         let ok = unsafe {
-            (self.annot_set_color)(annot, colour_type, r as c_uint, g as c_uint, b as c_uint, a as c_uint)
+            (self.sym(&self.annot_set_color)?)(annot, colour_type, r as c_uint, g as c_uint, b as c_uint, a as c_uint)
         };
-        unsafe { (self.page_close_annot)(annot) };
-        Ok(ok != 0)
+        unsafe { (self.sym(&self.page_close_annot)?)(annot) };
+        self.mark_dirty_if(page_handle, ok != 0)
     }
 
     /// Get the flags of an annotation.
-    pub fn get_annotation_flags(&self, page_handle: u32, index: i32) -> Result<i32, String> {
-        let page = match self.handles.get(&page_handle) {
-            Some(HandleEntry::Page(page)) => *page,
-            _ => return Err("Invalid page handle".to_string()),
-        };
+    pub fn get_annotation_flags(&self, page_handle: u32, index: i32) -> Result<i32, PdfiumError> {
+        let page = self.page_ptr(page_handle)?;
 
-        let annot = unsafe { (self.page_get_annot)(page, index) };
+        let annot = unsafe { (self.sym(&self.page_get_annot)?)(page, index) };
         if annot.is_null() {
-            return Err(format!("Failed to get annotation at index {}", index));
+            return Err(PdfiumError::Failed(format!("Failed to get annotation at index {}", index)));
         }
 
-        let flags = unsafe { (self.annot_get_flags)(annot) };
-        unsafe { (self.page_close_annot)(annot) };
+        let flags = unsafe { (self.sym(&self.annot_get_flags)?)(annot) };
+        unsafe { (self.sym(&self.page_close_annot)?)(annot) };
         Ok(flags)
     }
 
@@ -1970,20 +3165,17 @@ impl PdfiumLibrary {
         page_handle: u32,
         index: i32,
         flags: i32,
-    ) -> Result<bool, String> {
-        let page = match self.handles.get(&page_handle) {
-            Some(HandleEntry::Page(page)) => *page,
-            _ => return Err("Invalid page handle".to_string()),
-        };
+    ) -> Result<bool, PdfiumError> {
+        let page = self.page_ptr(page_handle)?;
 
-        let annot = unsafe { (self.page_get_annot)(page, index) };
+        let annot = unsafe { (self.sym(&self.page_get_annot)?)(page, index) };
         if annot.is_null() {
             return Ok(false);
         }
 
-        let ok = unsafe { (self.annot_set_flags)(annot, flags) };
-        unsafe { (self.page_close_annot)(annot) };
-        Ok(ok != 0)
+        let ok = unsafe { (self.sym(&self.annot_set_flags)?)(annot, flags) };
+        unsafe { (self.sym(&self.page_close_annot)?)(annot) };
+        self.mark_dirty_if(page_handle, ok != 0)
     }
 
     /// Set a string value on an annotation.
@@ -1995,26 +3187,23 @@ impl PdfiumLibrary {
         index: i32,
         key: &str,
         value: &str,
-    ) -> Result<bool, String> {
-        let page = match self.handles.get(&page_handle) {
-            Some(HandleEntry::Page(page)) => *page,
-            _ => return Err("Invalid page handle".to_string()),
-        };
+    ) -> Result<bool, PdfiumError> {
+        let page = self.page_ptr(page_handle)?;
 
-        let annot = unsafe { (self.page_get_annot)(page, index) };
+        let annot = unsafe { (self.sym(&self.page_get_annot)?)(page, index) };
         if annot.is_null() {
             return Ok(false);
         }
 
-        let key_cstr = CString::new(key).map_err(|e| format!("Invalid key: {}", e))?;
+        let key_cstr = CString::new(key).map_err(|e| PdfiumError::InvalidArgument(format!("key: {}", e)))?;
 
         // Encode value as UTF-16LE with null terminator
         let mut utf16: Vec<u16> = value.encode_utf16().collect();
         utf16.push(0);
 
-        let ok = unsafe { (self.annot_set_string_value)(annot, key_cstr.as_ptr(), utf16.as_ptr()) };
-        unsafe { (self.page_close_annot)(annot) };
-        Ok(ok != 0)
+        let ok = unsafe { (self.sym(&self.annot_set_string_value)?)(annot, key_cstr.as_ptr(), utf16.as_ptr()) };
+        unsafe { (self.sym(&self.page_close_annot)?)(annot) };
+        self.mark_dirty_if(page_handle, ok != 0)
     }
 
     /// Set the border of an annotation.
@@ -2025,23 +3214,21 @@ impl PdfiumLibrary {
         horizontal_radius: f32,
         vertical_radius: f32,
         border_width: f32,
-    ) -> Result<bool, String> {
-        let page = match self.handles.get(&page_handle) {
-            Some(HandleEntry::Page(page)) => *page,
-            _ => return Err("Invalid page handle".to_string()),
-        };
+    ) -> Result<bool, PdfiumError> {
+        let page = self.page_ptr(page_handle)?;
 
-        let annot = unsafe { (self.page_get_annot)(page, index) };
+        let annot = unsafe { (self.sym(&self.page_get_annot)?)(page, index) };
         if annot.is_null() {
             return Ok(false);
         }
 
-        let ok = unsafe { (self.annot_set_border)(annot, horizontal_radius, vertical_radius, border_width) };
-        unsafe { (self.page_close_annot)(annot) };
-        Ok(ok != 0)
+        let ok = unsafe { (self.sym(&self.annot_set_border)?)(annot, horizontal_radius, vertical_radius, border_width) };
+        unsafe { (self.sym(&self.page_close_annot)?)(annot) };
+        self.mark_dirty_if(page_handle, ok != 0)
     }
 
     /// Set attachment points at a specific quad index on an annotation.
+    #[allow(clippy::too_many_arguments)]
     pub fn set_annotation_attachment_points(
         &self,
         page_handle: u32,
@@ -2051,24 +3238,22 @@ impl PdfiumLibrary {
         x2: f32, y2: f32,
         x3: f32, y3: f32,
         x4: f32, y4: f32,
-    ) -> Result<bool, String> {
-        let page = match self.handles.get(&page_handle) {
-            Some(HandleEntry::Page(page)) => *page,
-            _ => return Err("Invalid page handle".to_string()),
-        };
+    ) -> Result<bool, PdfiumError> {
+        let page = self.page_ptr(page_handle)?;
 
-        let annot = unsafe { (self.page_get_annot)(page, annot_index) };
+        let annot = unsafe { (self.sym(&self.page_get_annot)?)(page, annot_index) };
         if annot.is_null() {
             return Ok(false);
         }
 
         let qp = FS_QUADPOINTSF { x1, y1, x2, y2, x3, y3, x4, y4 };
-        let ok = unsafe { (self.annot_set_attachment_points)(annot, quad_index, &qp) };
-        unsafe { (self.page_close_annot)(annot) };
-        Ok(ok != 0)
+        let ok = unsafe { (self.sym(&self.annot_set_attachment_points)?)(annot, quad_index, &qp) };
+        unsafe { (self.sym(&self.page_close_annot)?)(annot) };
+        self.mark_dirty_if(page_handle, ok != 0)
     }
 
     /// Append attachment points to an annotation.
+    #[allow(clippy::too_many_arguments)]
     pub fn append_annotation_attachment_points(
         &self,
         page_handle: u32,
@@ -2077,21 +3262,18 @@ impl PdfiumLibrary {
         x2: f32, y2: f32,
         x3: f32, y3: f32,
         x4: f32, y4: f32,
-    ) -> Result<bool, String> {
-        let page = match self.handles.get(&page_handle) {
-            Some(HandleEntry::Page(page)) => *page,
-            _ => return Err("Invalid page handle".to_string()),
-        };
+    ) -> Result<bool, PdfiumError> {
+        let page = self.page_ptr(page_handle)?;
 
-        let annot = unsafe { (self.page_get_annot)(page, annot_index) };
+        let annot = unsafe { (self.sym(&self.page_get_annot)?)(page, annot_index) };
         if annot.is_null() {
             return Ok(false);
         }
 
         let qp = FS_QUADPOINTSF { x1, y1, x2, y2, x3, y3, x4, y4 };
-        let ok = unsafe { (self.annot_append_attachment_points)(annot, &qp) };
-        unsafe { (self.page_close_annot)(annot) };
-        Ok(ok != 0)
+        let ok = unsafe { (self.sym(&self.annot_append_attachment_points)?)(annot, &qp) };
+        unsafe { (self.sym(&self.page_close_annot)?)(annot) };
+        self.mark_dirty_if(page_handle, ok != 0)
     }
 
     /// Set the URI on a link annotation.
@@ -2100,21 +3282,18 @@ impl PdfiumLibrary {
         page_handle: u32,
         index: i32,
         uri: &str,
-    ) -> Result<bool, String> {
-        let page = match self.handles.get(&page_handle) {
-            Some(HandleEntry::Page(page)) => *page,
-            _ => return Err("Invalid page handle".to_string()),
-        };
+    ) -> Result<bool, PdfiumError> {
+        let page = self.page_ptr(page_handle)?;
 
-        let annot = unsafe { (self.page_get_annot)(page, index) };
+        let annot = unsafe { (self.sym(&self.page_get_annot)?)(page, index) };
         if annot.is_null() {
             return Ok(false);
         }
 
-        let uri_cstr = CString::new(uri).map_err(|e| format!("Invalid URI: {}", e))?;
-        let ok = unsafe { (self.annot_set_uri)(annot, uri_cstr.as_ptr()) };
-        unsafe { (self.page_close_annot)(annot) };
-        Ok(ok != 0)
+        let uri_cstr = CString::new(uri).map_err(|e| PdfiumError::InvalidArgument(format!("URI: {}", e)))?;
+        let ok = unsafe { (self.sym(&self.annot_set_uri)?)(annot, uri_cstr.as_ptr()) };
+        unsafe { (self.sym(&self.page_close_annot)?)(annot) };
+        self.mark_dirty_if(page_handle, ok != 0)
     }
 
     // --- Links ---
@@ -2124,29 +3303,23 @@ impl PdfiumLibrary {
     /// Uses `FPDFLink_Enumerate` to iterate links, then for each link reads
     /// bounds, action (type, URI, file path), and destination (page index,
     /// fit type, location).
-    pub fn get_links(&self, page_handle: u32, doc_handle: u32) -> Result<Vec<LinkInfo>, String> {
-        let page = match self.handles.get(&page_handle) {
-            Some(HandleEntry::Page(page)) => *page,
-            _ => return Err("Invalid page handle".to_string()),
-        };
-        let doc = match self.handles.get(&doc_handle) {
-            Some(HandleEntry::Document(doc)) => *doc,
-            _ => return Err("Invalid document handle".to_string()),
-        };
+    pub fn get_links(&self, page_handle: u32, doc_handle: u32) -> Result<Vec<LinkInfo>, PdfiumError> {
+        let page = self.page_ptr(page_handle)?;
+        let doc = self.document_ptr(doc_handle)?;
 
         let mut result = Vec::new();
         let mut start_pos: c_int = 0;
         let mut link_ptr: *mut c_void = ptr::null_mut();
         let mut index: i32 = 0;
 
-        while unsafe { (self.link_enumerate)(page, &mut start_pos, &mut link_ptr) } != 0 {
+        while unsafe { (self.sym(&self.link_enumerate)?)(page, &mut start_pos, &mut link_ptr) } != 0 {
             if link_ptr.is_null() {
                 continue;
             }
 
             // Bounds
             let mut rect = FS_RECTF { left: 0.0, top: 0.0, right: 0.0, bottom: 0.0 };
-            let has_rect = unsafe { (self.link_get_annot_rect)(link_ptr, &mut rect) } != 0;
+            let has_rect = unsafe { (self.sym(&self.link_get_annot_rect)?)(link_ptr, &mut rect) } != 0;
             let (left, bottom, right, top) = if has_rect {
                 // FS_RECTF: left, bottom, right, top (PDF coordinate order for link rects)
                 (rect.left, rect.top, rect.right, rect.bottom)
@@ -2155,30 +3328,30 @@ impl PdfiumLibrary {
             };
 
             // Action
-            let action_handle = unsafe { (self.link_get_action)(link_ptr) };
+            let action_handle = unsafe { (self.sym(&self.link_get_action)?)(link_ptr) };
             let has_action = !action_handle.is_null();
             let mut action_type: u32 = 0;
             let mut uri: Option<String> = None;
             let mut file_path: Option<String> = None;
 
             if has_action {
-                action_type = unsafe { (self.action_get_type)(action_handle) } as u32;
+                action_type = unsafe { (self.sym(&self.action_get_type)?)(action_handle) } as u32;
 
                 // URI (action type 3)
                 if action_type == 3 {
-                    uri = self.read_action_uri(doc, action_handle);
+                    uri = self.read_action_uri(doc, action_handle)?;
                 }
 
                 // File path (action type 2 = RemoteGoTo, 4 = Launch)
                 if action_type == 2 || action_type == 4 {
-                    file_path = self.read_action_file_path(action_handle);
+                    file_path = self.read_action_file_path(action_handle)?;
                 }
             }
 
             // Destination — try link's direct dest first, then action's dest
-            let mut dest_handle = unsafe { (self.link_get_dest)(doc, link_ptr) };
+            let mut dest_handle = unsafe { (self.sym(&self.link_get_dest)?)(doc, link_ptr) };
             if dest_handle.is_null() && has_action {
-                dest_handle = unsafe { (self.action_get_dest)(doc, action_handle) };
+                dest_handle = unsafe { (self.sym(&self.action_get_dest)?)(doc, action_handle) };
             }
 
             let has_dest = !dest_handle.is_null();
@@ -2192,19 +3365,19 @@ impl PdfiumLibrary {
             let mut zoom: f32 = 0.0;
 
             if has_dest {
-                dest_page_index = unsafe { (self.dest_get_dest_page_index)(doc, dest_handle) };
+                dest_page_index = unsafe { (self.sym(&self.dest_get_dest_page_index)?)(doc, dest_handle) };
 
                 let mut num_params: c_ulong = 0;
                 let mut params = [0f32; 4];
                 dest_fit_type = unsafe {
-                    (self.dest_get_view)(dest_handle, &mut num_params, params.as_mut_ptr())
+                    (self.sym(&self.dest_get_view)?)(dest_handle, &mut num_params, params.as_mut_ptr())
                 } as u32;
 
                 let mut has_x_raw: c_int = 0;
                 let mut has_y_raw: c_int = 0;
                 let mut has_zoom_raw: c_int = 0;
                 unsafe {
-                    (self.dest_get_location_in_page)(
+                    (self.sym(&self.dest_get_location_in_page)?)(
                         dest_handle,
                         &mut has_x_raw,
                         &mut has_y_raw,
@@ -2246,47 +3419,47 @@ impl PdfiumLibrary {
         Ok(result)
     }
 
-    fn read_action_uri(&self, doc: FPDF_DOCUMENT, action: FPDF_ACTION) -> Option<String> {
-        let size = unsafe { (self.action_get_uri_path)(doc, action, ptr::null_mut(), 0) } as usize;
+    fn read_action_uri(&self, doc: FPDF_DOCUMENT, action: FPDF_ACTION) -> Result<Option<String>, PdfiumError> {
+        let size = unsafe { (self.sym(&self.action_get_uri_path)?)(doc, action, ptr::null_mut(), 0) } as usize;
         if size <= 1 {
-            return None;
+            return Ok(None);
         }
         let mut buf = vec![0u8; size];
         unsafe {
-            (self.action_get_uri_path)(doc, action, buf.as_mut_ptr() as *mut c_void, size as c_ulong);
+            (self.sym(&self.action_get_uri_path)?)(doc, action, buf.as_mut_ptr() as *mut c_void, size as c_ulong);
         }
         // UTF-8, trim null
         if buf.last() == Some(&0) { buf.pop(); }
-        String::from_utf8(buf).ok()
+        Ok(String::from_utf8(buf).ok())
     }
 
-    fn read_action_file_path(&self, action: FPDF_ACTION) -> Option<String> {
-        let size = unsafe { (self.action_get_file_path)(action, ptr::null_mut(), 0) } as usize;
+    fn read_action_file_path(&self, action: FPDF_ACTION) -> Result<Option<String>, PdfiumError> {
+        let size = unsafe { (self.sym(&self.action_get_file_path)?)(action, ptr::null_mut(), 0) } as usize;
         if size <= 1 {
-            return None;
+            return Ok(None);
         }
         let mut buf = vec![0u8; size];
         unsafe {
-            (self.action_get_file_path)(action, buf.as_mut_ptr() as *mut c_void, size as c_ulong);
+            (self.sym(&self.action_get_file_path)?)(action, buf.as_mut_ptr() as *mut c_void, size as c_ulong);
         }
         // UTF-8, trim null
         if buf.last() == Some(&0) { buf.pop(); }
-        String::from_utf8(buf).ok()
+        Ok(String::from_utf8(buf).ok())
     }
 
-    fn read_bookmark_title(&self, bookmark: FPDF_BOOKMARK) -> String {
+    fn read_bookmark_title(&self, bookmark: FPDF_BOOKMARK) -> Result<String, PdfiumError> {
         let size =
-            unsafe { (self.bookmark_get_title)(bookmark, ptr::null_mut(), 0) } as usize;
+            unsafe { (self.sym(&self.bookmark_get_title)?)(bookmark, ptr::null_mut(), 0) } as usize;
         if size <= 2 {
             // <= null terminator (2 bytes for UTF-16LE)
-            return String::new();
+            return Ok(String::new());
         }
 
         let u16_len = size / 2;
         let mut buffer: Vec<u16> = vec![0u16; u16_len];
 
         unsafe {
-            (self.bookmark_get_title)(
+            (self.sym(&self.bookmark_get_title)?)(
                 bookmark,
                 buffer.as_mut_ptr() as *mut c_void,
                 size as c_ulong,
@@ -2300,8 +3473,23 @@ impl PdfiumLibrary {
             u16_len
         };
 
-        String::from_utf16(&buffer[..text_len]).unwrap_or_default()
+        Ok(String::from_utf16(&buffer[..text_len]).unwrap_or_default())
+    }
+}
+
+pub(crate) fn password_cstring(password: Option<&str>) -> Result<Option<CString>, PdfiumError> {
+    password
+        .map(|p| CString::new(p).map_err(|e| PdfiumError::InvalidArgument(format!("password: {}", e))))
+        .transpose()
+}
+
+/// Whole pixels covering `pixels`, at least one.
+fn render_extent(pixels: f32) -> Result<i32, PdfiumError> {
+    let rounded = pixels.round().max(1.0);
+    if rounded > i32::MAX as f32 {
+        return Err(PdfiumError::InvalidArgument(format!("render size: {} pixels", pixels)));
     }
+    Ok(rounded as i32)
 }
 
 #[cfg(test)]
@@ -2310,57 +3498,16 @@ mod tests {
     //! `cargo test -- --test-threads=1`
 
     use super::*;
-    use std::path::PathBuf;
-
-    /// Resolve the path to libpdfium for testing.
-    /// Uses PDFIUM_LIB_PATH env var, or falls back to npm/darwin-arm64/libpdfium.dylib.
-    fn lib_path() -> Option<PathBuf> {
-        if let Ok(path) = std::env::var("PDFIUM_LIB_PATH") {
-            return Some(PathBuf::from(path));
-        }
-
-        // Fall back to local dev path relative to crate root
-        let candidates = [
-            "npm/darwin-arm64/libpdfium.dylib",
-            "npm/darwin-x64/libpdfium.dylib",
-            "npm/linux-x64-gnu/libpdfium.so",
-            "npm/linux-arm64-gnu/libpdfium.so",
-            "npm/linux-x64-musl/libpdfium.so",
-            "npm/win32-x64-msvc/pdfium.dll",
-        ];
-
-        for candidate in &candidates {
-            let path = PathBuf::from(candidate);
-            if path.exists() {
-                return Some(path);
-            }
-        }
-
-        None
-    }
-
-    fn test_pdf_path() -> PathBuf {
-        PathBuf::from("test/fixtures/test_1.pdf")
-    }
-
-    macro_rules! skip_if_no_lib {
-        () => {
-            match lib_path() {
-                Some(p) => p,
-                None => {
-                    eprintln!("Skipping test: no libpdfium available");
-                    return;
-                }
-            }
-        };
-    }
+    use crate::test_support::{skip_if_no_lib, test_pdf_path};
+    use crate::render::{ColorScheme, PageBox, PixelFormat};
+    use crate::save::SaveMode;
 
     #[test]
     fn load_nonexistent_library_fails() {
         let result = PdfiumLibrary::load("/nonexistent/libpdfium.so");
         assert!(result.is_err());
         let err = result.err().unwrap();
-        assert!(err.contains("Failed to load library"), "Unexpected error: {}", err);
+        assert!(matches!(err, PdfiumError::LibraryLoad(_)), "Unexpected error: {}", err);
     }
 
     #[test]
@@ -2370,6 +3517,21 @@ mod tests {
         assert!(lib.is_ok(), "Failed to load library: {:?}", lib.err());
     }
 
+    #[test]
+    fn bundled_build_has_all_capabilities() {
+        let path = skip_if_no_lib!();
+        let lib = PdfiumLibrary::load(&path).unwrap();
+        let caps = lib.capabilities();
+        assert!(caps.signatures && caps.attachments && caps.annotation_editing, "{:?}", caps);
+        assert!(caps.text_search && caps.links && caps.bookmarks, "{:?}", caps);
+        assert!(caps.progressive_loading, "{:?}", caps);
+        assert!(caps.progressive_rendering, "{:?}", caps);
+        assert!(caps.forms, "{:?}", caps);
+        assert!(caps.color_schemes, "{:?}", caps);
+        assert!(caps.thumbnails, "{:?}", caps);
+        assert!(caps.object_rendering, "{:?}", caps);
+    }
+
     #[test]
     fn init_and_destroy_lifecycle() {
         let path = skip_if_no_lib!();
@@ -2378,10 +3540,48 @@ mod tests {
         lib.destroy_library();
     }
 
+    #[test]
+    fn methods_require_init() {
+        let path = skip_if_no_lib!();
+        let lib = PdfiumLibrary::load(&path).unwrap();
+        let pdf_data = std::fs::read(test_pdf_path()).expect("Failed to read test PDF");
+        assert_eq!(lib.load_document(&pdf_data, None), Err(PdfiumError::NotInitialised));
+
+        lib.init_library();
+        let doc = lib.load_document(&pdf_data, None).unwrap();
+        lib.load_page(doc, 0).unwrap();
+
+        // Destroying closes outstanding handles.
+        lib.destroy_library();
+        assert_eq!(lib.live_handle_stats().total(), 0);
+        assert_eq!(lib.get_page_count(doc), Err(PdfiumError::NotInitialised));
+
+        // A second destroy is a no-op.
+        lib.destroy_library();
+    }
+
+    #[test]
+    fn init_is_shared_between_instances() {
+        let path = skip_if_no_lib!();
+        let first = PdfiumLibrary::load(&path).unwrap();
+        let second = PdfiumLibrary::load(&path).unwrap();
+        first.init_library();
+        second.init_library();
+
+        // PDFium stays up until the last instance lets go.
+        first.destroy_library();
+        let pdf_data = std::fs::read(test_pdf_path()).expect("Failed to read test PDF");
+        let doc = second.load_document(&pdf_data, None).unwrap();
+        assert!(second.get_page_count(doc).unwrap() > 0);
+
+        // Dropping closes the document and destroys the library.
+        drop(second);
+    }
+
     #[test]
     fn load_document_and_get_page_count() {
         let path = skip_if_no_lib!();
-        let mut lib = PdfiumLibrary::load(&path).unwrap();
+        let lib = PdfiumLibrary::load(&path).unwrap();
         lib.init_library();
 
         let pdf_data = std::fs::read(test_pdf_path()).expect("Failed to read test PDF");
@@ -2398,7 +3598,7 @@ mod tests {
     #[test]
     fn load_document_invalid_data_fails() {
         let path = skip_if_no_lib!();
-        let mut lib = PdfiumLibrary::load(&path).unwrap();
+        let lib = PdfiumLibrary::load(&path).unwrap();
         lib.init_library();
 
         let result = lib.load_document(b"not a pdf", None);
@@ -2407,6 +3607,40 @@ mod tests {
         lib.destroy_library();
     }
 
+    #[test]
+    fn load_document_from_reader() {
+        let path = skip_if_no_lib!();
+        let lib = PdfiumLibrary::load(&path).unwrap();
+        lib.init_library();
+
+        let file = std::fs::File::open(test_pdf_path()).unwrap();
+        let doc = lib.load_document_from_reader(file, None).unwrap();
+        let page = lib.load_page(doc, 0).unwrap();
+        assert!(lib.get_page_width(page).unwrap() > 0.0);
+        lib.close_document(doc).unwrap();
+
+        let garbage = lib.load_document_from_reader(std::io::Cursor::new(b"not a pdf".to_vec()), None);
+        assert!(garbage.is_err());
+
+        lib.destroy_library();
+    }
+
+    #[test]
+    fn load_document_from_path() {
+        let path = skip_if_no_lib!();
+        let lib = PdfiumLibrary::load(&path).unwrap();
+        lib.init_library();
+
+        let doc = lib.load_document_from_path(test_pdf_path(), None).unwrap();
+        assert!(lib.get_page_count(doc).unwrap() > 0);
+        lib.close_document(doc).unwrap();
+
+        let missing = lib.load_document_from_path("test/fixtures/does-not-exist.pdf", None);
+        assert!(matches!(missing, Err(PdfiumError::File)));
+
+        lib.destroy_library();
+    }
+
     #[test]
     fn invalid_handle_errors() {
         let path = skip_if_no_lib!();
@@ -2423,7 +3657,7 @@ mod tests {
     #[test]
     fn page_dimensions() {
         let path = skip_if_no_lib!();
-        let mut lib = PdfiumLibrary::load(&path).unwrap();
+        let lib = PdfiumLibrary::load(&path).unwrap();
         lib.init_library();
 
         let pdf_data = std::fs::read(test_pdf_path()).expect("Failed to read test PDF");
@@ -2443,7 +3677,7 @@ mod tests {
     #[test]
     fn text_extraction() {
         let path = skip_if_no_lib!();
-        let mut lib = PdfiumLibrary::load(&path).unwrap();
+        let lib = PdfiumLibrary::load(&path).unwrap();
         lib.init_library();
 
         let pdf_data = std::fs::read(test_pdf_path()).expect("Failed to read test PDF");
@@ -2455,7 +3689,7 @@ mod tests {
         assert!(char_count >= 0);
 
         let text = lib.get_full_text(text_page).unwrap();
-        assert_eq!(text.len() > 0, char_count > 0);
+        assert_eq!(!text.is_empty(), char_count > 0);
 
         lib.close_text_page(text_page).unwrap();
         lib.close_page(page).unwrap();
@@ -2466,7 +3700,7 @@ mod tests {
     #[test]
     fn render_page_produces_rgba_buffer() {
         let path = skip_if_no_lib!();
-        let mut lib = PdfiumLibrary::load(&path).unwrap();
+        let lib = PdfiumLibrary::load(&path).unwrap();
         lib.init_library();
 
         let pdf_data = std::fs::read(test_pdf_path()).expect("Failed to read test PDF");
@@ -2487,6 +3721,345 @@ mod tests {
         lib.destroy_library();
     }
 
+    #[test]
+    fn render_page_into_honours_format_and_stride() {
+        let path = skip_if_no_lib!();
+        let lib = PdfiumLibrary::load(&path).unwrap();
+        lib.init_library();
+
+        let doc = lib.load_document_from_path(test_pdf_path(), None).unwrap();
+        let page = lib.load_page(doc, 0).unwrap();
+        let (width, height) = (60, 80);
+
+        let rgba = lib.render_page_with(page, width, height, &RenderOptions::new()).unwrap();
+        assert_eq!(rgba.len(), (width * height * 4) as usize);
+
+        // BGRA rendered into a padded buffer matches RGBA with red and blue swapped.
+        let stride = PixelFormat::Bgra.min_stride(width) + 16;
+        let mut bgra = vec![0xAAu8; stride * height as usize];
+        let options = RenderOptions::new().format(PixelFormat::Bgra);
+        lib.render_page_into(page, width, height, &options, &mut bgra, stride).unwrap();
+        for (y, row) in bgra.chunks(stride).enumerate() {
+            let expected = &rgba[y * width as usize * 4..][..width as usize * 4];
+            for (got, want) in row[..expected.len()].chunks(4).zip(expected.chunks(4)) {
+                assert_eq!([got[2], got[1], got[0], got[3]], want);
+            }
+            // Padding is left alone.
+            assert!(row[expected.len()..].iter().all(|&b| b == 0xAA));
+        }
+
+        let gray = lib
+            .render_page_with(page, width, height, &RenderOptions::new().format(PixelFormat::Gray8))
+            .unwrap();
+        assert_eq!(gray.len(), (width * height) as usize);
+
+        let mut short = vec![0u8; 10];
+        assert!(lib.render_page_into(page, width, height, &options, &mut short, stride).is_err());
+
+        lib.close_document(doc).unwrap();
+        lib.destroy_library();
+    }
+
+    #[test]
+    fn form_fields_are_drawn_on_request() {
+        let path = skip_if_no_lib!();
+        let lib = PdfiumLibrary::load(&path).unwrap();
+        lib.init_library();
+
+        let doc = lib.load_document_from_path("test/fixtures/test_6_with_form.pdf", None).unwrap();
+        let page = lib.load_page(doc, 0).unwrap();
+        lib.set_form_field_highlight_colour(doc, 0, 0xFF0000).unwrap();
+        lib.set_form_field_highlight_alpha(doc, 128).unwrap();
+
+        let plain = lib.render_page_with(page, 200, 200, &RenderOptions::new()).unwrap();
+        let options = RenderOptions::new().draw_forms(true);
+        let with_forms = lib.render_page_with(page, 200, 200, &options).unwrap();
+        assert_eq!(with_forms.len(), plain.len());
+        assert_ne!(with_forms, plain);
+
+        let mut cancellable = vec![0u8; plain.len()];
+        lib.render_page_cancellable(page, 200, 200, &options, &mut cancellable, 800, || false)
+            .unwrap();
+        assert_eq!(cancellable, with_forms);
+
+        let mut region = vec![0u8; plain.len()];
+        let clip = ClipRect::bitmap(200, 200);
+        assert!(lib
+            .render_region(page, 200, 200, &Matrix::IDENTITY, clip, &options, &mut region, 800)
+            .is_err());
+
+        // Closing the document tears down its form-fill environment first.
+        lib.close_document(doc).unwrap();
+        assert!(lib.set_form_field_highlight_alpha(doc, 128).is_err());
+        lib.destroy_library();
+    }
+
+    #[test]
+    fn color_scheme_recolours_text_but_not_the_background() {
+        let path = skip_if_no_lib!();
+        let lib = PdfiumLibrary::load(&path).unwrap();
+        lib.init_library();
+
+        let doc = lib.load_document_from_path(test_pdf_path(), None).unwrap();
+        let page = lib.load_page(doc, 0).unwrap();
+        let background = 0xFF10_1010;
+        let scheme = ColorScheme::new(0xFF00_FF00, 0xFF00_FF00, 0xFF00_FF00, 0xFF00_FF00);
+        let plain = RenderOptions::new().background(background);
+        let themed = plain.color_scheme(scheme);
+
+        let before = lib.render_page_with(page, 200, 200, &plain).unwrap();
+        let after = lib.render_page_with(page, 200, 200, &themed).unwrap();
+        assert_ne!(before, after);
+        // Untouched pixels keep the background; drawn ones turn green.
+        assert!(after.chunks(4).any(|px| px == [0x10, 0x10, 0x10, 0xFF]));
+        assert!(after.chunks(4).any(|px| px == [0x00, 0xFF, 0x00, 0xFF]));
+
+        let mut region = vec![0u8; after.len()];
+        let clip = ClipRect::bitmap(200, 200);
+        assert!(lib
+            .render_region(page, 200, 200, &Matrix::IDENTITY, clip, &themed, &mut region, 800)
+            .is_err());
+
+        lib.close_document(doc).unwrap();
+        lib.destroy_library();
+    }
+
+    #[test]
+    fn page_objects_render_on_their_own() {
+        let path = skip_if_no_lib!();
+        let lib = PdfiumLibrary::load(&path).unwrap();
+        lib.init_library();
+
+        let doc = lib.load_document_from_path("test/fixtures/test_3_with_images.pdf", None).unwrap();
+        let page = lib.load_page(doc, 0).unwrap();
+        let types: Vec<i32> = (0..lib.count_page_objects(page).unwrap())
+            .map(|i| lib.get_page_object_type(page, i).unwrap())
+            .collect();
+
+        let image = types.iter().position(|&t| t == 3).expect("fixture has an image") as i32;
+        let options = RenderOptions::new().format(PixelFormat::Bgr);
+        let rendered = lib.render_page_object(page, image, 1.0, &options).unwrap();
+        assert_eq!(rendered.format, PixelFormat::Bgr);
+        assert_eq!(rendered.data.len(), (rendered.width * rendered.height * 3) as usize);
+
+        if let Some(text) = types.iter().position(|&t| t == 1) {
+            let small = lib.render_page_object(page, text as i32, 1.0, &RenderOptions::new()).unwrap();
+            let large = lib.render_page_object(page, text as i32, 3.0, &RenderOptions::new()).unwrap();
+            assert!(large.width > small.width);
+        }
+        if let Some(other) = types.iter().position(|&t| t != 1 && t != 3) {
+            assert!(lib.render_page_object(page, other as i32, 1.0, &RenderOptions::new()).is_err());
+        }
+        assert!(lib.render_page_object(page, types.len() as i32, 1.0, &RenderOptions::new()).is_err());
+
+        lib.close_document(doc).unwrap();
+        lib.destroy_library();
+    }
+
+    #[test]
+    fn annotations_render_without_the_page() {
+        let path = skip_if_no_lib!();
+        let lib = PdfiumLibrary::load(&path).unwrap();
+        lib.init_library();
+
+        let doc = lib.load_document_from_path(test_pdf_path(), None).unwrap();
+        let page = lib.load_page(doc, 0).unwrap();
+        // A red square outline (subtype 5), 100 x 50 points.
+        let index = lib.create_annotation(page, 5).unwrap();
+        assert!(lib.set_annotation_rect(page, index, 100.0, 600.0, 200.0, 550.0).unwrap());
+        assert!(lib.set_annotation_colour(page, index, 0, 255, 0, 0, 255).unwrap());
+        let dirty = lib.is_document_dirty(doc).unwrap();
+
+        let options = RenderOptions::new().background(0);
+        let image = lib.render_annotation(page, index, 2.0, &options).unwrap();
+        assert_eq!((image.width, image.height), (200, 100));
+        // The outline is drawn; the page under its middle is not.
+        assert!(image.data.chunks(4).any(|px| px[0] > 200 && px[1] < 50 && px[3] > 200));
+        let centre = ((50 * 200 + 100) * 4) as usize;
+        assert_eq!(image.data[centre + 3], 0);
+
+        // The source document is left as it was.
+        assert_eq!(lib.is_document_dirty(doc).unwrap(), dirty);
+        assert_eq!(lib.get_annotations(page).unwrap().len() as i32, index + 1);
+        assert!(lib.render_annotation(page, index + 1, 1.0, &options).is_err());
+        assert_eq!(lib.live_handle_stats().documents, 1);
+
+        // Pages inserted ahead of it don't lose track of the page.
+        let other = lib.load_document_from_path(test_pdf_path(), None).unwrap();
+        lib.import_pages_by_index(doc, other, &[0], 0).unwrap();
+        lib.import_pages(doc, other, Some("1"), 0).unwrap();
+        let moved = lib.render_annotation(page, index, 2.0, &options).unwrap();
+        assert_eq!(moved, image);
+        lib.close_document(other).unwrap();
+
+        lib.close_document(doc).unwrap();
+        lib.destroy_library();
+    }
+
+    #[test]
+    fn embedded_thumbnails_shrink_within_limit() {
+        // Already small enough: used as is.
+        assert_eq!(embedded_thumbnail_size(100, 60, 128), Some((100, 60)));
+        assert_eq!(embedded_thumbnail_size(128, 128, 128), Some((128, 128)));
+        // A pixel over, or a few times over: shrunk to fit.
+        assert_eq!(embedded_thumbnail_size(129, 100, 128), Some((128, 99)));
+        assert_eq!(embedded_thumbnail_size(300, 512, 128), Some((75, 128)));
+        assert_eq!(embedded_thumbnail_size(1000, 1, 250), Some((250, 1)));
+        // Too far over: rendered instead.
+        assert_eq!(embedded_thumbnail_size(513, 100, 128), None);
+    }
+
+    #[test]
+    fn thumbnails_fall_back_to_a_scaled_render() {
+        let path = skip_if_no_lib!();
+        let lib = PdfiumLibrary::load(&path).unwrap();
+        lib.init_library();
+
+        let doc = lib.load_document_from_path(test_pdf_path(), None).unwrap();
+        let page = lib.load_page(doc, 0).unwrap();
+        assert_eq!(lib.get_raw_thumbnail_data(page).unwrap(), None);
+        assert_eq!(lib.get_decoded_thumbnail_data(page).unwrap(), None);
+        assert_eq!(lib.get_embedded_thumbnail(page).unwrap(), None);
+
+        let thumbnail = lib.thumbnail(page, 128).unwrap();
+        assert_eq!(thumbnail.source, ThumbnailSource::Rendered);
+        assert_eq!(thumbnail.width.max(thumbnail.height), 128);
+        assert_eq!(thumbnail.data.len(), (thumbnail.width * thumbnail.height * 4) as usize);
+        assert!(lib.thumbnail(page, 0).is_err());
+
+        lib.close_document(doc).unwrap();
+        lib.destroy_library();
+    }
+
+    #[test]
+    fn renders_are_sized_from_the_page_box_and_rotation() {
+        let path = skip_if_no_lib!();
+        let lib = PdfiumLibrary::load(&path).unwrap();
+        lib.init_library();
+
+        let doc = lib.load_document_from_path(test_pdf_path(), None).unwrap();
+        let page = lib.load_page(doc, 0).unwrap();
+        let width = lib.get_page_width(page).unwrap() as f32;
+        let height = lib.get_page_height(page).unwrap() as f32;
+        let at_dpi = |dpi, page_box, rotation| {
+            let options = RenderOptions::new().rotation(rotation);
+            let image = lib.render_at_dpi(page, dpi, page_box, &options).unwrap();
+            assert_eq!(image.data.len(), (image.width * image.height * 4) as usize);
+            (image.width, image.height)
+        };
+        let size = |w: f32, h: f32, dpi: f32| ((w * dpi / 72.0).round() as u32, (h * dpi / 72.0).round() as u32);
+
+        assert_eq!(at_dpi(144.0, PageBox::Crop, 0), size(width, height, 144.0));
+        assert_eq!(at_dpi(144.0, PageBox::Crop, 1), size(height, width, 144.0));
+        // The page defines no trim box, so it falls back to the CropBox.
+        assert_eq!(at_dpi(72.0, PageBox::Trim, 0), size(width, height, 72.0));
+
+        // Crop a 36pt margin: the media box now reaches past the visible area.
+        let [left, bottom, right, top] = lib.get_page_box(page, 0).unwrap().unwrap();
+        lib.set_page_box(page, 1, left + 36.0, bottom + 36.0, right - 36.0, top - 36.0).unwrap();
+        let (media_width, media_height) = (right - left, top - bottom);
+        assert_eq!(at_dpi(72.0, PageBox::Crop, 0), size(media_width - 72.0, media_height - 72.0, 72.0));
+        assert_eq!(at_dpi(72.0, PageBox::Media, 0), size(media_width, media_height, 72.0));
+
+        // /Rotate turns every box, and the option's rotation turns it further.
+        lib.set_page_rotation(page, 1).unwrap();
+        assert_eq!(at_dpi(72.0, PageBox::Media, 0), size(media_height, media_width, 72.0));
+        assert_eq!(at_dpi(72.0, PageBox::Media, 1), size(media_width, media_height, 72.0));
+        assert_eq!(at_dpi(72.0, PageBox::Crop, 0), size(media_height - 72.0, media_width - 72.0, 72.0));
+
+        let fitted = lib.render_fit(page, 200, 200, &RenderOptions::new()).unwrap();
+        assert_eq!(fitted.width.max(fitted.height), 200);
+        assert_eq!(fitted.width > fitted.height, media_height > media_width);
+        let turned = lib.render_fit(page, 200, 200, &RenderOptions::new().rotation(1)).unwrap();
+        assert_eq!((turned.width, turned.height), (fitted.height, fitted.width));
+
+        assert!(lib.render_at_dpi(page, 0.0, PageBox::Crop, &RenderOptions::new()).is_err());
+        assert!(lib.render_fit(page, 0, 200, &RenderOptions::new()).is_err());
+
+        lib.close_document(doc).unwrap();
+        lib.destroy_library();
+    }
+
+    #[test]
+    fn render_region_matches_the_same_area_of_a_full_render() {
+        let path = skip_if_no_lib!();
+        let lib = PdfiumLibrary::load(&path).unwrap();
+        lib.init_library();
+
+        let doc = lib.load_document_from_path(test_pdf_path(), None).unwrap();
+        let page = lib.load_page(doc, 0).unwrap();
+        let zoom = 2.0;
+        let full_width = (lib.get_page_width(page).unwrap() * zoom) as i32;
+        let full_height = (lib.get_page_height(page).unwrap() * zoom) as i32;
+        let options = RenderOptions::new();
+        let full = lib.render_page_with(page, full_width, full_height, &options).unwrap();
+
+        // A 64x64 tile from the middle of the page.
+        let (x, y, size) = (full_width / 2, full_height / 2, 64);
+        let stride = size as usize * 4;
+        let mut tile = vec![0u8; stride * size as usize];
+        let matrix = Matrix::viewport(zoom as f32, x as f32, y as f32);
+        lib.render_region(page, size, size, &matrix, ClipRect::bitmap(size, size), &options, &mut tile, stride)
+            .unwrap();
+
+        let full_stride = full_width as usize * 4;
+        let differing = tile
+            .chunks(stride)
+            .enumerate()
+            .flat_map(|(row, tile_row)| {
+                let start = (y as usize + row) * full_stride + x as usize * 4;
+                tile_row.iter().zip(&full[start..start + stride])
+            })
+            .filter(|(a, b)| a.abs_diff(**b) > 8)
+            .count();
+        // Allow for rounding in the page-to-device transform at the edges.
+        assert!(differing < tile.len() / 50, "{} of {} bytes differ", differing, tile.len());
+
+        let rotated = RenderOptions::new().rotation(1);
+        assert!(lib
+            .render_region(page, size, size, &matrix, ClipRect::bitmap(size, size), &rotated, &mut tile, stride)
+            .is_err());
+        let singular = Matrix::scale(0.0, 0.0);
+        assert!(lib
+            .render_region(page, size, size, &singular, ClipRect::bitmap(size, size), &options, &mut tile, stride)
+            .is_err());
+
+        lib.close_document(doc).unwrap();
+        lib.destroy_library();
+    }
+
+    #[test]
+    fn cancellable_render_matches_and_can_be_stopped() {
+        let path = skip_if_no_lib!();
+        let lib = PdfiumLibrary::load(&path).unwrap();
+        lib.init_library();
+
+        let doc = lib.load_document_from_path(test_pdf_path(), None).unwrap();
+        let page = lib.load_page(doc, 0).unwrap();
+        let options = RenderOptions::new();
+        let (width, height) = (120, 160);
+        let stride = width as usize * 4;
+        let expected = lib.render_page_with(page, width, height, &options).unwrap();
+
+        let mut buffer = vec![0u8; stride * height as usize];
+        lib.render_page_cancellable(page, width, height, &options, &mut buffer, stride, || false)
+            .unwrap();
+        assert_eq!(buffer, expected);
+
+        let err = lib
+            .render_page_cancellable(page, width, height, &options, &mut buffer, stride, || true)
+            .unwrap_err();
+        assert_eq!(err, PdfiumError::Cancelled);
+
+        // The page renders normally after a cancelled render.
+        lib.render_page_cancellable(page, width, height, &options, &mut buffer, stride, || false)
+            .unwrap();
+        assert_eq!(buffer, expected);
+
+        lib.close_document(doc).unwrap();
+        lib.destroy_library();
+    }
+
     #[test]
     fn render_invalid_page_handle_fails() {
         let path = skip_if_no_lib!();
@@ -2502,7 +4075,7 @@ mod tests {
     #[test]
     fn close_document_frees_handle() {
         let path = skip_if_no_lib!();
-        let mut lib = PdfiumLibrary::load(&path).unwrap();
+        let lib = PdfiumLibrary::load(&path).unwrap();
         lib.init_library();
 
         let pdf_data = std::fs::read(test_pdf_path()).expect("Failed to read test PDF");
@@ -2516,10 +4089,33 @@ mod tests {
         lib.destroy_library();
     }
 
+    #[test]
+    fn close_document_closes_children() {
+        let path = skip_if_no_lib!();
+        let lib = PdfiumLibrary::load(&path).unwrap();
+        lib.init_library();
+
+        let pdf_data = std::fs::read(test_pdf_path()).expect("Failed to read test PDF");
+        let doc = lib.load_document(&pdf_data, None).unwrap();
+        let page = lib.load_page(doc, 0).unwrap();
+        let text_page = lib.load_text_page(page).unwrap();
+        let nup = lib.import_n_pages_to_one(doc, 842.0, 595.0, 2, 1).unwrap();
+        assert_eq!(lib.live_handle_stats(), HandleStats { documents: 2, pages: 1, text_pages: 1 });
+        lib.close_document(doc).unwrap();
+        assert_eq!(lib.live_handle_stats().total(), 0);
+
+        assert!(matches!(lib.get_page_width(page), Err(PdfiumError::StaleHandle { .. })));
+        assert!(matches!(lib.count_text_chars(text_page), Err(PdfiumError::StaleHandle { .. })));
+        assert!(matches!(lib.get_page_count(nup), Err(PdfiumError::StaleHandle { .. })));
+        assert!(lib.close_page(page).is_err());
+
+        lib.destroy_library();
+    }
+
     #[test]
     fn metadata_extraction() {
         let path = skip_if_no_lib!();
-        let mut lib = PdfiumLibrary::load(&path).unwrap();
+        let lib = PdfiumLibrary::load(&path).unwrap();
         lib.init_library();
 
         let pdf_data = std::fs::read(test_pdf_path()).expect("Failed to read test PDF");
@@ -2541,7 +4137,7 @@ mod tests {
     #[test]
     fn file_version_and_permissions() {
         let path = skip_if_no_lib!();
-        let mut lib = PdfiumLibrary::load(&path).unwrap();
+        let lib = PdfiumLibrary::load(&path).unwrap();
         lib.init_library();
 
         let pdf_data = std::fs::read(test_pdf_path()).expect("Failed to read test PDF");
@@ -2567,7 +4163,7 @@ mod tests {
     #[test]
     fn page_boxes() {
         let path = skip_if_no_lib!();
-        let mut lib = PdfiumLibrary::load(&path).unwrap();
+        let lib = PdfiumLibrary::load(&path).unwrap();
         lib.init_library();
 
         let pdf_data = std::fs::read(test_pdf_path()).expect("Failed to read test PDF");
@@ -2598,7 +4194,7 @@ mod tests {
     #[test]
     fn char_font_info() {
         let path = skip_if_no_lib!();
-        let mut lib = PdfiumLibrary::load(&path).unwrap();
+        let lib = PdfiumLibrary::load(&path).unwrap();
         lib.init_library();
 
         let pdf_data = std::fs::read(test_pdf_path()).expect("Failed to read test PDF");
@@ -2620,7 +4216,7 @@ mod tests {
             assert!(!name.is_empty(), "Font name should not be empty");
 
             let render_mode = lib.get_char_render_mode(text_page, 0).unwrap();
-            assert!(render_mode >= 0 && render_mode <= 7, "Render mode should be 0-7");
+            assert!((0..=7).contains(&render_mode), "Render mode should be 0-7");
         }
 
         lib.close_text_page(text_page).unwrap();
@@ -2632,7 +4228,7 @@ mod tests {
     #[test]
     fn save_document() {
         let path = skip_if_no_lib!();
-        let mut lib = PdfiumLibrary::load(&path).unwrap();
+        let lib = PdfiumLibrary::load(&path).unwrap();
         lib.init_library();
 
         let pdf_data = std::fs::read(test_pdf_path()).expect("Failed to read test PDF");
@@ -2652,10 +4248,67 @@ mod tests {
         lib.destroy_library();
     }
 
+    #[test]
+    fn save_to_writer_and_path() {
+        let path = skip_if_no_lib!();
+        let lib = PdfiumLibrary::load(&path).unwrap();
+        lib.init_library();
+
+        let doc = lib.load_document_from_path(test_pdf_path(), None).unwrap();
+        let expected = lib.save_document(doc, 0, None).unwrap();
+
+        let mut streamed = Vec::new();
+        lib.save_to_writer(doc, &mut streamed, &SaveOptions::new()).unwrap();
+        assert_eq!(streamed, expected);
+
+        struct FullDisk;
+        impl Write for FullDisk {
+            fn write(&mut self, _: &[u8]) -> std::io::Result<usize> {
+                Err(std::io::Error::other("no space left"))
+            }
+            fn flush(&mut self) -> std::io::Result<()> {
+                Ok(())
+            }
+        }
+        let err = lib.save_to_writer(doc, FullDisk, &SaveOptions::new()).unwrap_err();
+        assert_eq!(err, PdfiumError::Io("no space left".into()));
+
+        let out = std::env::temp_dir().join(format!("pdfium-save-{}.pdf", std::process::id()));
+        lib.save_to_path(doc, &out, &SaveOptions::new()).unwrap();
+        assert_eq!(std::fs::read(&out).unwrap(), expected);
+        std::fs::remove_file(&out).unwrap();
+
+        lib.close_document(doc).unwrap();
+        lib.destroy_library();
+    }
+
+    #[test]
+    fn edits_mark_the_document_dirty_until_saved() {
+        let path = skip_if_no_lib!();
+        let lib = PdfiumLibrary::load(&path).unwrap();
+        lib.init_library();
+
+        let doc = lib.load_document_from_path(test_pdf_path(), None).unwrap();
+        assert!(!lib.is_document_dirty(doc).unwrap());
+
+        let page = lib.load_page(doc, 0).unwrap();
+        assert!(!lib.is_document_dirty(doc).unwrap());
+        lib.set_page_rotation(page, 1).unwrap();
+        assert!(lib.is_document_dirty(doc).unwrap());
+
+        let options = SaveOptions::new().mode(SaveMode::Incremental);
+        lib.save_to_writer(doc, Vec::new(), &options).unwrap();
+        assert!(!lib.is_document_dirty(doc).unwrap());
+
+        lib.close_document(doc).unwrap();
+        assert!(lib.is_document_dirty(doc).is_err());
+        lib.destroy_library();
+    }
+
     #[test]
     fn attachments() {
         let path = skip_if_no_lib!();
-        let mut lib = PdfiumLibrary::load(&path).unwrap();
+        let lib = PdfiumLibrary::load(&path).unwrap();
         lib.init_library();
 
         let pdf_data = std::fs::read(test_pdf_path()).expect("Failed to read test PDF");
@@ -2681,7 +4334,7 @@ mod tests {
     #[test]
     fn bookmarks() {
         let path = skip_if_no_lib!();
-        let mut lib = PdfiumLibrary::load(&path).unwrap();
+        let lib = PdfiumLibrary::load(&path).unwrap();
         lib.init_library();
 
         // test_1.pdf likely has no bookmarks
@@ -2713,7 +4366,7 @@ mod tests {
     #[test]
     fn annotations() {
         let path = skip_if_no_lib!();
-        let mut lib = PdfiumLibrary::load(&path).unwrap();
+        let lib = PdfiumLibrary::load(&path).unwrap();
         lib.init_library();
 
         let pdf_data = std::fs::read(test_pdf_path()).expect("Failed to read test PDF");
@@ -2735,7 +4388,7 @@ mod tests {
     #[test]
     fn links() {
         let path = skip_if_no_lib!();
-        let mut lib = PdfiumLibrary::load(&path).unwrap();
+        let lib = PdfiumLibrary::load(&path).unwrap();
         lib.init_library();
 
         let pdf_data = std::fs::read(test_pdf_path()).expect("Failed to read test PDF");
@@ -2762,7 +4415,7 @@ mod tests {
     #[test]
     fn annotation_mutations() {
         let path = skip_if_no_lib!();
-        let mut lib = PdfiumLibrary::load(&path).unwrap();
+        let lib = PdfiumLibrary::load(&path).unwrap();
         lib.init_library();
 
         let pdf_data = std::fs::read(test_pdf_path()).expect("Failed to read test PDF");
@@ -2815,7 +4468,7 @@ mod tests {
     #[test]
     fn text_character_operations() {
         let path = skip_if_no_lib!();
-        let mut lib = PdfiumLibrary::load(&path).unwrap();
+        let lib = PdfiumLibrary::load(&path).unwrap();
         lib.init_library();
 
         let pdf_data = std::fs::read(test_pdf_path()).expect("Failed to read test PDF");
@@ -2871,7 +4524,7 @@ mod tests {
     #[test]
     fn text_search_and_rects() {
         let path = skip_if_no_lib!();
-        let mut lib = PdfiumLibrary::load(&path).unwrap();
+        let lib = PdfiumLibrary::load(&path).unwrap();
         lib.init_library();
 
         let pdf_data = std::fs::read(test_pdf_path()).expect("Failed to read test PDF");
@@ -2915,7 +4568,7 @@ mod tests {
     #[test]
     fn page_rotation_and_flatten() {
         let path = skip_if_no_lib!();
-        let mut lib = PdfiumLibrary::load(&path).unwrap();
+        let lib = PdfiumLibrary::load(&path).unwrap();
         lib.init_library();
 
         let pdf_data = std::fs::read(test_pdf_path()).expect("Failed to read test PDF");
@@ -2924,7 +4577,7 @@ mod tests {
 
         // Get rotation
         let rotation = lib.get_page_rotation(page).unwrap();
-        assert!(rotation >= 0 && rotation <= 3, "Rotation should be 0-3");
+        assert!((0..=3).contains(&rotation), "Rotation should be 0-3");
 
         // Set rotation
         lib.set_page_rotation(page, 1).unwrap();
@@ -2939,7 +4592,7 @@ mod tests {
 
         // Flatten (2 means nothing to flatten, which is ok for this test PDF)
         let flatten_result = lib.flatten_page(page, 0).unwrap();
-        assert!(flatten_result >= 0 && flatten_result <= 2);
+        assert!((0..=2).contains(&flatten_result));
 
         // Generate content
         let ok = lib.generate_content(page).unwrap();
@@ -2953,7 +4606,7 @@ mod tests {
     #[test]
     fn coordinate_conversion() {
         let path = skip_if_no_lib!();
-        let mut lib = PdfiumLibrary::load(&path).unwrap();
+        let lib = PdfiumLibrary::load(&path).unwrap();
         lib.init_library();
 
         let pdf_data = std::fs::read(test_pdf_path()).expect("Failed to read test PDF");
@@ -2981,7 +4634,7 @@ mod tests {
     #[test]
     fn import_pages() {
         let path = skip_if_no_lib!();
-        let mut lib = PdfiumLibrary::load(&path).unwrap();
+        let lib = PdfiumLibrary::load(&path).unwrap();
         lib.init_library();
 
         let pdf_data = std::fs::read(test_pdf_path()).expect("Failed to read test PDF");
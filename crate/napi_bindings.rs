@@ -1,9 +1,37 @@
 //! napi-rs bindings exposing PdfiumLibrary to Node.js.
 
-use crate::{AnnotationInfo, BookmarkNode, LinkInfo, PdfiumLibrary};
+use crate::{
+    AlphaMode, AnnotationInfo, BookmarkNode, Capabilities, ClipRect, ColorScheme, InitOptions, LinkInfo, Matrix, PageBox,
+    PdfiumError, PdfiumLibrary, PixelFormat, RenderFlags, RenderOptions, RenderedImage, RendererType, SaveOptions,
+    SharedPdfium, ThumbnailSource, Tile, TilePyramid, DEFAULT_QUEUE_CAPACITY,
+};
+use crate::init::BinaryKey;
+#[cfg(feature = "encode")]
+use crate::ImageFormat;
 use napi::bindgen_prelude::*;
+use napi::{JsObject, NapiRaw};
 use napi_derive::napi;
-use std::cell::RefCell;
+use std::panic::{self, AssertUnwindSafe};
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::{mpsc, Arc, Mutex, PoisonError, Weak};
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// Error status for thrown errors. napi-rs copies it onto the JS error's
+/// `code` property, so callers can branch on e.g. `ERR_PDFIUM_PASSWORD`.
+pub struct ErrorCode(&'static str);
+
+impl AsRef<str> for ErrorCode {
+    fn as_ref(&self) -> &str {
+        self.0
+    }
+}
+
+impl From<PdfiumError> for Error<ErrorCode> {
+    fn from(err: PdfiumError) -> Self {
+        Error::new(ErrorCode(err.code()), err.to_string())
+    }
+}
 
 #[napi(object)]
 pub struct NativeAttachment {
@@ -156,6 +184,29 @@ pub struct NativeRect {
     pub bottom: f64,
 }
 
+#[napi(object)]
+pub struct NativeMatrix {
+    pub a: f64,
+    pub b: f64,
+    pub c: f64,
+    pub d: f64,
+    pub e: f64,
+    pub f: f64,
+}
+
+/// Colours, as 0xAARRGGBB, that replace those of a page's paths and text.
+#[napi(object)]
+pub struct NativeColorScheme {
+    pub path_fill: u32,
+    pub path_stroke: u32,
+    pub text_fill: u32,
+    pub text_stroke: u32,
+}
+
+fn to_color_scheme(scheme: NativeColorScheme) -> ColorScheme {
+    ColorScheme::new(scheme.path_fill, scheme.path_stroke, scheme.text_fill, scheme.text_stroke)
+}
+
 #[napi(object)]
 pub struct NativeColour {
     pub r: u32,
@@ -164,232 +215,690 @@ pub struct NativeColour {
     pub a: u32,
 }
 
+#[napi(object)]
+pub struct NativeInitOptions {
+    /// Extra directories to search for fonts.
+    pub font_paths: Option<Vec<String>>,
+    /// 0=AGG, 1=Skia. Omit to use the build default.
+    pub renderer_type: Option<i32>,
+}
+
+#[napi(object)]
+pub struct NativeCapabilities {
+    pub text_font_info: bool,
+    pub text_char_details: bool,
+    pub text_search: bool,
+    pub text_rects: bool,
+    pub page_boxes: bool,
+    pub page_editing: bool,
+    pub coordinate_conversion: bool,
+    pub progressive_loading: bool,
+    pub progressive_rendering: bool,
+    pub signatures: bool,
+    pub attachments: bool,
+    pub page_import: bool,
+    pub bookmarks: bool,
+    pub links: bool,
+    pub annotations: bool,
+    pub annotation_editing: bool,
+    pub object_rendering: bool,
+    pub thumbnails: bool,
+    pub color_schemes: bool,
+    pub forms: bool,
+    pub skia_renderer: bool,
+}
+
+fn to_native_capabilities(caps: Capabilities) -> NativeCapabilities {
+    NativeCapabilities {
+        text_font_info: caps.text_font_info,
+        text_char_details: caps.text_char_details,
+        text_search: caps.text_search,
+        text_rects: caps.text_rects,
+        page_boxes: caps.page_boxes,
+        page_editing: caps.page_editing,
+        coordinate_conversion: caps.coordinate_conversion,
+        progressive_loading: caps.progressive_loading,
+        progressive_rendering: caps.progressive_rendering,
+        signatures: caps.signatures,
+        attachments: caps.attachments,
+        page_import: caps.page_import,
+        bookmarks: caps.bookmarks,
+        links: caps.links,
+        annotations: caps.annotations,
+        annotation_editing: caps.annotation_editing,
+        object_rendering: caps.object_rendering,
+        thumbnails: caps.thumbnails,
+        color_schemes: caps.color_schemes,
+        forms: caps.forms,
+        skia_renderer: caps.skia_renderer,
+    }
+}
+
+/// Named `FPDF_RenderPageBitmap` flags; unset means off.
+#[napi(object)]
+pub struct NativeRenderFlags {
+    pub annotations: Option<bool>,
+    pub lcd_text: Option<bool>,
+    pub no_native_text: Option<bool>,
+    pub grayscale: Option<bool>,
+    pub printing: Option<bool>,
+    pub no_smooth_text: Option<bool>,
+    pub no_smooth_image: Option<bool>,
+    pub no_smooth_path: Option<bool>,
+    pub force_halftone: Option<bool>,
+    pub reverse_byte_order: Option<bool>,
+    pub convert_fill_to_stroke: Option<bool>,
+}
+
+fn to_render_flags(flags: &NativeRenderFlags) -> RenderFlags {
+    [
+        (flags.annotations, RenderFlags::ANNOTATIONS),
+        (flags.lcd_text, RenderFlags::LCD_TEXT),
+        (flags.no_native_text, RenderFlags::NO_NATIVE_TEXT),
+        (flags.grayscale, RenderFlags::GRAYSCALE),
+        (flags.printing, RenderFlags::PRINTING),
+        (flags.no_smooth_text, RenderFlags::NO_SMOOTH_TEXT),
+        (flags.no_smooth_image, RenderFlags::NO_SMOOTH_IMAGE),
+        (flags.no_smooth_path, RenderFlags::NO_SMOOTH_PATH),
+        (flags.force_halftone, RenderFlags::FORCE_HALFTONE),
+        (flags.reverse_byte_order, RenderFlags::REVERSE_BYTE_ORDER),
+        (flags.convert_fill_to_stroke, RenderFlags::CONVERT_FILL_TO_STROKE),
+    ]
+    .into_iter()
+    .fold(RenderFlags::empty(), |all, (on, flag)| all.set(flag, on.unwrap_or(false)))
+}
+
+#[napi(object)]
+pub struct NativeBitmapRenderOptions {
+    /// Clockwise quarter turns.
+    pub rotation: Option<i32>,
+    pub flags: Option<NativeRenderFlags>,
+    pub bg_colour: Option<u32>,
+    pub draw_forms: Option<bool>,
+    pub premultiplied: Option<bool>,
+}
+
+#[napi(object)]
+pub struct NativeTileOptions {
+    pub tile_size: u32,
+    /// Zoom of the highest level; 1 = 72 DPI.
+    pub max_zoom: f64,
+    pub overlap: Option<u32>,
+    /// Clockwise quarter turns on top of the page's own rotation.
+    pub rotation: Option<i32>,
+    pub flags: Option<i32>,
+    pub bg_colour: Option<u32>,
+    /// "raw" (RGBA pixels, the default), "png", "jpeg" or "webp". Encoded
+    /// formats need a build with the `encode` feature.
+    pub format: Option<String>,
+    /// JPEG quality 1–100, default 85.
+    pub quality: Option<u32>,
+}
+
+#[napi(object)]
+pub struct NativePyramidLevel {
+    pub width: u32,
+    pub height: u32,
+    pub columns: u32,
+    pub rows: u32,
+}
+
+#[napi(object)]
+pub struct NativeTilePyramid {
+    pub width: u32,
+    pub height: u32,
+    pub tile_size: u32,
+    pub overlap: u32,
+    pub levels: Vec<NativePyramidLevel>,
+    /// The `.dzi` descriptor for the requested tile format; absent for raw tiles.
+    pub dzi: Option<String>,
+}
+
+#[napi(object)]
+pub struct NativeTile {
+    pub x: u32,
+    pub y: u32,
+    pub width: u32,
+    pub height: u32,
+    /// RGBA pixels, or the encoded image if a tile format was requested.
+    pub data: Buffer,
+}
+
+#[napi(object)]
+pub struct NativeRenderedImage {
+    pub width: u32,
+    pub height: u32,
+    /// RGBA pixels.
+    pub data: Buffer,
+}
+
+fn to_native_image(image: RenderedImage) -> NativeRenderedImage {
+    NativeRenderedImage {
+        width: image.width,
+        height: image.height,
+        data: image.data.into(),
+    }
+}
+
+#[napi(object)]
+pub struct NativeThumbnail {
+    pub width: u32,
+    pub height: u32,
+    /// RGBA pixels.
+    pub data: Buffer,
+    /// "embedded" or "rendered".
+    pub source: String,
+}
+
+#[napi(object)]
+pub struct NativeHandleStats {
+    pub documents: u32,
+    pub pages: u32,
+    pub text_pages: u32,
+}
+
 #[napi(object)]
 pub struct NativeSearchResult {
     pub index: i32,
     pub count: i32,
 }
 
+// --- Async executor ---
+//
+// Promise-returning methods hand their PDFium call to a thread owned by the
+// loaded binary and settle the promise from there through a threadsafe
+// function. The libuv pool is never used, so work waiting for the library
+// cannot starve Node's own fs, dns and crypto callbacks.
+
+/// Convert a `PdfiumError` into a promise rejection that keeps its `code`.
+/// napi-rs would otherwise flatten it to a generic status.
+fn task_error(env: Env, err: PdfiumError) -> Error {
+    Error::from(JsError::from(Error::<ErrorCode>::from(err)).into_unknown(env))
+}
+
+type Job = Box<dyn FnOnce() + Send>;
+
+/// The thread that runs async work for one loaded binary.
+struct Executor {
+    jobs: mpsc::Sender<Job>,
+    /// Jobs queued or running, capped at `DEFAULT_QUEUE_CAPACITY`.
+    pending: Arc<AtomicUsize>,
+}
+
+/// Executors by loaded binary, shared by every `NativePdfium` on it.
+static EXECUTORS: Mutex<Vec<(BinaryKey, Weak<Executor>)>> = Mutex::new(Vec::new());
+
+impl Executor {
+    /// The executor for `binary`, starting its thread if no live instance
+    /// has one yet. The thread exits once the last instance is dropped and
+    /// its queued jobs have run.
+    fn for_binary(binary: BinaryKey) -> std::result::Result<Arc<Self>, PdfiumError> {
+        let mut executors = EXECUTORS.lock().unwrap_or_else(PoisonError::into_inner);
+        executors.retain(|(_, executor)| executor.strong_count() > 0);
+        if let Some(executor) = executors.iter().find(|(key, _)| *key == binary).and_then(|(_, e)| e.upgrade()) {
+            return Ok(executor);
+        }
+
+        let (jobs, queued) = mpsc::channel::<Job>();
+        thread::Builder::new()
+            .name("pdfium".into())
+            .spawn(move || queued.into_iter().for_each(|job| job()))
+            .map_err(|err| PdfiumError::Failed(format!("Failed to start the PDFium thread: {}", err)))?;
+        let executor = Arc::new(Self {
+            jobs,
+            pending: Arc::new(AtomicUsize::new(0)),
+        });
+        executors.push((binary, Arc::downgrade(&executor)));
+        Ok(executor)
+    }
+
+    /// Queue `job`, which runs with `Ok(())` on the executor thread. If
+    /// `DEFAULT_QUEUE_CAPACITY` jobs are already waiting it runs right away
+    /// on the calling thread with `PdfiumError::Busy` instead.
+    fn submit(&self, job: impl FnOnce(std::result::Result<(), PdfiumError>) + Send + 'static) {
+        if self.pending.fetch_add(1, Ordering::AcqRel) >= DEFAULT_QUEUE_CAPACITY {
+            self.pending.fetch_sub(1, Ordering::AcqRel);
+            return job(Err(PdfiumError::Busy));
+        }
+        let pending = Arc::clone(&self.pending);
+        self.jobs
+            .send(Box::new(move || {
+                job(Ok(()));
+                pending.fetch_sub(1, Ordering::AcqRel);
+            }))
+            .unwrap_or_else(|_| unreachable!("the PDFium thread runs until its last sender is dropped"));
+    }
+}
+
+/// The pyramid `options` describes, with the JS defaults filled in.
+fn tile_pyramid<'lib>(
+    lib: &'lib PdfiumLibrary,
+    page_handle: u32,
+    options: &NativeTileOptions,
+) -> std::result::Result<TilePyramid<'lib>, PdfiumError> {
+    let render = RenderOptions::new()
+        .rotation(options.rotation.unwrap_or(0))
+        .flags(options.flags.unwrap_or(0))
+        .background(options.bg_colour.unwrap_or(0xFFFF_FFFF));
+    Ok(lib
+        .tile_pyramid(page_handle, options.tile_size, options.max_zoom as f32, &render)?
+        .overlap(options.overlap.unwrap_or(0)))
+}
+
+/// How tiles are handed back, from `NativeTileOptions::format`.
+enum TileEncoding {
+    Raw,
+    #[cfg(feature = "encode")]
+    Image(ImageFormat),
+}
+
+impl TileEncoding {
+    fn from_options(options: &NativeTileOptions) -> std::result::Result<Self, PdfiumError> {
+        match options.format.as_deref() {
+            None | Some("raw") => Ok(TileEncoding::Raw),
+            #[cfg(feature = "encode")]
+            Some("png") => Ok(TileEncoding::Image(ImageFormat::Png)),
+            #[cfg(feature = "encode")]
+            Some("jpeg") => {
+                let quality = options.quality.unwrap_or(85);
+                let quality = u8::try_from(quality)
+                    .map_err(|_| PdfiumError::InvalidArgument(format!("JPEG quality: {}", quality)))?;
+                Ok(TileEncoding::Image(ImageFormat::Jpeg { quality }))
+            }
+            #[cfg(feature = "encode")]
+            Some("webp") => Ok(TileEncoding::Image(ImageFormat::WebpLossless)),
+            Some(other) => Err(PdfiumError::InvalidArgument(format!("tile format: {}", other))),
+        }
+    }
+
+    /// File extension for the `.dzi` manifest; raw tiles have none.
+    fn extension(&self) -> Option<&'static str> {
+        match self {
+            TileEncoding::Raw => None,
+            #[cfg(feature = "encode")]
+            TileEncoding::Image(format) => Some(format.extension()),
+        }
+    }
+
+    fn tile_data(&self, tile: Tile) -> std::result::Result<Vec<u8>, PdfiumError> {
+        match self {
+            TileEncoding::Raw => Ok(tile.data),
+            #[cfg(feature = "encode")]
+            TileEncoding::Image(format) => tile.encode(*format),
+        }
+    }
+}
+
+/// Cancels the renders it is passed to. JS code typically calls `cancel`
+/// from an `AbortSignal`'s abort event.
+#[napi]
+#[derive(Default)]
+pub struct NativeCancelToken {
+    cancelled: Arc<AtomicBool>,
+}
+
+#[napi]
+impl NativeCancelToken {
+    #[napi(constructor)]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Stop every render using this token at its next pause point.
+    #[napi]
+    pub fn cancel(&self) {
+        self.cancelled.store(true, Ordering::Relaxed);
+    }
+
+    #[napi(getter)]
+    pub fn cancelled(&self) -> bool {
+        self.cancelled.load(Ordering::Relaxed)
+    }
+}
+
+/// Pixels that `render_page_to_bitmap` draws into again and again, so
+/// redraws at a fixed size don't allocate.
+#[napi]
+pub struct NativeBitmap {
+    width: i32,
+    height: i32,
+    format: PixelFormat,
+    // Allocated by Node, so `data` hands JS the same Buffer rather than a copy.
+    data: Buffer,
+}
+
+#[napi]
+impl NativeBitmap {
+    /// `format` is one of "gray8", "bgr", "bgrx", "bgra" or "rgba" (the default).
+    #[napi(constructor)]
+    pub fn new(env: Env, width: u32, height: u32, format: Option<String>) -> Result<Self, ErrorCode> {
+        let format = match format {
+            Some(name) => name.parse().map_err(Error::from)?,
+            None => PixelFormat::default(),
+        };
+        let (width, height) = match (i32::try_from(width), i32::try_from(height)) {
+            (Ok(w), Ok(h)) if w > 0 && h > 0 => (w, h),
+            _ => {
+                return Err(Error::from(PdfiumError::InvalidArgument(format!(
+                    "bitmap size: {}x{}",
+                    width, height
+                ))))
+            }
+        };
+        let len = format.min_stride(width) * height as usize;
+        let data = env
+            .create_buffer(len)
+            .and_then(|buffer| unsafe { Buffer::from_napi_value(env.raw(), buffer.into_raw().raw()) })
+            .map_err(|err| Error::from(PdfiumError::Failed(err.reason)))?;
+        let mut bitmap = Self { width, height, format, data };
+        // Node does not zero new buffers.
+        bitmap.data.fill(0);
+        Ok(bitmap)
+    }
+
+    #[napi(getter)]
+    pub fn width(&self) -> u32 {
+        self.width as u32
+    }
+
+    #[napi(getter)]
+    pub fn height(&self) -> u32 {
+        self.height as u32
+    }
+
+    /// Bytes from the start of one row to the next.
+    #[napi(getter)]
+    pub fn stride(&self) -> u32 {
+        self.format.min_stride(self.width) as u32
+    }
+
+    #[napi(getter)]
+    pub fn format(&self) -> String {
+        match self.format {
+            PixelFormat::Gray8 => "gray8",
+            PixelFormat::Bgr => "bgr",
+            PixelFormat::Bgrx => "bgrx",
+            PixelFormat::Bgra => "bgra",
+            PixelFormat::Rgba => "rgba",
+        }
+        .to_string()
+    }
+
+    /// The pixels as last rendered. This is the bitmap's own memory, not a
+    /// copy: later renders update it in place.
+    #[napi(getter)]
+    pub fn data(&self) -> Buffer {
+        self.data.clone()
+    }
+}
+
 #[napi]
 pub struct NativePdfium {
-    inner: RefCell<PdfiumLibrary>,
+    inner: SharedPdfium,
+    executor: Arc<Executor>,
+}
+
+/// How long a synchronous method waits for async work to let go of the
+/// library before throwing `ERR_PDFIUM_BUSY`.
+const SYNC_WAIT: Duration = Duration::from_millis(250);
+
+impl NativePdfium {
+    /// Run `f` on the calling (JS) thread. If async work holds or is waiting
+    /// for the library this waits up to `SYNC_WAIT` for it, then fails with
+    /// `ERR_PDFIUM_BUSY` rather than blocking the event loop until the queue
+    /// drains.
+    fn sync<R>(&self, f: impl FnOnce(&PdfiumLibrary) -> R) -> Result<R, ErrorCode> {
+        self.inner.try_for(SYNC_WAIT, f).map_err(Error::from)
+    }
+
+    /// Run `f` on the binary's executor thread and return a promise that
+    /// settles with `map` of its result. Rejects with `ERR_PDFIUM_BUSY` when
+    /// too much work is already queued.
+    fn spawn<R, T>(
+        &self,
+        env: Env,
+        f: impl FnOnce(&PdfiumLibrary) -> std::result::Result<R, PdfiumError> + Send + 'static,
+        map: impl FnOnce(R) -> T + Send + 'static,
+    ) -> Result<JsObject>
+    where
+        R: Send + 'static,
+        T: ToNapiValue + 'static,
+    {
+        let (deferred, promise) = env.create_deferred()?;
+        let settle = move |result: std::result::Result<R, PdfiumError>| {
+            deferred.resolve(move |env| result.map(map).map_err(|err| task_error(env, err)))
+        };
+        let inner = self.inner.clone();
+        self.executor.submit(move |admitted| {
+            // A panic must still settle the promise, or its threadsafe
+            // function would keep the event loop alive forever.
+            let result = admitted.and_then(|()| {
+                panic::catch_unwind(AssertUnwindSafe(|| inner.with(f)))
+                    .unwrap_or_else(|_| Err(PdfiumError::Failed("PDFium call panicked".into())))
+            });
+            settle(result);
+        });
+        Ok(promise)
+    }
 }
 
 #[napi]
 impl NativePdfium {
     #[napi(factory)]
-    pub fn load(library_path: String) -> Result<Self> {
+    pub fn load(library_path: String) -> Result<Self, ErrorCode> {
         let lib = PdfiumLibrary::load(&library_path)
-            .map_err(|e| Error::from_reason(e))?;
-        Ok(Self {
-            inner: RefCell::new(lib),
-        })
+            .map_err(Error::from)?;
+        let inner = SharedPdfium::new(lib);
+        let executor = Executor::for_binary(inner.binary_key()).map_err(Error::from)?;
+        Ok(Self { inner, executor })
     }
 
+    /// Initialise PDFium. Without `options` this shares any initialisation
+    /// already in place; with them, they must match it.
     #[napi]
-    pub fn init_library(&self) {
-        self.inner.borrow().init_library();
+    pub fn init_library(&self, options: Option<NativeInitOptions>) -> Result<(), ErrorCode> {
+        let Some(options) = options else {
+            return self.sync(|lib| lib.init_library());
+        };
+        let mut init = InitOptions::new().font_paths(options.font_paths.unwrap_or_default());
+        if let Some(renderer_type) = options.renderer_type {
+            init = init.renderer(RendererType::from_raw(renderer_type).map_err(Error::from)?);
+        }
+        self.sync(|lib| lib.init_library_with(&init))?
+            .map_err(Error::from)
     }
 
+    /// Tear down PDFium once every async call already started has finished,
+    /// blocking until then.
     #[napi]
     pub fn destroy_library(&self) {
-        self.inner.borrow().destroy_library();
+        let (done, finished) = mpsc::channel();
+        let inner = self.inner.clone();
+        self.executor
+            .jobs
+            .send(Box::new(move || {
+                inner.with(|lib| lib.destroy_library());
+                let _ = done.send(());
+            }))
+            .unwrap_or_else(|_| unreachable!("the PDFium thread runs until its last sender is dropped"));
+        // A panic on the executor drops `done`, which also ends the wait.
+        let _ = finished.recv();
+    }
+
+    #[napi]
+    pub fn get_last_error(&self) -> Result<u32, ErrorCode> {
+        self.sync(|lib| lib.get_last_error())
+    }
+
+    /// Optional feature groups supported by the loaded PDFium build.
+    #[napi]
+    pub fn capabilities(&self) -> NativeCapabilities {
+        to_native_capabilities(self.inner.capabilities())
     }
 
+    /// Live handle counts, for leak detection. Async calls still running are
+    /// not reflected until they settle.
     #[napi]
-    pub fn get_last_error(&self) -> u32 {
-        self.inner.borrow().get_last_error()
+    pub fn live_handle_stats(&self) -> NativeHandleStats {
+        let stats = self.inner.live_handle_stats();
+        NativeHandleStats {
+            documents: stats.documents,
+            pages: stats.pages,
+            text_pages: stats.text_pages,
+        }
     }
 
+    /// Load a document from a copy of `data`.
     #[napi]
-    pub fn load_document(&self, data: Buffer, password: Option<String>) -> Result<u32> {
-        self.inner
-            .borrow_mut()
-            .load_document(&data, password.as_deref())
-            .map_err(|e| Error::from_reason(e))
+    pub fn load_document(&self, data: Buffer, password: Option<String>) -> Result<u32, ErrorCode> {
+        self.sync(|lib| lib.load_document(&data, password.as_deref()))?
+            .map_err(Error::from)
     }
 
+    /// Load a document from a file, which PDFium reads on demand.
     #[napi]
-    pub fn close_document(&self, handle: u32) -> Result<()> {
-        self.inner
-            .borrow_mut()
-            .close_document(handle)
-            .map_err(|e| Error::from_reason(e))
+    pub fn load_document_from_path(&self, path: String, password: Option<String>) -> Result<u32, ErrorCode> {
+        self.sync(|lib| lib.load_document_from_path(path, password.as_deref()))?
+            .map_err(Error::from)
     }
 
     #[napi]
-    pub fn get_page_count(&self, doc_handle: u32) -> Result<i32> {
-        self.inner
-            .borrow()
-            .get_page_count(doc_handle)
-            .map_err(|e| Error::from_reason(e))
+    pub fn close_document(&self, handle: u32) -> Result<(), ErrorCode> {
+        self.sync(|lib| lib.close_document(handle))?
+            .map_err(Error::from)
     }
 
     #[napi]
-    pub fn load_page(&self, doc_handle: u32, index: i32) -> Result<u32> {
-        self.inner
-            .borrow_mut()
-            .load_page(doc_handle, index)
-            .map_err(|e| Error::from_reason(e))
+    pub fn get_page_count(&self, doc_handle: u32) -> Result<i32, ErrorCode> {
+        self.sync(|lib| lib.get_page_count(doc_handle))?
+            .map_err(Error::from)
     }
 
     #[napi]
-    pub fn close_page(&self, page_handle: u32) -> Result<()> {
-        self.inner
-            .borrow_mut()
-            .close_page(page_handle)
-            .map_err(|e| Error::from_reason(e))
+    pub fn load_page(&self, doc_handle: u32, index: i32) -> Result<u32, ErrorCode> {
+        self.sync(|lib| lib.load_page(doc_handle, index))?
+            .map_err(Error::from)
     }
 
     #[napi]
-    pub fn get_page_width(&self, page_handle: u32) -> Result<f64> {
-        self.inner
-            .borrow()
-            .get_page_width(page_handle)
-            .map_err(|e| Error::from_reason(e))
+    pub fn close_page(&self, page_handle: u32) -> Result<(), ErrorCode> {
+        self.sync(|lib| lib.close_page(page_handle))?
+            .map_err(Error::from)
     }
 
     #[napi]
-    pub fn get_page_height(&self, page_handle: u32) -> Result<f64> {
-        self.inner
-            .borrow()
-            .get_page_height(page_handle)
-            .map_err(|e| Error::from_reason(e))
+    pub fn get_page_width(&self, page_handle: u32) -> Result<f64, ErrorCode> {
+        self.sync(|lib| lib.get_page_width(page_handle))?
+            .map_err(Error::from)
     }
 
     #[napi]
-    pub fn load_text_page(&self, page_handle: u32) -> Result<u32> {
-        self.inner
-            .borrow_mut()
-            .load_text_page(page_handle)
-            .map_err(|e| Error::from_reason(e))
+    pub fn get_page_height(&self, page_handle: u32) -> Result<f64, ErrorCode> {
+        self.sync(|lib| lib.get_page_height(page_handle))?
+            .map_err(Error::from)
     }
 
     #[napi]
-    pub fn close_text_page(&self, text_page_handle: u32) -> Result<()> {
-        self.inner
-            .borrow_mut()
-            .close_text_page(text_page_handle)
-            .map_err(|e| Error::from_reason(e))
+    pub fn load_text_page(&self, page_handle: u32) -> Result<u32, ErrorCode> {
+        self.sync(|lib| lib.load_text_page(page_handle))?
+            .map_err(Error::from)
     }
 
     #[napi]
-    pub fn count_text_chars(&self, text_page_handle: u32) -> Result<i32> {
-        self.inner
-            .borrow()
-            .count_text_chars(text_page_handle)
-            .map_err(|e| Error::from_reason(e))
+    pub fn close_text_page(&self, text_page_handle: u32) -> Result<(), ErrorCode> {
+        self.sync(|lib| lib.close_text_page(text_page_handle))?
+            .map_err(Error::from)
     }
 
     #[napi]
-    pub fn get_full_text(&self, text_page_handle: u32) -> Result<String> {
-        self.inner
-            .borrow()
-            .get_full_text(text_page_handle)
-            .map_err(|e| Error::from_reason(e))
+    pub fn count_text_chars(&self, text_page_handle: u32) -> Result<i32, ErrorCode> {
+        self.sync(|lib| lib.count_text_chars(text_page_handle))?
+            .map_err(Error::from)
+    }
+
+    #[napi]
+    pub fn get_full_text(&self, text_page_handle: u32) -> Result<String, ErrorCode> {
+        self.sync(|lib| lib.get_full_text(text_page_handle))?
+            .map_err(Error::from)
     }
 
     // --- Text Character Font Info ---
 
     #[napi]
-    pub fn get_char_font_size(&self, text_page_handle: u32, char_index: i32) -> Result<f64> {
-        self.inner
-            .borrow()
-            .get_char_font_size(text_page_handle, char_index)
-            .map_err(|e| Error::from_reason(e))
+    pub fn get_char_font_size(&self, text_page_handle: u32, char_index: i32) -> Result<f64, ErrorCode> {
+        self.sync(|lib| lib.get_char_font_size(text_page_handle, char_index))?
+            .map_err(Error::from)
     }
 
     #[napi]
-    pub fn get_char_font_weight(&self, text_page_handle: u32, char_index: i32) -> Result<i32> {
-        self.inner
-            .borrow()
-            .get_char_font_weight(text_page_handle, char_index)
-            .map_err(|e| Error::from_reason(e))
+    pub fn get_char_font_weight(&self, text_page_handle: u32, char_index: i32) -> Result<i32, ErrorCode> {
+        self.sync(|lib| lib.get_char_font_weight(text_page_handle, char_index))?
+            .map_err(Error::from)
     }
 
     /// Get font name and flags for a character.
     /// Returns { name, flags } or null if unavailable.
     #[napi]
-    pub fn get_char_font_info(&self, text_page_handle: u32, char_index: i32) -> Result<Option<NativeCharFontInfo>> {
-        self.inner
-            .borrow()
-            .get_char_font_info(text_page_handle, char_index)
+    pub fn get_char_font_info(&self, text_page_handle: u32, char_index: i32) -> Result<Option<NativeCharFontInfo>, ErrorCode> {
+        self.sync(|lib| lib.get_char_font_info(text_page_handle, char_index))?
             .map(|opt| opt.map(|(name, flags)| NativeCharFontInfo { name, flags }))
-            .map_err(|e| Error::from_reason(e))
+            .map_err(Error::from)
     }
 
     #[napi]
-    pub fn get_char_render_mode(&self, text_page_handle: u32, char_index: i32) -> Result<i32> {
-        self.inner
-            .borrow()
-            .get_char_render_mode(text_page_handle, char_index)
-            .map_err(|e| Error::from_reason(e))
+    pub fn get_char_render_mode(&self, text_page_handle: u32, char_index: i32) -> Result<i32, ErrorCode> {
+        self.sync(|lib| lib.get_char_render_mode(text_page_handle, char_index))?
+            .map_err(Error::from)
     }
 
     // --- Metadata ---
 
     #[napi]
-    pub fn get_meta_text(&self, doc_handle: u32, tag: String) -> Result<Option<String>> {
-        self.inner
-            .borrow()
-            .get_meta_text(doc_handle, &tag)
-            .map_err(|e| Error::from_reason(e))
+    pub fn get_meta_text(&self, doc_handle: u32, tag: String) -> Result<Option<String>, ErrorCode> {
+        self.sync(|lib| lib.get_meta_text(doc_handle, &tag))?
+            .map_err(Error::from)
     }
 
     #[napi]
-    pub fn get_file_version(&self, doc_handle: u32) -> Result<Option<i32>> {
-        self.inner
-            .borrow()
-            .get_file_version(doc_handle)
-            .map_err(|e| Error::from_reason(e))
+    pub fn get_file_version(&self, doc_handle: u32) -> Result<Option<i32>, ErrorCode> {
+        self.sync(|lib| lib.get_file_version(doc_handle))?
+            .map_err(Error::from)
     }
 
     #[napi]
-    pub fn get_doc_permissions(&self, doc_handle: u32) -> Result<u32> {
-        self.inner
-            .borrow()
-            .get_doc_permissions(doc_handle)
-            .map_err(|e| Error::from_reason(e))
+    pub fn get_doc_permissions(&self, doc_handle: u32) -> Result<u32, ErrorCode> {
+        self.sync(|lib| lib.get_doc_permissions(doc_handle))?
+            .map_err(Error::from)
     }
 
     #[napi]
-    pub fn get_doc_user_permissions(&self, doc_handle: u32) -> Result<u32> {
-        self.inner
-            .borrow()
-            .get_doc_user_permissions(doc_handle)
-            .map_err(|e| Error::from_reason(e))
+    pub fn get_doc_user_permissions(&self, doc_handle: u32) -> Result<u32, ErrorCode> {
+        self.sync(|lib| lib.get_doc_user_permissions(doc_handle))?
+            .map_err(Error::from)
     }
 
     #[napi]
-    pub fn get_page_mode(&self, doc_handle: u32) -> Result<i32> {
-        self.inner
-            .borrow()
-            .get_page_mode(doc_handle)
-            .map_err(|e| Error::from_reason(e))
+    pub fn get_page_mode(&self, doc_handle: u32) -> Result<i32, ErrorCode> {
+        self.sync(|lib| lib.get_page_mode(doc_handle))?
+            .map_err(Error::from)
     }
 
     #[napi]
-    pub fn get_security_handler_revision(&self, doc_handle: u32) -> Result<i32> {
-        self.inner
-            .borrow()
-            .get_security_handler_revision(doc_handle)
-            .map_err(|e| Error::from_reason(e))
+    pub fn get_security_handler_revision(&self, doc_handle: u32) -> Result<i32, ErrorCode> {
+        self.sync(|lib| lib.get_security_handler_revision(doc_handle))?
+            .map_err(Error::from)
     }
 
     #[napi]
-    pub fn is_tagged(&self, doc_handle: u32) -> Result<bool> {
-        self.inner
-            .borrow()
-            .is_tagged(doc_handle)
-            .map_err(|e| Error::from_reason(e))
+    pub fn is_tagged(&self, doc_handle: u32) -> Result<bool, ErrorCode> {
+        self.sync(|lib| lib.is_tagged(doc_handle))?
+            .map_err(Error::from)
     }
 
     #[napi]
-    pub fn get_page_label(&self, doc_handle: u32, page_index: i32) -> Result<Option<String>> {
-        self.inner
-            .borrow()
-            .get_page_label(doc_handle, page_index)
-            .map_err(|e| Error::from_reason(e))
+    pub fn get_page_label(&self, doc_handle: u32, page_index: i32) -> Result<Option<String>, ErrorCode> {
+        self.sync(|lib| lib.get_page_label(doc_handle, page_index))?
+            .map_err(Error::from)
     }
 
     // --- Page Boxes ---
@@ -397,12 +906,10 @@ impl NativePdfium {
     /// Get a page box. box_type: 0=Media, 1=Crop, 2=Bleed, 3=Trim, 4=Art.
     /// Returns [left, bottom, right, top] or null.
     #[napi]
-    pub fn get_page_box(&self, page_handle: u32, box_type: i32) -> Result<Option<Vec<f64>>> {
-        self.inner
-            .borrow()
-            .get_page_box(page_handle, box_type)
+    pub fn get_page_box(&self, page_handle: u32, box_type: i32) -> Result<Option<Vec<f64>>, ErrorCode> {
+        self.sync(|lib| lib.get_page_box(page_handle, box_type))?
             .map(|opt| opt.map(|[l, b, r, t]| vec![l as f64, b as f64, r as f64, t as f64]))
-            .map_err(|e| Error::from_reason(e))
+            .map_err(Error::from)
     }
 
     /// Set a page box. box_type: 0=Media, 1=Crop, 2=Bleed, 3=Trim, 4=Art.
@@ -415,16 +922,15 @@ impl NativePdfium {
         bottom: f64,
         right: f64,
         top: f64,
-    ) -> Result<()> {
-        self.inner
-            .borrow()
-            .set_page_box(page_handle, box_type, left as f32, bottom as f32, right as f32, top as f32)
-            .map_err(|e| Error::from_reason(e))
+    ) -> Result<(), ErrorCode> {
+        self.sync(|lib| lib.set_page_box(page_handle, box_type, left as f32, bottom as f32, right as f32, top as f32))?
+            .map_err(Error::from)
     }
 
     // --- Render ---
 
     #[napi]
+    #[allow(clippy::too_many_arguments)]
     pub fn render_page(
         &self,
         page_handle: u32,
@@ -433,12 +939,287 @@ impl NativePdfium {
         rotation: i32,
         flags: i32,
         bg_colour: u32,
-    ) -> Result<Buffer> {
-        self.inner
-            .borrow()
-            .render_page(page_handle, width, height, rotation, flags, bg_colour)
+        draw_forms: Option<bool>,
+        color_scheme: Option<NativeColorScheme>,
+    ) -> Result<Buffer, ErrorCode> {
+        let mut options = RenderOptions::new()
+            .rotation(rotation)
+            .flags(flags)
+            .background(bg_colour)
+            .draw_forms(draw_forms.unwrap_or(false));
+        if let Some(scheme) = color_scheme {
+            options = options.color_scheme(to_color_scheme(scheme));
+        }
+        self.sync(|lib| lib.render_page_with(page_handle, width, height, &options))?
             .map(|data| data.into())
-            .map_err(|e| Error::from_reason(e))
+            .map_err(Error::from)
+    }
+
+    /// Render into `buffer`, whose rows start `stride` bytes apart.
+    /// `format` is one of "gray8", "bgr", "bgrx", "bgra" or "rgba".
+    #[napi]
+    #[allow(clippy::too_many_arguments)]
+    pub fn render_page_into(
+        &self,
+        page_handle: u32,
+        width: i32,
+        height: i32,
+        rotation: i32,
+        flags: i32,
+        bg_colour: u32,
+        format: String,
+        premultiplied: bool,
+        mut buffer: Buffer,
+        stride: u32,
+    ) -> Result<(), ErrorCode> {
+        let options = RenderOptions::new()
+            .format(format.parse().map_err(Error::from)?)
+            .alpha(if premultiplied { AlphaMode::Premultiplied } else { AlphaMode::Straight })
+            .rotation(rotation)
+            .flags(flags)
+            .background(bg_colour);
+        self.sync(|lib| lib.render_page_into(page_handle, width, height, &options, &mut buffer, stride as usize))?
+            .map_err(Error::from)
+    }
+
+    /// Render the part of the page `matrix` maps into `clip` to a new RGBA
+    /// buffer of `width` x `height` pixels.
+    #[napi]
+    #[allow(clippy::too_many_arguments)]
+    pub fn render_region(
+        &self,
+        page_handle: u32,
+        width: i32,
+        height: i32,
+        matrix: NativeMatrix,
+        clip: NativeRect,
+        flags: i32,
+        bg_colour: u32,
+    ) -> Result<Buffer, ErrorCode> {
+        let matrix = Matrix::new(
+            matrix.a as f32,
+            matrix.b as f32,
+            matrix.c as f32,
+            matrix.d as f32,
+            matrix.e as f32,
+            matrix.f as f32,
+        );
+        let clip = ClipRect::new(clip.left as f32, clip.top as f32, clip.right as f32, clip.bottom as f32);
+        let options = RenderOptions::new().flags(flags).background(bg_colour);
+        let stride = options.get_format().min_stride(width);
+        let mut buffer = vec![0u8; stride * height.max(0) as usize];
+        self.sync(|lib| lib.render_region(page_handle, width, height, &matrix, clip, &options, &mut buffer, stride))?
+            .map(|()| buffer.into())
+            .map_err(Error::from)
+    }
+
+    /// Describe the deep-zoom tile pyramid for a page.
+    #[napi]
+    pub fn get_tile_pyramid(&self, page_handle: u32, options: NativeTileOptions) -> Result<NativeTilePyramid, ErrorCode> {
+        let encoding = TileEncoding::from_options(&options).map_err(Error::from)?;
+        self.sync(|lib| {
+            tile_pyramid(lib, page_handle, &options).map(|pyramid| NativeTilePyramid {
+                width: pyramid.width(),
+                height: pyramid.height(),
+                tile_size: pyramid.tile_size(),
+                overlap: pyramid.get_overlap(),
+                levels: pyramid
+                    .levels()
+                    .iter()
+                    .map(|level| NativePyramidLevel {
+                        width: level.width,
+                        height: level.height,
+                        columns: level.columns,
+                        rows: level.rows,
+                    })
+                    .collect(),
+                dzi: encoding.extension().map(|extension| pyramid.dzi_manifest(extension)),
+            })
+        })?
+            .map_err(Error::from)
+    }
+
+    /// Render one tile of a page's deep-zoom pyramid.
+    #[napi]
+    pub fn render_tile(
+        &self,
+        page_handle: u32,
+        options: NativeTileOptions,
+        level: u32,
+        column: u32,
+        row: u32,
+    ) -> Result<NativeTile, ErrorCode> {
+        let encoding = TileEncoding::from_options(&options).map_err(Error::from)?;
+        let tile = self
+            .sync(|lib| tile_pyramid(lib, page_handle, &options)?.render_tile(level, column, row))?
+            .map_err(Error::from)?;
+        Ok(NativeTile {
+            x: tile.x,
+            y: tile.y,
+            width: tile.width,
+            height: tile.height,
+            data: encoding.tile_data(tile).map_err(Error::from)?.into(),
+        })
+    }
+
+    // --- Page Objects ---
+
+    #[napi]
+    pub fn count_page_objects(&self, page_handle: u32) -> Result<i32, ErrorCode> {
+        self.sync(|lib| lib.count_page_objects(page_handle))?
+            .map_err(Error::from)
+    }
+
+    /// 1=Text, 2=Path, 3=Image, 4=Shading, 5=Form (0 if unknown).
+    #[napi]
+    pub fn get_page_object_type(&self, page_handle: u32, index: i32) -> Result<i32, ErrorCode> {
+        self.sync(|lib| lib.get_page_object_type(page_handle, index))?
+            .map_err(Error::from)
+    }
+
+    /// Rasterise one image or text object as RGBA over `bg_colour`. Text is
+    /// drawn at `scale` pixels per point; images at their own size.
+    #[napi]
+    pub fn render_page_object(
+        &self,
+        page_handle: u32,
+        index: i32,
+        scale: f64,
+        bg_colour: u32,
+    ) -> Result<NativeRenderedImage, ErrorCode> {
+        let options = RenderOptions::new().background(bg_colour);
+        self.sync(|lib| lib.render_page_object(page_handle, index, scale as f32, &options))?
+            .map(to_native_image)
+            .map_err(Error::from)
+    }
+
+    /// Render one annotation's appearance alone as RGBA, at `scale` pixels
+    /// per point. Each call copies the whole page, so prefer a page render
+    /// for many annotations.
+    #[napi]
+    pub fn render_annotation(
+        &self,
+        page_handle: u32,
+        index: i32,
+        scale: f64,
+        flags: i32,
+        bg_colour: u32,
+    ) -> Result<NativeRenderedImage, ErrorCode> {
+        let options = RenderOptions::new().flags(flags).background(bg_colour);
+        self.sync(|lib| lib.render_annotation(page_handle, index, scale as f32, &options))?
+            .map(to_native_image)
+            .map_err(Error::from)
+    }
+
+    /// Render a page box (0 = MediaBox … 4 = ArtBox, as for `getPageBox`)
+    /// as RGBA at `dpi`, sized from the box and the page's /Rotate.
+    #[napi]
+    pub fn render_at_dpi(
+        &self,
+        page_handle: u32,
+        dpi: f64,
+        box_type: i32,
+        rotation: i32,
+        flags: i32,
+        bg_colour: u32,
+    ) -> Result<NativeRenderedImage, ErrorCode> {
+        let page_box = PageBox::from_box_type(box_type)
+            .ok_or_else(|| Error::from(PdfiumError::InvalidArgument(format!("box type: {}", box_type))))?;
+        let options = RenderOptions::new().rotation(rotation).flags(flags).background(bg_colour);
+        self.sync(|lib| lib.render_at_dpi(page_handle, dpi as f32, page_box, &options))?
+            .map(to_native_image)
+            .map_err(Error::from)
+    }
+
+    /// Pack named render flags into the integer the other render methods take.
+    #[napi]
+    pub fn render_flags(&self, flags: NativeRenderFlags) -> i32 {
+        to_render_flags(&flags).bits()
+    }
+
+    /// A bitmap for `render_page_to_bitmap`; the same as `new NativeBitmap`.
+    #[napi]
+    pub fn create_bitmap(&self, env: Env, width: u32, height: u32, format: Option<String>) -> Result<NativeBitmap, ErrorCode> {
+        NativeBitmap::new(env, width, height, format)
+    }
+
+    /// Render the page to fill `bitmap` in its own format, replacing what it held.
+    #[napi]
+    pub fn render_page_to_bitmap(
+        &self,
+        page_handle: u32,
+        bitmap: &mut NativeBitmap,
+        options: Option<NativeBitmapRenderOptions>,
+    ) -> Result<(), ErrorCode> {
+        let mut render = RenderOptions::new().format(bitmap.format);
+        if let Some(options) = options {
+            render = render
+                .rotation(options.rotation.unwrap_or(0))
+                .render_flags(options.flags.as_ref().map(to_render_flags).unwrap_or_default())
+                .background(options.bg_colour.unwrap_or(0xFFFF_FFFF))
+                .draw_forms(options.draw_forms.unwrap_or(false))
+                .alpha(if options.premultiplied.unwrap_or(false) {
+                    AlphaMode::Premultiplied
+                } else {
+                    AlphaMode::Straight
+                });
+        }
+        let stride = bitmap.format.min_stride(bitmap.width);
+        self.sync(|lib| lib.render_page_into(page_handle, bitmap.width, bitmap.height, &render, &mut bitmap.data, stride))?
+            .map_err(Error::from)
+    }
+
+    /// Render the page as RGBA, as large as fits within `max_width` x
+    /// `max_height` pixels.
+    #[napi]
+    pub fn render_fit(
+        &self,
+        page_handle: u32,
+        max_width: u32,
+        max_height: u32,
+        rotation: i32,
+        flags: i32,
+        bg_colour: u32,
+    ) -> Result<NativeRenderedImage, ErrorCode> {
+        let options = RenderOptions::new().rotation(rotation).flags(flags).background(bg_colour);
+        self.sync(|lib| lib.render_fit(page_handle, max_width, max_height, &options))?
+            .map(to_native_image)
+            .map_err(Error::from)
+    }
+
+    // --- Thumbnails ---
+
+    /// A preview no larger than `max_edge` pixels, from the embedded
+    /// thumbnail (shrunk to fit if needed) when the page has one, otherwise
+    /// rendered.
+    #[napi]
+    pub fn get_thumbnail(&self, page_handle: u32, max_edge: u32) -> Result<NativeThumbnail, ErrorCode> {
+        self.sync(|lib| lib.thumbnail(page_handle, max_edge))?
+            .map(|thumbnail| NativeThumbnail {
+                width: thumbnail.width,
+                height: thumbnail.height,
+                data: thumbnail.data.into(),
+                source: match thumbnail.source {
+                    ThumbnailSource::Embedded => "embedded",
+                    ThumbnailSource::Rendered => "rendered",
+                }
+                .to_string(),
+            })
+            .map_err(Error::from)
+    }
+
+    #[napi]
+    pub fn get_decoded_thumbnail_data(&self, page_handle: u32) -> Result<Option<Buffer>, ErrorCode> {
+        self.sync(|lib| lib.get_decoded_thumbnail_data(page_handle))?
+            .map(|data| data.map(Buffer::from))
+            .map_err(Error::from)
+    }
+
+    #[napi]
+    pub fn get_raw_thumbnail_data(&self, page_handle: u32) -> Result<Option<Buffer>, ErrorCode> {
+        self.sync(|lib| lib.get_raw_thumbnail_data(page_handle))?
+            .map(|data| data.map(Buffer::from))
+            .map_err(Error::from)
     }
 
     // --- Save / Export ---
@@ -447,31 +1228,64 @@ impl NativePdfium {
     /// flags: 0=None, 1=Incremental, 2=NoIncremental, 3=RemoveSecurity.
     /// version: optional PDF version (e.g. 17 for PDF 1.7).
     #[napi]
-    pub fn save_document(&self, doc_handle: u32, flags: u32, version: Option<i32>) -> Result<Buffer> {
-        self.inner
-            .borrow()
-            .save_document(doc_handle, flags, version)
+    pub fn save_document(&self, doc_handle: u32, flags: u32, version: Option<i32>) -> Result<Buffer, ErrorCode> {
+        self.sync(|lib| lib.save_document(doc_handle, flags, version))?
             .map(|data| data.into())
-            .map_err(|e| Error::from_reason(e))
+            .map_err(Error::from)
+    }
+
+    /// Save a document straight to a file, replacing it atomically.
+    #[napi]
+    pub fn save_document_to_path(
+        &self,
+        doc_handle: u32,
+        path: String,
+        flags: u32,
+        version: Option<i32>,
+    ) -> Result<(), ErrorCode> {
+        self.sync(|lib| lib.save_to_path(doc_handle, path, &SaveOptions::from_raw(flags, version)?))?
+            .map_err(Error::from)
+    }
+
+    /// Whether the document has changed since it was loaded or last saved.
+    #[napi]
+    pub fn is_document_dirty(&self, doc_handle: u32) -> Result<bool, ErrorCode> {
+        self.sync(|lib| lib.is_document_dirty(doc_handle))?
+            .map_err(Error::from)
+    }
+
+    // --- Forms ---
+
+    /// Highlight colour (0xRRGGBB) of form fields of `field_type`, or of
+    /// every field for 0, as drawn by renders with `drawForms`.
+    #[napi]
+    pub fn set_form_field_highlight_colour(&self, doc_handle: u32, field_type: i32, colour: u32) -> Result<(), ErrorCode> {
+        self.sync(|lib| lib.set_form_field_highlight_colour(doc_handle, field_type, colour))?
+            .map_err(Error::from)
+    }
+
+    /// Opacity of the form field highlight, 0–255.
+    #[napi]
+    pub fn set_form_field_highlight_alpha(&self, doc_handle: u32, alpha: u32) -> Result<(), ErrorCode> {
+        let alpha = u8::try_from(alpha)
+            .map_err(|_| Error::from(PdfiumError::InvalidArgument(format!("highlight alpha: {}", alpha))))?;
+        self.sync(|lib| lib.set_form_field_highlight_alpha(doc_handle, alpha))?
+            .map_err(Error::from)
     }
 
     // --- Attachments ---
 
     #[napi]
-    pub fn get_attachment_count(&self, doc_handle: u32) -> Result<i32> {
-        self.inner
-            .borrow()
-            .get_attachment_count(doc_handle)
-            .map_err(|e| Error::from_reason(e))
+    pub fn get_attachment_count(&self, doc_handle: u32) -> Result<i32, ErrorCode> {
+        self.sync(|lib| lib.get_attachment_count(doc_handle))?
+            .map_err(Error::from)
     }
 
     #[napi]
-    pub fn get_attachment(&self, doc_handle: u32, index: i32) -> Result<Option<NativeAttachment>> {
-        self.inner
-            .borrow()
-            .get_attachment(doc_handle, index)
+    pub fn get_attachment(&self, doc_handle: u32, index: i32) -> Result<Option<NativeAttachment>, ErrorCode> {
+        self.sync(|lib| lib.get_attachment(doc_handle, index))?
             .map(|opt| opt.map(|(name, data)| NativeAttachment { name, data: data.into() }))
-            .map_err(|e| Error::from_reason(e))
+            .map_err(Error::from)
     }
 
     // --- Page Import ---
@@ -483,11 +1297,9 @@ impl NativePdfium {
         src_handle: u32,
         page_range: Option<String>,
         insert_index: i32,
-    ) -> Result<()> {
-        self.inner
-            .borrow()
-            .import_pages(dest_handle, src_handle, page_range.as_deref(), insert_index)
-            .map_err(|e| Error::from_reason(e))
+    ) -> Result<(), ErrorCode> {
+        self.sync(|lib| lib.import_pages(dest_handle, src_handle, page_range.as_deref(), insert_index))?
+            .map_err(Error::from)
     }
 
     #[napi]
@@ -497,11 +1309,9 @@ impl NativePdfium {
         src_handle: u32,
         page_indices: Vec<i32>,
         insert_index: i32,
-    ) -> Result<()> {
-        self.inner
-            .borrow()
-            .import_pages_by_index(dest_handle, src_handle, &page_indices, insert_index)
-            .map_err(|e| Error::from_reason(e))
+    ) -> Result<(), ErrorCode> {
+        self.sync(|lib| lib.import_pages_by_index(dest_handle, src_handle, &page_indices, insert_index))?
+            .map_err(Error::from)
     }
 
     #[napi]
@@ -512,77 +1322,61 @@ impl NativePdfium {
         output_height: f64,
         pages_per_row: u32,
         pages_per_column: u32,
-    ) -> Result<u32> {
-        self.inner
-            .borrow_mut()
-            .import_n_pages_to_one(
-                src_handle,
-                output_width as f32,
-                output_height as f32,
-                pages_per_row as usize,
-                pages_per_column as usize,
-            )
-            .map_err(|e| Error::from_reason(e))
+    ) -> Result<u32, ErrorCode> {
+        self.sync(|lib| lib.import_n_pages_to_one(
+            src_handle,
+            output_width as f32,
+            output_height as f32,
+            pages_per_row as usize,
+            pages_per_column as usize,
+        ))?
+            .map_err(Error::from)
     }
 
     #[napi]
-    pub fn copy_viewer_preferences(&self, dest_handle: u32, src_handle: u32) -> Result<bool> {
-        self.inner
-            .borrow()
-            .copy_viewer_preferences(dest_handle, src_handle)
-            .map_err(|e| Error::from_reason(e))
+    pub fn copy_viewer_preferences(&self, dest_handle: u32, src_handle: u32) -> Result<bool, ErrorCode> {
+        self.sync(|lib| lib.copy_viewer_preferences(dest_handle, src_handle))?
+            .map_err(Error::from)
     }
 
     // --- Signatures ---
 
     #[napi]
-    pub fn get_signature_count(&self, doc_handle: u32) -> Result<i32> {
-        self.inner
-            .borrow()
-            .get_signature_count(doc_handle)
-            .map_err(|e| Error::from_reason(e))
+    pub fn get_signature_count(&self, doc_handle: u32) -> Result<i32, ErrorCode> {
+        self.sync(|lib| lib.get_signature_count(doc_handle))?
+            .map_err(Error::from)
     }
 
     // --- Bookmarks ---
 
     #[napi]
-    pub fn get_bookmarks(&self, doc_handle: u32) -> Result<Vec<NativeBookmark>> {
-        let nodes = self
-            .inner
-            .borrow()
-            .get_bookmarks(doc_handle)
-            .map_err(|e| Error::from_reason(e))?;
+    pub fn get_bookmarks(&self, doc_handle: u32) -> Result<Vec<NativeBookmark>, ErrorCode> {
+        let nodes = self.sync(|lib| lib.get_bookmarks(doc_handle))?
+            .map_err(Error::from)?;
         Ok(to_native_bookmarks(nodes))
     }
 
     // --- Annotations ---
 
     #[napi]
-    pub fn get_annotations(&self, page_handle: u32) -> Result<Vec<NativeAnnotation>> {
-        let infos = self
-            .inner
-            .borrow()
-            .get_annotations(page_handle)
-            .map_err(|e| Error::from_reason(e))?;
+    pub fn get_annotations(&self, page_handle: u32) -> Result<Vec<NativeAnnotation>, ErrorCode> {
+        let infos = self.sync(|lib| lib.get_annotations(page_handle))?
+            .map_err(Error::from)?;
         Ok(to_native_annotations(infos))
     }
 
     // --- Annotation Mutations ---
 
     #[napi]
-    pub fn create_annotation(&self, page_handle: u32, subtype: i32) -> Result<i32> {
-        self.inner
-            .borrow()
-            .create_annotation(page_handle, subtype)
-            .map_err(|e| Error::from_reason(e))
+    pub fn create_annotation(&self, page_handle: u32, subtype: i32) -> Result<i32, ErrorCode> {
+        self.sync(|lib| lib.create_annotation(page_handle, subtype))?
+            .map_err(Error::from)
     }
 
     #[napi]
-    pub fn remove_annotation(&self, page_handle: u32, index: i32) -> Result<bool> {
-        self.inner
-            .borrow()
-            .remove_annotation(page_handle, index)
-            .map_err(|e| Error::from_reason(e))
+    pub fn remove_annotation(&self, page_handle: u32, index: i32) -> Result<bool, ErrorCode> {
+        self.sync(|lib| lib.remove_annotation(page_handle, index))?
+            .map_err(Error::from)
     }
 
     #[napi]
@@ -594,14 +1388,13 @@ impl NativePdfium {
         top: f64,
         right: f64,
         bottom: f64,
-    ) -> Result<bool> {
-        self.inner
-            .borrow()
-            .set_annotation_rect(page_handle, index, left as f32, top as f32, right as f32, bottom as f32)
-            .map_err(|e| Error::from_reason(e))
+    ) -> Result<bool, ErrorCode> {
+        self.sync(|lib| lib.set_annotation_rect(page_handle, index, left as f32, top as f32, right as f32, bottom as f32))?
+            .map_err(Error::from)
     }
 
     #[napi]
+    #[allow(clippy::too_many_arguments)]
     pub fn set_annotation_colour(
         &self,
         page_handle: u32,
@@ -611,27 +1404,21 @@ impl NativePdfium {
         g: u32,
         b: u32,
         a: u32,
-    ) -> Result<bool> {
-        self.inner
-            .borrow()
-            .set_annotation_colour(page_handle, index, colour_type, r, g, b, a)
-            .map_err(|e| Error::from_reason(e))
+    ) -> Result<bool, ErrorCode> {
+        self.sync(|lib| lib.set_annotation_colour(page_handle, index, colour_type, r, g, b, a))?
+            .map_err(Error::from)
     }
 
     #[napi]
-    pub fn get_annotation_flags(&self, page_handle: u32, index: i32) -> Result<i32> {
-        self.inner
-            .borrow()
-            .get_annotation_flags(page_handle, index)
-            .map_err(|e| Error::from_reason(e))
+    pub fn get_annotation_flags(&self, page_handle: u32, index: i32) -> Result<i32, ErrorCode> {
+        self.sync(|lib| lib.get_annotation_flags(page_handle, index))?
+            .map_err(Error::from)
     }
 
     #[napi]
-    pub fn set_annotation_flags(&self, page_handle: u32, index: i32, flags: i32) -> Result<bool> {
-        self.inner
-            .borrow()
-            .set_annotation_flags(page_handle, index, flags)
-            .map_err(|e| Error::from_reason(e))
+    pub fn set_annotation_flags(&self, page_handle: u32, index: i32, flags: i32) -> Result<bool, ErrorCode> {
+        self.sync(|lib| lib.set_annotation_flags(page_handle, index, flags))?
+            .map_err(Error::from)
     }
 
     #[napi]
@@ -641,11 +1428,9 @@ impl NativePdfium {
         index: i32,
         key: String,
         value: String,
-    ) -> Result<bool> {
-        self.inner
-            .borrow()
-            .set_annotation_string_value(page_handle, index, &key, &value)
-            .map_err(|e| Error::from_reason(e))
+    ) -> Result<bool, ErrorCode> {
+        self.sync(|lib| lib.set_annotation_string_value(page_handle, index, &key, &value))?
+            .map_err(Error::from)
     }
 
     #[napi]
@@ -656,20 +1441,19 @@ impl NativePdfium {
         horizontal_radius: f64,
         vertical_radius: f64,
         border_width: f64,
-    ) -> Result<bool> {
-        self.inner
-            .borrow()
-            .set_annotation_border(
-                page_handle,
-                index,
-                horizontal_radius as f32,
-                vertical_radius as f32,
-                border_width as f32,
-            )
-            .map_err(|e| Error::from_reason(e))
+    ) -> Result<bool, ErrorCode> {
+        self.sync(|lib| lib.set_annotation_border(
+            page_handle,
+            index,
+            horizontal_radius as f32,
+            vertical_radius as f32,
+            border_width as f32,
+        ))?
+            .map_err(Error::from)
     }
 
     #[napi]
+    #[allow(clippy::too_many_arguments)]
     pub fn set_annotation_attachment_points(
         &self,
         page_handle: u32,
@@ -679,22 +1463,21 @@ impl NativePdfium {
         x2: f64, y2: f64,
         x3: f64, y3: f64,
         x4: f64, y4: f64,
-    ) -> Result<bool> {
-        self.inner
-            .borrow()
-            .set_annotation_attachment_points(
-                page_handle,
-                annot_index,
-                quad_index as usize,
-                x1 as f32, y1 as f32,
-                x2 as f32, y2 as f32,
-                x3 as f32, y3 as f32,
-                x4 as f32, y4 as f32,
-            )
-            .map_err(|e| Error::from_reason(e))
+    ) -> Result<bool, ErrorCode> {
+        self.sync(|lib| lib.set_annotation_attachment_points(
+            page_handle,
+            annot_index,
+            quad_index as usize,
+            x1 as f32, y1 as f32,
+            x2 as f32, y2 as f32,
+            x3 as f32, y3 as f32,
+            x4 as f32, y4 as f32,
+        ))?
+            .map_err(Error::from)
     }
 
     #[napi]
+    #[allow(clippy::too_many_arguments)]
     pub fn append_annotation_attachment_points(
         &self,
         page_handle: u32,
@@ -703,103 +1486,83 @@ impl NativePdfium {
         x2: f64, y2: f64,
         x3: f64, y3: f64,
         x4: f64, y4: f64,
-    ) -> Result<bool> {
-        self.inner
-            .borrow()
-            .append_annotation_attachment_points(
-                page_handle,
-                annot_index,
-                x1 as f32, y1 as f32,
-                x2 as f32, y2 as f32,
-                x3 as f32, y3 as f32,
-                x4 as f32, y4 as f32,
-            )
-            .map_err(|e| Error::from_reason(e))
+    ) -> Result<bool, ErrorCode> {
+        self.sync(|lib| lib.append_annotation_attachment_points(
+            page_handle,
+            annot_index,
+            x1 as f32, y1 as f32,
+            x2 as f32, y2 as f32,
+            x3 as f32, y3 as f32,
+            x4 as f32, y4 as f32,
+        ))?
+            .map_err(Error::from)
     }
 
     #[napi]
-    pub fn set_annotation_uri(&self, page_handle: u32, index: i32, uri: String) -> Result<bool> {
-        self.inner
-            .borrow()
-            .set_annotation_uri(page_handle, index, &uri)
-            .map_err(|e| Error::from_reason(e))
+    pub fn set_annotation_uri(&self, page_handle: u32, index: i32, uri: String) -> Result<bool, ErrorCode> {
+        self.sync(|lib| lib.set_annotation_uri(page_handle, index, &uri))?
+            .map_err(Error::from)
     }
 
     // --- Text Character Extended Operations ---
 
     #[napi]
-    pub fn get_char_unicode(&self, text_page_handle: u32, char_index: i32) -> Result<u32> {
-        self.inner
-            .borrow()
-            .get_char_unicode(text_page_handle, char_index)
-            .map_err(|e| Error::from_reason(e))
+    pub fn get_char_unicode(&self, text_page_handle: u32, char_index: i32) -> Result<u32, ErrorCode> {
+        self.sync(|lib| lib.get_char_unicode(text_page_handle, char_index))?
+            .map_err(Error::from)
     }
 
     #[napi]
-    pub fn is_char_generated(&self, text_page_handle: u32, char_index: i32) -> Result<bool> {
-        self.inner
-            .borrow()
-            .is_char_generated(text_page_handle, char_index)
-            .map_err(|e| Error::from_reason(e))
+    pub fn is_char_generated(&self, text_page_handle: u32, char_index: i32) -> Result<bool, ErrorCode> {
+        self.sync(|lib| lib.is_char_generated(text_page_handle, char_index))?
+            .map_err(Error::from)
     }
 
     #[napi]
-    pub fn is_char_hyphen(&self, text_page_handle: u32, char_index: i32) -> Result<bool> {
-        self.inner
-            .borrow()
-            .is_char_hyphen(text_page_handle, char_index)
-            .map_err(|e| Error::from_reason(e))
+    pub fn is_char_hyphen(&self, text_page_handle: u32, char_index: i32) -> Result<bool, ErrorCode> {
+        self.sync(|lib| lib.is_char_hyphen(text_page_handle, char_index))?
+            .map_err(Error::from)
     }
 
     #[napi]
-    pub fn has_char_unicode_map_error(&self, text_page_handle: u32, char_index: i32) -> Result<bool> {
-        self.inner
-            .borrow()
-            .has_char_unicode_map_error(text_page_handle, char_index)
-            .map_err(|e| Error::from_reason(e))
+    pub fn has_char_unicode_map_error(&self, text_page_handle: u32, char_index: i32) -> Result<bool, ErrorCode> {
+        self.sync(|lib| lib.has_char_unicode_map_error(text_page_handle, char_index))?
+            .map_err(Error::from)
     }
 
     #[napi]
-    pub fn get_char_angle(&self, text_page_handle: u32, char_index: i32) -> Result<f64> {
-        self.inner
-            .borrow()
-            .get_char_angle(text_page_handle, char_index)
-            .map_err(|e| Error::from_reason(e))
+    pub fn get_char_angle(&self, text_page_handle: u32, char_index: i32) -> Result<f64, ErrorCode> {
+        self.sync(|lib| lib.get_char_angle(text_page_handle, char_index))?
+            .map_err(Error::from)
     }
 
     /// Get the origin (x, y) of a character. Returns { x, y } or null.
     #[napi]
-    pub fn get_char_origin(&self, text_page_handle: u32, char_index: i32) -> Result<Option<NativePoint>> {
-        self.inner
-            .borrow()
-            .get_char_origin(text_page_handle, char_index)
+    pub fn get_char_origin(&self, text_page_handle: u32, char_index: i32) -> Result<Option<NativePoint>, ErrorCode> {
+        self.sync(|lib| lib.get_char_origin(text_page_handle, char_index))?
             .map(|opt| opt.map(|(x, y)| NativePoint { x, y }))
-            .map_err(|e| Error::from_reason(e))
+            .map_err(Error::from)
     }
 
     /// Get char bounding box. Returns { left, right, bottom, top } or null.
     #[napi]
-    pub fn get_char_box(&self, text_page_handle: u32, char_index: i32) -> Result<Option<NativeCharBox>> {
-        self.inner
-            .borrow()
-            .get_char_box(text_page_handle, char_index)
+    pub fn get_char_box(&self, text_page_handle: u32, char_index: i32) -> Result<Option<NativeCharBox>, ErrorCode> {
+        self.sync(|lib| lib.get_char_box(text_page_handle, char_index))?
             .map(|opt| opt.map(|(left, right, bottom, top)| NativeCharBox { left, right, bottom, top }))
-            .map_err(|e| Error::from_reason(e))
+            .map_err(Error::from)
     }
 
     /// Get char loose bounding box (FS_RECTF). Returns { left, top, right, bottom } or null.
     #[napi]
-    pub fn get_char_loose_box(&self, text_page_handle: u32, char_index: i32) -> Result<Option<NativeRect>> {
-        self.inner
-            .borrow()
-            .get_char_loose_box(text_page_handle, char_index)
+    pub fn get_char_loose_box(&self, text_page_handle: u32, char_index: i32) -> Result<Option<NativeRect>, ErrorCode> {
+        self.sync(|lib| lib.get_char_loose_box(text_page_handle, char_index))?
             .map(|opt| opt.map(|(left, top, right, bottom)| NativeRect {
                 left: left as f64,
                 top: top as f64,
                 right: right as f64,
                 bottom: bottom as f64,
             }))
-            .map_err(|e| Error::from_reason(e))
+            .map_err(Error::from)
     }
 
     #[napi]
@@ -810,41 +1573,33 @@ impl NativePdfium {
         y: f64,
         x_tolerance: f64,
         y_tolerance: f64,
-    ) -> Result<i32> {
-        self.inner
-            .borrow()
-            .get_char_index_at_pos(text_page_handle, x, y, x_tolerance, y_tolerance)
-            .map_err(|e| Error::from_reason(e))
+    ) -> Result<i32, ErrorCode> {
+        self.sync(|lib| lib.get_char_index_at_pos(text_page_handle, x, y, x_tolerance, y_tolerance))?
+            .map_err(Error::from)
     }
 
     /// Get fill colour of a character. Returns { r, g, b, a } or null.
     #[napi]
-    pub fn get_char_fill_colour(&self, text_page_handle: u32, char_index: i32) -> Result<Option<NativeColour>> {
-        self.inner
-            .borrow()
-            .get_char_fill_colour(text_page_handle, char_index)
+    pub fn get_char_fill_colour(&self, text_page_handle: u32, char_index: i32) -> Result<Option<NativeColour>, ErrorCode> {
+        self.sync(|lib| lib.get_char_fill_colour(text_page_handle, char_index))?
             .map(|opt| opt.map(|(r, g, b, a)| NativeColour { r, g, b, a }))
-            .map_err(|e| Error::from_reason(e))
+            .map_err(Error::from)
     }
 
     /// Get stroke colour of a character. Returns { r, g, b, a } or null.
     #[napi]
-    pub fn get_char_stroke_colour(&self, text_page_handle: u32, char_index: i32) -> Result<Option<NativeColour>> {
-        self.inner
-            .borrow()
-            .get_char_stroke_colour(text_page_handle, char_index)
+    pub fn get_char_stroke_colour(&self, text_page_handle: u32, char_index: i32) -> Result<Option<NativeColour>, ErrorCode> {
+        self.sync(|lib| lib.get_char_stroke_colour(text_page_handle, char_index))?
             .map(|opt| opt.map(|(r, g, b, a)| NativeColour { r, g, b, a }))
-            .map_err(|e| Error::from_reason(e))
+            .map_err(Error::from)
     }
 
     /// Get the character transformation matrix. Returns [a, b, c, d, e, f] or null.
     #[napi]
-    pub fn get_char_matrix(&self, text_page_handle: u32, char_index: i32) -> Result<Option<Vec<f64>>> {
-        self.inner
-            .borrow()
-            .get_char_matrix(text_page_handle, char_index)
+    pub fn get_char_matrix(&self, text_page_handle: u32, char_index: i32) -> Result<Option<Vec<f64>>, ErrorCode> {
+        self.sync(|lib| lib.get_char_matrix(text_page_handle, char_index))?
             .map(|opt| opt.map(|m| m.to_vec()))
-            .map_err(|e| Error::from_reason(e))
+            .map_err(Error::from)
     }
 
     // --- Text Search ---
@@ -856,17 +1611,15 @@ impl NativePdfium {
         text_page_handle: u32,
         query: String,
         flags: u32,
-    ) -> Result<Vec<NativeSearchResult>> {
-        self.inner
-            .borrow()
-            .find_text(text_page_handle, &query, flags)
+    ) -> Result<Vec<NativeSearchResult>, ErrorCode> {
+        self.sync(|lib| lib.find_text(text_page_handle, &query, flags))?
             .map(|results| {
                 results
                     .into_iter()
                     .map(|(index, count)| NativeSearchResult { index, count })
                     .collect()
             })
-            .map_err(|e| Error::from_reason(e))
+            .map_err(Error::from)
     }
 
     // --- Text Rectangles ---
@@ -877,21 +1630,17 @@ impl NativePdfium {
         text_page_handle: u32,
         start_index: i32,
         count: i32,
-    ) -> Result<i32> {
-        self.inner
-            .borrow()
-            .count_text_rects(text_page_handle, start_index, count)
-            .map_err(|e| Error::from_reason(e))
+    ) -> Result<i32, ErrorCode> {
+        self.sync(|lib| lib.count_text_rects(text_page_handle, start_index, count))?
+            .map_err(Error::from)
     }
 
     /// Get a text rectangle by index. Returns { left, top, right, bottom } or null.
     #[napi]
-    pub fn get_text_rect(&self, text_page_handle: u32, rect_index: i32) -> Result<Option<NativeRect>> {
-        self.inner
-            .borrow()
-            .get_text_rect(text_page_handle, rect_index)
+    pub fn get_text_rect(&self, text_page_handle: u32, rect_index: i32) -> Result<Option<NativeRect>, ErrorCode> {
+        self.sync(|lib| lib.get_text_rect(text_page_handle, rect_index))?
             .map(|opt| opt.map(|(left, top, right, bottom)| NativeRect { left, top, right, bottom }))
-            .map_err(|e| Error::from_reason(e))
+            .map_err(Error::from)
     }
 
     #[napi]
@@ -902,59 +1651,48 @@ impl NativePdfium {
         top: f64,
         right: f64,
         bottom: f64,
-    ) -> Result<String> {
-        self.inner
-            .borrow()
-            .get_bounded_text(text_page_handle, left, top, right, bottom)
-            .map_err(|e| Error::from_reason(e))
+    ) -> Result<String, ErrorCode> {
+        self.sync(|lib| lib.get_bounded_text(text_page_handle, left, top, right, bottom))?
+            .map_err(Error::from)
     }
 
     // --- Page Rotation / Flatten / Transparency / Content ---
 
     #[napi]
-    pub fn get_page_rotation(&self, page_handle: u32) -> Result<i32> {
-        self.inner
-            .borrow()
-            .get_page_rotation(page_handle)
-            .map_err(|e| Error::from_reason(e))
+    pub fn get_page_rotation(&self, page_handle: u32) -> Result<i32, ErrorCode> {
+        self.sync(|lib| lib.get_page_rotation(page_handle))?
+            .map_err(Error::from)
     }
 
     #[napi]
-    pub fn set_page_rotation(&self, page_handle: u32, rotation: i32) -> Result<()> {
-        self.inner
-            .borrow()
-            .set_page_rotation(page_handle, rotation)
-            .map_err(|e| Error::from_reason(e))
+    pub fn set_page_rotation(&self, page_handle: u32, rotation: i32) -> Result<(), ErrorCode> {
+        self.sync(|lib| lib.set_page_rotation(page_handle, rotation))?
+            .map_err(Error::from)
     }
 
     #[napi]
-    pub fn has_page_transparency(&self, page_handle: u32) -> Result<bool> {
-        self.inner
-            .borrow()
-            .has_page_transparency(page_handle)
-            .map_err(|e| Error::from_reason(e))
+    pub fn has_page_transparency(&self, page_handle: u32) -> Result<bool, ErrorCode> {
+        self.sync(|lib| lib.has_page_transparency(page_handle))?
+            .map_err(Error::from)
     }
 
     #[napi]
-    pub fn flatten_page(&self, page_handle: u32, flags: i32) -> Result<i32> {
-        self.inner
-            .borrow()
-            .flatten_page(page_handle, flags)
-            .map_err(|e| Error::from_reason(e))
+    pub fn flatten_page(&self, page_handle: u32, flags: i32) -> Result<i32, ErrorCode> {
+        self.sync(|lib| lib.flatten_page(page_handle, flags))?
+            .map_err(Error::from)
     }
 
     #[napi]
-    pub fn generate_content(&self, page_handle: u32) -> Result<bool> {
-        self.inner
-            .borrow()
-            .generate_content(page_handle)
-            .map_err(|e| Error::from_reason(e))
+    pub fn generate_content(&self, page_handle: u32) -> Result<bool, ErrorCode> {
+        self.sync(|lib| lib.generate_content(page_handle))?
+            .map_err(Error::from)
     }
 
     // --- Coordinate Conversion ---
 
     /// Convert device coordinates to page coordinates.
     #[napi]
+    #[allow(clippy::too_many_arguments)]
     pub fn device_to_page(
         &self,
         page_handle: u32,
@@ -965,16 +1703,15 @@ impl NativePdfium {
         rotation: i32,
         device_x: i32,
         device_y: i32,
-    ) -> Result<NativePoint> {
-        self.inner
-            .borrow()
-            .device_to_page(page_handle, start_x, start_y, size_x, size_y, rotation, device_x, device_y)
+    ) -> Result<NativePoint, ErrorCode> {
+        self.sync(|lib| lib.device_to_page(page_handle, start_x, start_y, size_x, size_y, rotation, device_x, device_y))?
             .map(|(x, y)| NativePoint { x, y })
-            .map_err(|e| Error::from_reason(e))
+            .map_err(Error::from)
     }
 
     /// Convert page coordinates to device coordinates.
     #[napi]
+    #[allow(clippy::too_many_arguments)]
     pub fn page_to_device(
         &self,
         page_handle: u32,
@@ -985,34 +1722,27 @@ impl NativePdfium {
         rotation: i32,
         page_x: f64,
         page_y: f64,
-    ) -> Result<NativeDevicePoint> {
-        self.inner
-            .borrow()
-            .page_to_device(page_handle, start_x, start_y, size_x, size_y, rotation, page_x, page_y)
+    ) -> Result<NativeDevicePoint, ErrorCode> {
+        self.sync(|lib| lib.page_to_device(page_handle, start_x, start_y, size_x, size_y, rotation, page_x, page_y))?
             .map(|(x, y)| NativeDevicePoint { x, y })
-            .map_err(|e| Error::from_reason(e))
+            .map_err(Error::from)
     }
 
     // --- Links ---
 
     #[napi]
-    pub fn get_links(&self, page_handle: u32, doc_handle: u32) -> Result<Vec<NativeLink>> {
-        let infos = self
-            .inner
-            .borrow()
-            .get_links(page_handle, doc_handle)
-            .map_err(|e| Error::from_reason(e))?;
+    pub fn get_links(&self, page_handle: u32, doc_handle: u32) -> Result<Vec<NativeLink>, ErrorCode> {
+        let infos = self.sync(|lib| lib.get_links(page_handle, doc_handle))?
+            .map_err(Error::from)?;
         Ok(to_native_links(infos))
     }
 
     // --- Signatures ---
 
     #[napi]
-    pub fn get_signature(&self, doc_handle: u32, index: i32) -> Result<NativeSignature> {
-        let (contents, byte_range, sub_filter, reason, time, doc_mdp) = self.inner
-            .borrow()
-            .get_signature(doc_handle, index)
-            .map_err(|e| Error::from_reason(e))?;
+    pub fn get_signature(&self, doc_handle: u32, index: i32) -> Result<NativeSignature, ErrorCode> {
+        let (contents, byte_range, sub_filter, reason, time, doc_mdp) = self.sync(|lib| lib.get_signature(doc_handle, index))?
+            .map_err(Error::from)?;
 
         Ok(NativeSignature {
             index,
@@ -1024,4 +1754,163 @@ impl NativePdfium {
             doc_mdp_permission: doc_mdp,
         })
     }
+
+    // --- Async ---
+    //
+    // Promise-returning variants of the heavy calls. They run on the
+    // binary's executor thread instead of blocking the event loop.
+
+    /// Load a document off the main thread from a copy of `data`.
+    #[napi(ts_return_type = "Promise<number>")]
+    pub fn load_document_async(&self, env: Env, data: Buffer, password: Option<String>) -> Result<JsObject> {
+        let data = data.to_vec();
+        self.spawn(env, move |lib| lib.load_document(&data, password.as_deref()), |doc| doc)
+    }
+
+    #[napi(ts_return_type = "Promise<number>")]
+    pub fn load_document_from_path_async(&self, env: Env, path: String, password: Option<String>) -> Result<JsObject> {
+        self.spawn(env, move |lib| lib.load_document_from_path(path, password.as_deref()), |doc| doc)
+    }
+
+    #[napi(ts_return_type = "Promise<string>")]
+    pub fn get_full_text_async(&self, env: Env, text_page_handle: u32) -> Result<JsObject> {
+        self.spawn(env, move |lib| lib.get_full_text(text_page_handle), |text| text)
+    }
+
+    #[napi(ts_return_type = "Promise<Buffer>")]
+    #[allow(clippy::too_many_arguments)]
+    pub fn render_page_async(
+        &self,
+        env: Env,
+        page_handle: u32,
+        width: i32,
+        height: i32,
+        rotation: i32,
+        flags: i32,
+        bg_colour: u32,
+        draw_forms: Option<bool>,
+        color_scheme: Option<NativeColorScheme>,
+    ) -> Result<JsObject> {
+        let mut options = RenderOptions::new()
+            .rotation(rotation)
+            .flags(flags)
+            .background(bg_colour)
+            .draw_forms(draw_forms.unwrap_or(false));
+        if let Some(scheme) = color_scheme {
+            options = options.color_scheme(to_color_scheme(scheme));
+        }
+        self.spawn(
+            env,
+            move |lib| lib.render_page_with(page_handle, width, height, &options),
+            Buffer::from,
+        )
+    }
+
+    /// A new token for `render_page_cancellable_async`.
+    #[napi]
+    pub fn create_cancel_token(&self) -> NativeCancelToken {
+        NativeCancelToken::new()
+    }
+
+    /// Render to an RGBA buffer in steps, rejecting with `ERR_PDFIUM_CANCELLED`
+    /// once `token` is cancelled or `timeout_ms` has passed since the call.
+    #[napi(ts_return_type = "Promise<Buffer>")]
+    #[allow(clippy::too_many_arguments)]
+    pub fn render_page_cancellable_async(
+        &self,
+        env: Env,
+        page_handle: u32,
+        width: i32,
+        height: i32,
+        rotation: i32,
+        flags: i32,
+        bg_colour: u32,
+        token: &NativeCancelToken,
+        timeout_ms: Option<u32>,
+    ) -> Result<JsObject> {
+        let options = RenderOptions::new().rotation(rotation).flags(flags).background(bg_colour);
+        let cancelled = Arc::clone(&token.cancelled);
+        let deadline = timeout_ms.map(|ms| Instant::now() + Duration::from_millis(ms.into()));
+        let should_stop =
+            move || cancelled.load(Ordering::Relaxed) || deadline.is_some_and(|deadline| Instant::now() >= deadline);
+        self.spawn(
+            env,
+            move |lib| {
+                let stride = options.get_format().min_stride(width);
+                let mut buffer = vec![0u8; stride * height.max(0) as usize];
+                lib.render_page_cancellable(page_handle, width, height, &options, &mut buffer, stride, should_stop)
+                    .map(|()| buffer)
+            },
+            Buffer::from,
+        )
+    }
+
+    #[napi(ts_return_type = "Promise<Buffer>")]
+    pub fn save_document_async(&self, env: Env, doc_handle: u32, flags: u32, version: Option<i32>) -> Result<JsObject> {
+        self.spawn(env, move |lib| lib.save_document(doc_handle, flags, version), Buffer::from)
+    }
+
+    #[napi(ts_return_type = "Promise<void>")]
+    pub fn save_document_to_path_async(
+        &self,
+        env: Env,
+        doc_handle: u32,
+        path: String,
+        flags: u32,
+        version: Option<i32>,
+    ) -> Result<JsObject> {
+        self.spawn(
+            env,
+            move |lib| lib.save_to_path(doc_handle, &path, &SaveOptions::from_raw(flags, version)?),
+            |()| (),
+        )
+    }
+}
+
+#[cfg(feature = "encode")]
+#[napi]
+impl NativePdfium {
+    /// Render the page straight to PNG. `dpi` is recorded in the file.
+    #[napi]
+    #[allow(clippy::too_many_arguments)]
+    pub fn render_page_to_png(
+        &self,
+        page_handle: u32,
+        width: i32,
+        height: i32,
+        rotation: i32,
+        flags: i32,
+        bg_colour: u32,
+        dpi: Option<f64>,
+    ) -> Result<Buffer, ErrorCode> {
+        let options = RenderOptions::new().rotation(rotation).flags(flags).background(bg_colour);
+        self.sync(|lib| {
+            lib.render_page_encoded(page_handle, width, height, &options, ImageFormat::Png, dpi.map(|d| d as f32))
+        })?
+            .map(|data| data.into())
+            .map_err(Error::from)
+    }
+
+    /// Render the page straight to JPEG at `quality` 1–100.
+    #[napi]
+    #[allow(clippy::too_many_arguments)]
+    pub fn render_page_to_jpeg(
+        &self,
+        page_handle: u32,
+        width: i32,
+        height: i32,
+        rotation: i32,
+        flags: i32,
+        bg_colour: u32,
+        quality: u32,
+        dpi: Option<f64>,
+    ) -> Result<Buffer, ErrorCode> {
+        let quality = u8::try_from(quality)
+            .map_err(|_| Error::from(PdfiumError::InvalidArgument(format!("JPEG quality: {}", quality))))?;
+        let options = RenderOptions::new().rotation(rotation).flags(flags).background(bg_colour);
+        let format = ImageFormat::Jpeg { quality };
+        self.sync(|lib| lib.render_page_encoded(page_handle, width, height, &options, format, dpi.map(|d| d as f32)))?
+            .map(|data| data.into())
+            .map_err(Error::from)
+    }
 }
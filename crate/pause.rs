@@ -0,0 +1,105 @@
+//! `IFSDK_PAUSE` over a Rust closure, used by progressive rendering.
+//!
+//! PDFium polls `NeedToPauseNow` from within `FPDF_RenderPageBitmap_Start` and
+//! `FPDF_RenderPage_Continue`; a true answer makes the call return early. The
+//! closure decides whether the render should stop, e.g. because a deadline
+//! passed or a cancellation flag was raised. A panic in it stops the render
+//! and is resumed once PDFium has returned.
+
+use crate::bindings::{FPDF_BOOL, IFSDK_PAUSE};
+use std::any::Any;
+use std::panic::{self, AssertUnwindSafe};
+use std::ptr;
+
+/// An `IFSDK_PAUSE` that asks `should_stop` whether to pause.
+#[repr(C)]
+pub(crate) struct PauseContext<'a> {
+    // Must stay first: PDFium hands the callback a pointer to this field.
+    pause: IFSDK_PAUSE,
+    should_stop: &'a mut dyn FnMut() -> bool,
+    stopped: bool,
+    panic: Option<Box<dyn Any + Send>>,
+}
+
+impl<'a> PauseContext<'a> {
+    pub(crate) fn new(should_stop: &'a mut dyn FnMut() -> bool) -> Self {
+        Self {
+            pause: IFSDK_PAUSE {
+                version: 1,
+                NeedToPauseNow: Some(need_to_pause_now),
+                user: ptr::null_mut(),
+            },
+            should_stop,
+            stopped: false,
+            panic: None,
+        }
+    }
+
+    /// Pointer to pass to `FPDF_RenderPageBitmap_Start` / `FPDF_RenderPage_Continue`.
+    pub(crate) fn as_ptr(&mut self) -> *mut IFSDK_PAUSE {
+        self as *mut Self as *mut IFSDK_PAUSE
+    }
+
+    /// Ask the closure directly, e.g. before starting work PDFium would not
+    /// poll for. Once it has said stop, it is not asked again.
+    pub(crate) fn poll(&mut self) -> bool {
+        if !self.stopped {
+            match panic::catch_unwind(AssertUnwindSafe(|| (self.should_stop)())) {
+                Ok(stop) => self.stopped = stop,
+                Err(payload) => {
+                    self.panic = Some(payload);
+                    self.stopped = true;
+                }
+            }
+        }
+        self.stopped
+    }
+
+    /// Resume a panic caught in the closure, if there was one.
+    pub(crate) fn resume_panic(&mut self) {
+        if let Some(payload) = self.panic.take() {
+            panic::resume_unwind(payload);
+        }
+    }
+}
+
+unsafe extern "C" fn need_to_pause_now(this: *mut IFSDK_PAUSE) -> FPDF_BOOL {
+    // SAFETY: `this` points to the start of a live `PauseContext`.
+    let ctx = &mut *(this as *mut PauseContext<'_>);
+    ctx.poll() as FPDF_BOOL
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn need_to_pause(ctx: &mut PauseContext<'_>) -> bool {
+        let raw = ctx.as_ptr();
+        unsafe { ((*raw).NeedToPauseNow.unwrap())(raw) != 0 }
+    }
+
+    #[test]
+    fn asks_the_closure_until_it_says_stop() {
+        let mut calls = 0;
+        let mut should_stop = || {
+            calls += 1;
+            calls == 3
+        };
+        let mut ctx = PauseContext::new(&mut should_stop);
+        assert!(!need_to_pause(&mut ctx));
+        assert!(!need_to_pause(&mut ctx));
+        assert!(need_to_pause(&mut ctx));
+        assert!(ctx.poll());
+        drop(ctx);
+        assert_eq!(calls, 3);
+    }
+
+    #[test]
+    fn panics_stop_the_render_and_resume_later() {
+        let mut should_stop = || -> bool { panic!("boom") };
+        let mut ctx = PauseContext::new(&mut should_stop);
+        assert!(need_to_pause(&mut ctx));
+        let result = panic::catch_unwind(AssertUnwindSafe(|| ctx.resume_panic()));
+        assert!(result.is_err());
+    }
+}
@@ -0,0 +1,276 @@
+//! Progressive loading through `FPDFAvail`.
+//!
+//! A `ProgressiveLoader` wraps a `DataAvailability` source whose bytes may
+//! still be arriving. Poll `is_document_available`, fetching the ranges the
+//! source is asked for in between, until it returns true; then take the
+//! document handle and check `is_page_available` before loading each page.
+//! For linearized files the first page becomes available long before the
+//! rest of the file.
+
+use crate::bindings::*;
+use crate::error::PdfiumError;
+use crate::library::{password_cstring, PdfiumLibrary};
+use crate::reader::{DataAvailability, ReaderAccess};
+use std::os::raw::c_int;
+use std::ptr;
+
+/// The `FPDFAvail_*` exports, resolved together when a loader is created.
+#[derive(Clone, Copy)]
+pub(crate) struct AvailSymbols {
+    pub(crate) create: FnAvailCreate,
+    pub(crate) destroy: FnAvailDestroy,
+    pub(crate) is_doc_avail: FnAvailIsDocAvail,
+    pub(crate) get_document: FnAvailGetDocument,
+    pub(crate) get_first_page_num: FnAvailGetFirstPageNum,
+    pub(crate) is_page_avail: FnAvailIsPageAvail,
+    pub(crate) is_linearized: FnAvailIsLinearized,
+}
+
+/// An `FPDF_AVAIL` together with the source it reads from.
+///
+/// PDFium keeps reading through the avail for as long as a document loaded
+/// from it is open, so once the document exists this moves into the library
+/// next to it and is destroyed after the document is closed.
+struct AvailSource {
+    avail: FPDF_AVAIL,
+    destroy: FnAvailDestroy,
+    access: ReaderAccess,
+}
+
+// SAFETY: `avail` is only passed back to PDFium by whichever thread owns the
+// library, like every other PDFium pointer.
+unsafe impl Send for AvailSource {}
+
+impl Drop for AvailSource {
+    fn drop(&mut self) {
+        unsafe { (self.destroy)(self.avail) };
+    }
+}
+
+/// Loads a document whose bytes arrive over time, e.g. through HTTP range
+/// requests, so pages can be shown before the whole file is present.
+pub struct ProgressiveLoader<'lib> {
+    lib: &'lib PdfiumLibrary,
+    syms: AvailSymbols,
+    avail: FPDF_AVAIL,
+    hints: *mut FX_DOWNLOADHINTS,
+    // Owned until the document is loaded, then handed to the library.
+    source: Option<AvailSource>,
+    document: Option<u32>,
+}
+
+impl PdfiumLibrary {
+    /// Start loading a document progressively from `source`.
+    pub fn progressive_loader<A>(&self, source: A) -> Result<ProgressiveLoader<'_>, PdfiumError>
+    where
+        A: DataAvailability + 'static,
+    {
+        self.ensure_initialised()?;
+        let syms = self.avail_symbols()?;
+        let mut access = ReaderAccess::from_source(source)?;
+
+        let avail = unsafe { (syms.create)(access.avail_ptr(), access.as_ptr()) };
+        if avail.is_null() {
+            return Err(PdfiumError::Failed("FPDFAvail_Create failed".into()));
+        }
+
+        let hints = access.hints_ptr();
+        Ok(ProgressiveLoader {
+            lib: self,
+            syms,
+            avail,
+            hints,
+            source: Some(AvailSource {
+                avail,
+                destroy: syms.destroy,
+                access,
+            }),
+            document: None,
+        })
+    }
+}
+
+impl ProgressiveLoader<'_> {
+    /// Whether the file is linearized, or `None` if not enough of it has
+    /// arrived to tell.
+    pub fn is_linearized(&mut self) -> Result<Option<bool>, PdfiumError> {
+        self.ensure_usable()?;
+        Ok(match unsafe { (self.syms.is_linearized)(self.avail) } {
+            PDF_LINEARIZED => Some(true),
+            PDF_NOT_LINEARIZED => Some(false),
+            _ => None,
+        })
+    }
+
+    /// Whether enough of the file has arrived to open the document. When it
+    /// has not, the source has been asked for the ranges PDFium needs next.
+    pub fn is_document_available(&mut self) -> Result<bool, PdfiumError> {
+        self.ensure_usable()?;
+        if self.document.is_some() {
+            return Ok(true);
+        }
+        let result = unsafe { (self.syms.is_doc_avail)(self.avail, self.hints) };
+        self.data_status(result)
+    }
+
+    /// Open the document once `is_document_available` reports true.
+    ///
+    /// The returned handle is a normal document handle, closed with
+    /// `close_document`. Later calls return the same handle.
+    pub fn document(&mut self, password: Option<&str>) -> Result<u32, PdfiumError> {
+        if let Some(handle) = self.document {
+            self.ensure_usable()?;
+            return Ok(handle);
+        }
+        if !self.is_document_available()? {
+            return Err(PdfiumError::Failed("Document data is not available yet".into()));
+        }
+
+        let password_cstr = password_cstring(password)?;
+        let doc = unsafe {
+            (self.syms.get_document)(self.avail, password_cstr.as_ref().map_or(ptr::null(), |c| c.as_ptr()))
+        };
+        if doc.is_null() {
+            return Err(self.take_failure().unwrap_or_else(|| PdfiumError::from_error_code(self.lib.get_last_error())));
+        }
+
+        let source = self.source.take().expect("source is held until the document is loaded");
+        let handle = self.lib.register_document(doc, Box::new(source))?;
+        self.document = Some(handle);
+        Ok(handle)
+    }
+
+    /// The page to show first: for linearized files, the one whose data
+    /// arrives first.
+    pub fn first_page_number(&self) -> Result<i32, PdfiumError> {
+        let doc = self.lib.document_ptr(self.loaded_document()?)?;
+        Ok(unsafe { (self.syms.get_first_page_num)(doc) })
+    }
+
+    /// Whether page `index` can be loaded. When it cannot, the source has
+    /// been asked for the ranges PDFium needs next.
+    pub fn is_page_available(&mut self, index: i32) -> Result<bool, PdfiumError> {
+        let handle = self.loaded_document()?;
+        if index < 0 || index >= self.lib.get_page_count(handle)? {
+            return Err(PdfiumError::PageNotFound { index: Some(index) });
+        }
+        let result = unsafe { (self.syms.is_page_avail)(self.avail, index as c_int, self.hints) };
+        self.data_status(result)
+    }
+
+    fn loaded_document(&self) -> Result<u32, PdfiumError> {
+        self.document
+            .ok_or_else(|| PdfiumError::Failed("Document is not loaded yet".into()))
+    }
+
+    /// Check that the avail has not been destroyed: before the document is
+    /// loaded the loader owns it, afterwards it lives as long as the document.
+    fn ensure_usable(&self) -> Result<(), PdfiumError> {
+        self.lib.ensure_initialised()?;
+        match (self.document, &self.source) {
+            (Some(handle), _) => self.lib.document_ptr(handle).map(drop),
+            (None, Some(_)) => Ok(()),
+            // Registering the document failed and took the avail with it.
+            (None, None) => Err(PdfiumError::Failed("Progressive load was abandoned".into())),
+        }
+    }
+
+    fn data_status(&mut self, result: c_int) -> Result<bool, PdfiumError> {
+        match result {
+            PDF_DATA_AVAIL => Ok(true),
+            PDF_DATA_NOTAVAIL => Ok(false),
+            _ => Err(self.take_failure().unwrap_or(PdfiumError::Format)),
+        }
+    }
+
+    /// A read error or panic in the source, while the loader still owns it.
+    fn take_failure(&mut self) -> Option<PdfiumError> {
+        let reason = self.source.as_mut()?.access.take_failure()?;
+        Some(PdfiumError::Failed(format!("Reader failed: {}", reason)))
+    }
+}
+
+impl Drop for ProgressiveLoader<'_> {
+    fn drop(&mut self) {
+        // After `FPDF_DestroyLibrary` the avail can no longer be destroyed.
+        if !self.lib.is_initialised() {
+            if let Some(source) = self.source.take() {
+                std::mem::forget(source);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{skip_if_no_lib, test_pdf_path};
+    use std::io;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+
+    /// Reveals a prefix of the file that the test grows between polls.
+    struct GradualSource {
+        data: Vec<u8>,
+        revealed: Arc<AtomicUsize>,
+    }
+
+    impl DataAvailability for GradualSource {
+        fn file_len(&self) -> u64 {
+            self.data.len() as u64
+        }
+
+        fn is_data_available(&mut self, offset: u64, size: u64) -> bool {
+            offset + size <= self.revealed.load(Ordering::SeqCst) as u64
+        }
+
+        fn read_at(&mut self, offset: u64, buf: &mut [u8]) -> io::Result<()> {
+            let start = offset as usize;
+            buf.copy_from_slice(&self.data[start..start + buf.len()]);
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn loads_as_data_arrives() {
+        let path = skip_if_no_lib!();
+        let lib = PdfiumLibrary::load(&path).unwrap();
+        lib.init_library();
+
+        let data = std::fs::read(test_pdf_path()).unwrap();
+        let len = data.len();
+        let revealed = Arc::new(AtomicUsize::new(0));
+        let mut loader = lib
+            .progressive_loader(GradualSource {
+                data,
+                revealed: Arc::clone(&revealed),
+            })
+            .unwrap();
+
+        assert!(!loader.is_document_available().unwrap());
+        assert!(loader.document(None).is_err());
+
+        while !loader.is_document_available().unwrap() {
+            assert!(revealed.load(Ordering::SeqCst) < len, "document never became available");
+            revealed.fetch_add(1024, Ordering::SeqCst);
+        }
+        assert!(loader.is_linearized().unwrap().is_some());
+
+        let doc = loader.document(None).unwrap();
+        assert_eq!(loader.document(None).unwrap(), doc);
+
+        let first = loader.first_page_number().unwrap();
+        while !loader.is_page_available(first).unwrap() {
+            revealed.fetch_add(1024, Ordering::SeqCst);
+        }
+        let page = lib.load_page(doc, first).unwrap();
+        assert!(lib.get_page_width(page).unwrap() > 0.0);
+
+        // The loader only stays usable while the document is open.
+        lib.close_document(doc).unwrap();
+        assert!(loader.is_page_available(first).is_err());
+
+        drop(loader);
+        lib.destroy_library();
+    }
+}
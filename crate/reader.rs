@@ -0,0 +1,323 @@
+//! `FPDF_FILEACCESS` (and the `FPDFAvail` callbacks) over a Rust byte source.
+//!
+//! PDFium pulls blocks through the `m_GetBlock` callback whenever it needs
+//! them, for as long as the document is open, so the source lives on the heap
+//! at a fixed address and is dropped only after the document is closed.
+
+use crate::bindings::{FPDF_BOOL, FPDF_FILEACCESS, FX_DOWNLOADHINTS, FX_FILEAVAIL};
+use crate::error::PdfiumError;
+use std::io::{self, Read, Seek, SeekFrom};
+use std::os::raw::{c_int, c_uchar, c_ulong, c_void};
+use std::panic::{self, AssertUnwindSafe};
+use std::ptr::NonNull;
+
+/// A document source whose bytes may arrive over time, e.g. through HTTP
+/// range requests. Used by `ProgressiveLoader`.
+pub trait DataAvailability: Send {
+    /// Total length of the file in bytes.
+    fn file_len(&self) -> u64;
+
+    /// Whether `size` bytes starting at `offset` can be read right now.
+    fn is_data_available(&mut self, offset: u64, size: u64) -> bool;
+
+    /// Fill `buf` from `offset`. Only called for ranges reported available.
+    fn read_at(&mut self, offset: u64, buf: &mut [u8]) -> io::Result<()>;
+
+    /// PDFium needs this range to make progress; a downloader should fetch it
+    /// next. The default ignores the hint.
+    fn request_range(&mut self, offset: u64, size: u64) {
+        let _ = (offset, size);
+    }
+}
+
+/// A `Read + Seek` source, where every byte is always available.
+struct SeekSource<R> {
+    reader: R,
+    len: u64,
+}
+
+impl<R: Read + Seek + Send> DataAvailability for SeekSource<R> {
+    fn file_len(&self) -> u64 {
+        self.len
+    }
+
+    fn is_data_available(&mut self, _offset: u64, _size: u64) -> bool {
+        true
+    }
+
+    fn read_at(&mut self, offset: u64, buf: &mut [u8]) -> io::Result<()> {
+        self.reader.seek(SeekFrom::Start(offset))?;
+        self.reader.read_exact(buf)
+    }
+}
+
+struct ReaderState {
+    source: Box<dyn DataAvailability>,
+    // Why the most recent read failed, reported if the load fails.
+    failure: Option<String>,
+    // A panic may leave the source half-updated, so it is never used again.
+    poisoned: bool,
+}
+
+impl ReaderState {
+    /// Run `f` against the source, turning a panic into `fallback` instead of
+    /// unwinding into PDFium, which would abort.
+    fn guarded<T>(&mut self, fallback: T, f: impl FnOnce(&mut dyn DataAvailability) -> T) -> T {
+        if self.poisoned {
+            return fallback;
+        }
+        match panic::catch_unwind(AssertUnwindSafe(|| f(&mut *self.source))) {
+            Ok(value) => value,
+            Err(_) => {
+                self.poisoned = true;
+                self.failure = Some("reader panicked".into());
+                fallback
+            }
+        }
+    }
+}
+
+// PDFium hands these callbacks a pointer to the C struct only, so each one
+// is the first field of a `repr(C)` wrapper that also carries the state.
+#[repr(C)]
+struct AvailCallbacks {
+    avail: FX_FILEAVAIL,
+    state: NonNull<ReaderState>,
+}
+
+#[repr(C)]
+struct HintCallbacks {
+    hints: FX_DOWNLOADHINTS,
+    state: NonNull<ReaderState>,
+}
+
+/// A byte source exposed to PDFium as an `FPDF_FILEACCESS`, plus the
+/// `FX_FILEAVAIL` and `FX_DOWNLOADHINTS` used for progressive loading.
+pub(crate) struct ReaderAccess {
+    access: Box<FPDF_FILEACCESS>,
+    avail: Box<AvailCallbacks>,
+    hints: Box<HintCallbacks>,
+    // Owned; leaked from a `Box` and freed on drop, so the callback pointers
+    // stay valid while PDFium holds them.
+    state: NonNull<ReaderState>,
+}
+
+// SAFETY: The state is only reached through this value or from PDFium calls
+// made by the thread that currently owns the library, and the source is `Send`.
+unsafe impl Send for ReaderAccess {}
+
+impl ReaderAccess {
+    pub(crate) fn new<R>(mut reader: R) -> Result<Self, PdfiumError>
+    where
+        R: Read + Seek + Send + 'static,
+    {
+        let len = reader
+            .seek(SeekFrom::End(0))
+            .map_err(|e| PdfiumError::Failed(format!("Reader failed: {}", e)))?;
+        Self::from_source(SeekSource { reader, len })
+    }
+
+    pub(crate) fn from_source<A>(source: A) -> Result<Self, PdfiumError>
+    where
+        A: DataAvailability + 'static,
+    {
+        let len = source.file_len();
+        let len = c_ulong::try_from(len)
+            .map_err(|_| PdfiumError::InvalidArgument(format!("document is {} bytes, too large for this platform", len)))?;
+
+        let state = NonNull::from(Box::leak(Box::new(ReaderState {
+            source: Box::new(source),
+            failure: None,
+            poisoned: false,
+        })));
+        let access = Box::new(FPDF_FILEACCESS {
+            m_FileLen: len,
+            m_GetBlock: Some(get_block),
+            m_Param: state.as_ptr() as *mut c_void,
+        });
+        let avail = Box::new(AvailCallbacks {
+            avail: FX_FILEAVAIL {
+                version: 1,
+                IsDataAvail: Some(is_data_avail),
+            },
+            state,
+        });
+        let hints = Box::new(HintCallbacks {
+            hints: FX_DOWNLOADHINTS {
+                version: 1,
+                AddSegment: Some(add_segment),
+            },
+            state,
+        });
+        Ok(Self { access, avail, hints, state })
+    }
+
+    /// Pointer to pass to `FPDF_LoadCustomDocument` or `FPDFAvail_Create`.
+    pub(crate) fn as_ptr(&mut self) -> *mut FPDF_FILEACCESS {
+        &mut *self.access
+    }
+
+    /// Pointer to pass to `FPDFAvail_Create`.
+    pub(crate) fn avail_ptr(&mut self) -> *mut FX_FILEAVAIL {
+        // Derived from the whole wrapper, which the callback reads beyond
+        // the C struct.
+        &mut *self.avail as *mut AvailCallbacks as *mut FX_FILEAVAIL
+    }
+
+    /// Pointer to pass to the `FPDFAvail_Is*Avail` checks.
+    pub(crate) fn hints_ptr(&mut self) -> *mut FX_DOWNLOADHINTS {
+        &mut *self.hints as *mut HintCallbacks as *mut FX_DOWNLOADHINTS
+    }
+
+    /// Take the reason the last read failed, if any.
+    pub(crate) fn take_failure(&mut self) -> Option<String> {
+        // SAFETY: Not called while PDFium is inside a callback.
+        unsafe { (*self.state.as_ptr()).failure.take() }
+    }
+}
+
+impl Drop for ReaderAccess {
+    fn drop(&mut self) {
+        // SAFETY: Allocated in `from_source`; whatever used it is already
+        // closed or destroyed.
+        drop(unsafe { Box::from_raw(self.state.as_ptr()) });
+    }
+}
+
+unsafe extern "C" fn get_block(param: *mut c_void, position: c_ulong, buf: *mut c_uchar, size: c_ulong) -> c_int {
+    // SAFETY: `param` is the `ReaderState` installed by `ReaderAccess::from_source`.
+    let state = &mut *(param as *mut ReaderState);
+    if size == 0 {
+        return 1;
+    }
+    // SAFETY: PDFium passes a writable buffer of `size` bytes.
+    let buf = std::slice::from_raw_parts_mut(buf, size as usize);
+    // `c_ulong` is only 32 bits on Windows.
+    #[allow(clippy::useless_conversion)]
+    let position = u64::from(position);
+
+    match state.guarded(Err(None), |source| source.read_at(position, buf).map_err(Some)) {
+        Ok(()) => 1,
+        Err(err) => {
+            if let Some(err) = err {
+                state.failure = Some(err.to_string());
+            }
+            0
+        }
+    }
+}
+
+unsafe extern "C" fn is_data_avail(this: *mut FX_FILEAVAIL, offset: usize, size: usize) -> FPDF_BOOL {
+    // SAFETY: `this` is the first field of an `AvailCallbacks`.
+    let state = &mut *(*(this as *mut AvailCallbacks)).state.as_ptr();
+    // A poisoned source claims everything is available so the read that
+    // follows fails, rather than leaving the caller waiting for data forever.
+    let available = state.guarded(true, |source| source.is_data_available(offset as u64, size as u64));
+    available as FPDF_BOOL
+}
+
+unsafe extern "C" fn add_segment(this: *mut FX_DOWNLOADHINTS, offset: usize, size: usize) {
+    // SAFETY: `this` is the first field of a `HintCallbacks`.
+    let state = &mut *(*(this as *mut HintCallbacks)).state.as_ptr();
+    state.guarded((), |source| source.request_range(offset as u64, size as u64));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Cursor;
+    use std::sync::{Arc, Mutex};
+
+    fn read(access: &mut ReaderAccess, position: c_ulong, size: usize) -> Option<Vec<u8>> {
+        let raw = access.as_ptr();
+        let mut buf = vec![0u8; size];
+        let ok = unsafe { ((*raw).m_GetBlock.unwrap())((*raw).m_Param, position, buf.as_mut_ptr(), size as c_ulong) };
+        (ok == 1).then_some(buf)
+    }
+
+    fn is_available(access: &mut ReaderAccess, offset: usize, size: usize) -> bool {
+        let raw = access.avail_ptr();
+        unsafe { ((*raw).IsDataAvail.unwrap())(raw, offset, size) != 0 }
+    }
+
+    #[test]
+    fn reads_blocks_at_offsets() {
+        let mut access = ReaderAccess::new(Cursor::new(b"%PDF-1.7 hello".to_vec())).unwrap();
+        assert_eq!(unsafe { (*access.as_ptr()).m_FileLen }, 14);
+        assert_eq!(read(&mut access, 9, 5).unwrap(), b"hello");
+        assert_eq!(read(&mut access, 0, 4).unwrap(), b"%PDF");
+        assert!(is_available(&mut access, 0, 14));
+        assert!(access.take_failure().is_none());
+    }
+
+    #[test]
+    fn short_read_is_a_failure() {
+        let mut access = ReaderAccess::new(Cursor::new(vec![0u8; 4])).unwrap();
+        assert!(read(&mut access, 2, 8).is_none());
+        assert!(access.take_failure().is_some());
+        // Read errors are not sticky.
+        assert!(read(&mut access, 0, 4).is_some());
+    }
+
+    struct PanickingReader;
+
+    impl Read for PanickingReader {
+        fn read(&mut self, _: &mut [u8]) -> io::Result<usize> {
+            panic!("boom");
+        }
+    }
+
+    impl Seek for PanickingReader {
+        fn seek(&mut self, _: SeekFrom) -> io::Result<u64> {
+            Ok(16)
+        }
+    }
+
+    #[test]
+    fn panic_is_contained_and_poisons_the_reader() {
+        let mut access = ReaderAccess::new(PanickingReader).unwrap();
+        assert!(read(&mut access, 0, 4).is_none());
+        assert_eq!(access.take_failure().as_deref(), Some("reader panicked"));
+        assert!(read(&mut access, 0, 4).is_none());
+        assert!(access.take_failure().is_none());
+        assert!(is_available(&mut access, 0, 4));
+    }
+
+    struct HalfAvailable {
+        requested: Arc<Mutex<Vec<(u64, u64)>>>,
+    }
+
+    impl DataAvailability for HalfAvailable {
+        fn file_len(&self) -> u64 {
+            100
+        }
+
+        fn is_data_available(&mut self, offset: u64, size: u64) -> bool {
+            offset + size <= 50
+        }
+
+        fn read_at(&mut self, _offset: u64, buf: &mut [u8]) -> io::Result<()> {
+            buf.fill(0);
+            Ok(())
+        }
+
+        fn request_range(&mut self, offset: u64, size: u64) {
+            self.requested.lock().unwrap().push((offset, size));
+        }
+    }
+
+    #[test]
+    fn availability_and_hints_reach_the_source() {
+        let requested = Arc::default();
+        let mut access = ReaderAccess::from_source(HalfAvailable {
+            requested: Arc::clone(&requested),
+        })
+        .unwrap();
+        assert!(is_available(&mut access, 10, 10));
+        assert!(!is_available(&mut access, 60, 10));
+
+        let hints = access.hints_ptr();
+        unsafe { ((*hints).AddSegment.unwrap())(hints, 60, 10) };
+        assert_eq!(*requested.lock().unwrap(), vec![(60, 10)]);
+    }
+}
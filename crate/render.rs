@@ -0,0 +1,826 @@
+//! Bitmap rendering options: the pixel layout PDFium renders into and the
+//! caller-supplied buffer that holds it.
+
+use crate::bindings::{FPDF_COLORSCHEME, FPDF_DWORD, FS_MATRIX, FS_RECTF};
+use crate::error::PdfiumError;
+use std::ops::{BitOr, BitOrAssign};
+use std::str::FromStr;
+
+// FPDFBitmap_* formats (fpdfview.h)
+const FPDFBITMAP_GRAY: i32 = 1;
+const FPDFBITMAP_BGR: i32 = 2;
+const FPDFBITMAP_BGRX: i32 = 3;
+const FPDFBITMAP_BGRA: i32 = 4;
+
+/// `FPDF_RenderPageBitmap` flag: write 32-bit pixels as RGBA rather than BGRA.
+pub(crate) const FPDF_REVERSE_BYTE_ORDER: i32 = 0x10;
+
+/// Byte layout of rendered pixels.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum PixelFormat {
+    /// One grey byte per pixel.
+    Gray8,
+    /// Three bytes per pixel: blue, green, red.
+    Bgr,
+    /// Four bytes per pixel: blue, green, red, unused.
+    Bgrx,
+    /// Four bytes per pixel: blue, green, red, alpha. PDFium's native layout.
+    Bgra,
+    /// Four bytes per pixel: red, green, blue, alpha.
+    #[default]
+    Rgba,
+}
+
+impl PixelFormat {
+    pub fn bytes_per_pixel(self) -> usize {
+        match self {
+            PixelFormat::Gray8 => 1,
+            PixelFormat::Bgr => 3,
+            PixelFormat::Bgrx | PixelFormat::Bgra | PixelFormat::Rgba => 4,
+        }
+    }
+
+    pub fn has_alpha(self) -> bool {
+        matches!(self, PixelFormat::Bgra | PixelFormat::Rgba)
+    }
+
+    /// Smallest stride that holds a row of `width` pixels.
+    pub fn min_stride(self, width: i32) -> usize {
+        width.max(0) as usize * self.bytes_per_pixel()
+    }
+
+    /// The layout of an existing bitmap in `FPDFBitmap_*` format `format`.
+    pub(crate) fn from_bitmap_format(format: i32) -> Option<Self> {
+        match format {
+            FPDFBITMAP_GRAY => Some(PixelFormat::Gray8),
+            FPDFBITMAP_BGR => Some(PixelFormat::Bgr),
+            FPDFBITMAP_BGRX => Some(PixelFormat::Bgrx),
+            FPDFBITMAP_BGRA => Some(PixelFormat::Bgra),
+            _ => None,
+        }
+    }
+
+    /// The `FPDFBitmap_*` format to create.
+    pub(crate) fn bitmap_format(self) -> i32 {
+        match self {
+            PixelFormat::Gray8 => FPDFBITMAP_GRAY,
+            PixelFormat::Bgr => FPDFBITMAP_BGR,
+            PixelFormat::Bgrx => FPDFBITMAP_BGRX,
+            PixelFormat::Bgra | PixelFormat::Rgba => FPDFBITMAP_BGRA,
+        }
+    }
+
+    /// Render `flags` adjusted so PDFium writes this layout; the byte order
+    /// is chosen by the format, never by the caller's flags.
+    pub(crate) fn render_flags(self, flags: i32) -> i32 {
+        let flags = flags & !FPDF_REVERSE_BYTE_ORDER;
+        match self {
+            PixelFormat::Rgba => flags | FPDF_REVERSE_BYTE_ORDER,
+            _ => flags,
+        }
+    }
+
+    /// An 0xAARRGGBB colour in the form `FPDFBitmap_FillRect` needs to
+    /// produce it in this layout. The fill ignores `FPDF_REVERSE_BYTE_ORDER`,
+    /// so RGBA swaps red and blue up front.
+    pub(crate) fn fill_colour(self, argb: u32) -> u32 {
+        match self {
+            PixelFormat::Rgba => (argb & 0xFF00_FF00) | ((argb >> 16) & 0xFF) | ((argb & 0xFF) << 16),
+            _ => argb,
+        }
+    }
+}
+
+impl FromStr for PixelFormat {
+    type Err = PdfiumError;
+
+    /// Parse the lower-case name used by the JavaScript bindings.
+    fn from_str(name: &str) -> Result<Self, Self::Err> {
+        match name {
+            "gray8" => Ok(PixelFormat::Gray8),
+            "bgr" => Ok(PixelFormat::Bgr),
+            "bgrx" => Ok(PixelFormat::Bgrx),
+            "bgra" => Ok(PixelFormat::Bgra),
+            "rgba" => Ok(PixelFormat::Rgba),
+            other => Err(PdfiumError::InvalidArgument(format!("pixel format: {}", other))),
+        }
+    }
+}
+
+/// How colour relates to alpha in formats that have it.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum AlphaMode {
+    /// Colour is independent of alpha, as PDFium renders it.
+    #[default]
+    Straight,
+    /// Colour is pre-multiplied by alpha, as most compositors expect.
+    Premultiplied,
+}
+
+/// `FPDF_RenderPageBitmap` flags, combined with `|`.
+///
+/// ```no_run
+/// # use pdfium::RenderFlags;
+/// let flags = RenderFlags::ANNOTATIONS | RenderFlags::LCD_TEXT;
+/// assert!(flags.contains(RenderFlags::ANNOTATIONS));
+/// assert_eq!(flags.bits(), 0x03);
+/// ```
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct RenderFlags(i32);
+
+impl RenderFlags {
+    /// `FPDF_ANNOT`: draw annotations.
+    pub const ANNOTATIONS: Self = Self(0x01);
+    /// `FPDF_LCD_TEXT`: sub-pixel text for LCD screens.
+    pub const LCD_TEXT: Self = Self(0x02);
+    /// `FPDF_NO_NATIVETEXT`: never draw text with the platform's rasteriser.
+    pub const NO_NATIVE_TEXT: Self = Self(0x04);
+    /// `FPDF_GRAYSCALE`: render in shades of grey.
+    pub const GRAYSCALE: Self = Self(0x08);
+    /// `FPDF_REVERSE_BYTE_ORDER`: write RGBA rather than BGRA. Renders
+    /// through `RenderOptions` take the byte order from the pixel format
+    /// and ignore this flag.
+    pub const REVERSE_BYTE_ORDER: Self = Self(FPDF_REVERSE_BYTE_ORDER);
+    /// `FPDF_CONVERT_FILL_TO_STROKE`: outline filled paths instead, in
+    /// high-contrast mode.
+    pub const CONVERT_FILL_TO_STROKE: Self = Self(0x20);
+    /// `FPDF_RENDER_FORCEHALFTONE`: always halftone when downscaling images.
+    pub const FORCE_HALFTONE: Self = Self(0x400);
+    /// `FPDF_PRINTING`: render for print, honouring annotations' print flags.
+    pub const PRINTING: Self = Self(0x800);
+    /// `FPDF_RENDER_NO_SMOOTHTEXT`: no anti-aliasing of text.
+    pub const NO_SMOOTH_TEXT: Self = Self(0x1000);
+    /// `FPDF_RENDER_NO_SMOOTHIMAGE`: no anti-aliasing of images.
+    pub const NO_SMOOTH_IMAGE: Self = Self(0x2000);
+    /// `FPDF_RENDER_NO_SMOOTHPATH`: no anti-aliasing of paths.
+    pub const NO_SMOOTH_PATH: Self = Self(0x4000);
+
+    pub const fn empty() -> Self {
+        Self(0)
+    }
+
+    /// Wrap raw flags. Bits without a constant here are kept and passed to
+    /// PDFium as they are.
+    pub const fn from_bits(bits: i32) -> Self {
+        Self(bits)
+    }
+
+    pub const fn bits(self) -> i32 {
+        self.0
+    }
+
+    /// Whether every flag in `other` is set.
+    pub const fn contains(self, other: Self) -> bool {
+        self.0 & other.0 == other.0
+    }
+
+    /// `self` with the flags in `other` set or cleared.
+    pub const fn set(self, other: Self, on: bool) -> Self {
+        if on {
+            Self(self.0 | other.0)
+        } else {
+            Self(self.0 & !other.0)
+        }
+    }
+}
+
+impl BitOr for RenderFlags {
+    type Output = Self;
+
+    fn bitor(self, other: Self) -> Self {
+        Self(self.0 | other.0)
+    }
+}
+
+impl BitOrAssign for RenderFlags {
+    fn bitor_assign(&mut self, other: Self) {
+        self.0 |= other.0;
+    }
+}
+
+impl From<RenderFlags> for i32 {
+    fn from(flags: RenderFlags) -> i32 {
+        flags.0
+    }
+}
+
+/// How `render_page_with`, `render_page_into` and `render_region` render a
+/// page.
+///
+/// ```no_run
+/// # use pdfium::{AlphaMode, PdfiumLibrary, PixelFormat, RenderOptions};
+/// # let lib = PdfiumLibrary::load("libpdfium.so")?;
+/// # lib.init_library();
+/// # let doc = lib.open_document_from_path("input.pdf", None)?;
+/// let page = doc.load_page(0)?;
+/// let options = RenderOptions::new()
+///     .format(PixelFormat::Bgra)
+///     .alpha(AlphaMode::Premultiplied);
+/// // Reuse one buffer for every page.
+/// let stride = PixelFormat::Bgra.min_stride(200);
+/// let mut buffer = vec![0u8; stride * 300];
+/// page.render_into(200, 300, &options, &mut buffer, stride)?;
+/// # Ok::<(), pdfium::PdfiumError>(())
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RenderOptions {
+    format: PixelFormat,
+    alpha: AlphaMode,
+    rotation: i32,
+    flags: i32,
+    background: u32,
+    draw_forms: bool,
+    color_scheme: Option<ColorScheme>,
+}
+
+impl Default for RenderOptions {
+    fn default() -> Self {
+        Self {
+            format: PixelFormat::default(),
+            alpha: AlphaMode::default(),
+            rotation: 0,
+            flags: 0,
+            background: 0xFFFF_FFFF,
+            draw_forms: false,
+            color_scheme: None,
+        }
+    }
+}
+
+impl RenderOptions {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn format(mut self, format: PixelFormat) -> Self {
+        self.format = format;
+        self
+    }
+
+    /// Only affects formats with an alpha channel.
+    pub fn alpha(mut self, alpha: AlphaMode) -> Self {
+        self.alpha = alpha;
+        self
+    }
+
+    /// Clockwise quarter turns, 0–3. `render_region` takes its rotation
+    /// from the matrix instead.
+    pub fn rotation(mut self, rotation: i32) -> Self {
+        self.rotation = rotation;
+        self
+    }
+
+    /// `FPDF_RenderPageBitmap` flags, e.g. `FPDF_ANNOT` (0x01).
+    pub fn flags(mut self, flags: i32) -> Self {
+        self.flags = flags;
+        self
+    }
+
+    /// `flags` as a typed set; the same as `flags(flags.bits())`.
+    pub fn render_flags(self, flags: RenderFlags) -> Self {
+        self.flags(flags.bits())
+    }
+
+    /// 0xAARRGGBB colour filled before rendering. Defaults to opaque white.
+    pub fn background(mut self, argb: u32) -> Self {
+        self.background = argb;
+        self
+    }
+
+    /// Draw interactive form fields over the page with `FPDF_FFLDraw`,
+    /// creating the document's form-fill environment on first use. Not
+    /// supported by `render_region`.
+    pub fn draw_forms(mut self, draw: bool) -> Self {
+        self.draw_forms = draw;
+        self
+    }
+
+    /// Draw paths and text in `scheme`'s colours instead of their own.
+    /// Renders through the progressive renderer; not supported by
+    /// `render_region`.
+    pub fn color_scheme(mut self, scheme: ColorScheme) -> Self {
+        self.color_scheme = Some(scheme);
+        self
+    }
+
+    pub fn get_format(&self) -> PixelFormat {
+        self.format
+    }
+
+    pub fn get_alpha(&self) -> AlphaMode {
+        self.alpha
+    }
+
+    pub fn get_rotation(&self) -> i32 {
+        self.rotation
+    }
+
+    pub fn get_flags(&self) -> i32 {
+        self.flags
+    }
+
+    pub fn get_render_flags(&self) -> RenderFlags {
+        RenderFlags::from_bits(self.flags)
+    }
+
+    pub fn get_background(&self) -> u32 {
+        self.background
+    }
+
+    pub fn get_draw_forms(&self) -> bool {
+        self.draw_forms
+    }
+
+    pub fn get_color_scheme(&self) -> Option<ColorScheme> {
+        self.color_scheme
+    }
+}
+
+/// Colours, as 0xAARRGGBB, that replace those of the page's paths and text,
+/// e.g. for a dark theme. Images keep their own colours.
+///
+/// ```no_run
+/// # use pdfium::{ColorScheme, PdfiumLibrary, RenderOptions};
+/// # let lib = PdfiumLibrary::load("libpdfium.so")?;
+/// # lib.init_library();
+/// # let doc = lib.open_document_from_path("input.pdf", None)?;
+/// let page = doc.load_page(0)?;
+/// let dark = ColorScheme::new(0xFF30_3030, 0xFFC0_C0C0, 0xFFE0_E0E0, 0xFFE0_E0E0);
+/// let options = RenderOptions::new().background(0xFF12_1212).color_scheme(dark);
+/// let pixels = page.render_with(800, 1000, &options)?;
+/// # Ok::<(), pdfium::PdfiumError>(())
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ColorScheme {
+    pub path_fill: u32,
+    pub path_stroke: u32,
+    pub text_fill: u32,
+    pub text_stroke: u32,
+}
+
+impl ColorScheme {
+    pub const fn new(path_fill: u32, path_stroke: u32, text_fill: u32, text_stroke: u32) -> Self {
+        Self {
+            path_fill,
+            path_stroke,
+            text_fill,
+            text_stroke,
+        }
+    }
+
+    pub(crate) fn to_raw(self) -> FPDF_COLORSCHEME {
+        FPDF_COLORSCHEME {
+            path_fill_color: self.path_fill as FPDF_DWORD,
+            path_stroke_color: self.path_stroke as FPDF_DWORD,
+            text_fill_color: self.text_fill as FPDF_DWORD,
+            text_stroke_color: self.text_stroke as FPDF_DWORD,
+        }
+    }
+}
+
+/// Affine transform from page space to bitmap pixels, as used by
+/// `render_region`. Page space here is the unrotated page in points with the
+/// origin at the top-left, so `Matrix::IDENTITY` renders at 72 DPI.
+///
+/// A point (x, y) maps to (ax + cy + e, bx + dy + f).
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Matrix {
+    pub a: f32,
+    pub b: f32,
+    pub c: f32,
+    pub d: f32,
+    pub e: f32,
+    pub f: f32,
+}
+
+impl Matrix {
+    pub const IDENTITY: Matrix = Matrix::new(1.0, 0.0, 0.0, 1.0, 0.0, 0.0);
+
+    pub const fn new(a: f32, b: f32, c: f32, d: f32, e: f32, f: f32) -> Self {
+        Self { a, b, c, d, e, f }
+    }
+
+    pub const fn scale(sx: f32, sy: f32) -> Self {
+        Self::new(sx, 0.0, 0.0, sy, 0.0, 0.0)
+    }
+
+    pub const fn translate(tx: f32, ty: f32) -> Self {
+        Self::new(1.0, 0.0, 0.0, 1.0, tx, ty)
+    }
+
+    /// Apply `self`, then `next`.
+    pub fn then(self, next: Matrix) -> Self {
+        Self {
+            a: self.a * next.a + self.b * next.c,
+            b: self.a * next.b + self.b * next.d,
+            c: self.c * next.a + self.d * next.c,
+            d: self.c * next.b + self.d * next.d,
+            e: self.e * next.a + self.f * next.c + next.e,
+            f: self.e * next.b + self.f * next.d + next.f,
+        }
+    }
+
+    /// The transform for a viewport whose top-left corner sits at (`x`, `y`)
+    /// pixels within the page rendered at `zoom` (1.0 = 72 DPI).
+    pub fn viewport(zoom: f32, x: f32, y: f32) -> Self {
+        Self::scale(zoom, zoom).then(Self::translate(-x, -y))
+    }
+
+    /// Turn a `width` x `height` page clockwise by `turns` quarter turns,
+    /// keeping it at the origin; odd turns swap its width and height.
+    pub fn quarter_turns(turns: i32, width: f32, height: f32) -> Self {
+        match turns.rem_euclid(4) {
+            0 => Self::IDENTITY,
+            1 => Self::new(0.0, 1.0, -1.0, 0.0, height, 0.0),
+            2 => Self::new(-1.0, 0.0, 0.0, -1.0, width, height),
+            _ => Self::new(0.0, -1.0, 1.0, 0.0, 0.0, width),
+        }
+    }
+
+    /// PDFium rejects transforms it cannot invert.
+    pub fn is_invertible(&self) -> bool {
+        let det = self.a * self.d - self.b * self.c;
+        det.is_finite() && det != 0.0 && self.e.is_finite() && self.f.is_finite()
+    }
+
+    pub(crate) fn to_raw(self) -> FS_MATRIX {
+        FS_MATRIX {
+            a: self.a,
+            b: self.b,
+            c: self.c,
+            d: self.d,
+            e: self.e,
+            f: self.f,
+        }
+    }
+}
+
+impl Default for Matrix {
+    fn default() -> Self {
+        Self::IDENTITY
+    }
+}
+
+/// Clip rectangle in bitmap pixels; nothing is drawn outside it.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ClipRect {
+    pub left: f32,
+    pub top: f32,
+    pub right: f32,
+    pub bottom: f32,
+}
+
+impl ClipRect {
+    pub const fn new(left: f32, top: f32, right: f32, bottom: f32) -> Self {
+        Self { left, top, right, bottom }
+    }
+
+    /// The whole of a `width` x `height` bitmap.
+    pub fn bitmap(width: i32, height: i32) -> Self {
+        Self::new(0.0, 0.0, width as f32, height as f32)
+    }
+
+    pub(crate) fn to_raw(self) -> FS_RECTF {
+        FS_RECTF {
+            left: self.left,
+            top: self.top,
+            right: self.right,
+            bottom: self.bottom,
+        }
+    }
+}
+
+/// A page boundary box, as defined by the PDF specification.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum PageBox {
+    Media,
+    /// The visible area: what a viewer shows and `render_page` draws.
+    Crop,
+    Bleed,
+    Trim,
+    Art,
+}
+
+impl PageBox {
+    /// The `box_type` taken by `get_page_box` and `set_page_box`.
+    pub fn box_type(self) -> i32 {
+        match self {
+            PageBox::Media => 0,
+            PageBox::Crop => 1,
+            PageBox::Bleed => 2,
+            PageBox::Trim => 3,
+            PageBox::Art => 4,
+        }
+    }
+
+    pub fn from_box_type(box_type: i32) -> Option<Self> {
+        match box_type {
+            0 => Some(PageBox::Media),
+            1 => Some(PageBox::Crop),
+            2 => Some(PageBox::Bleed),
+            3 => Some(PageBox::Trim),
+            4 => Some(PageBox::Art),
+            _ => None,
+        }
+    }
+}
+
+/// Where the page-space rect `[left, bottom, right, top]` lands on the page
+/// as displayed, as `[left, top, right, bottom]` in points from its top-left
+/// corner. `origin` is the page-space point at that corner and `rotation`
+/// the page's /Rotate in quarter turns.
+pub(crate) fn display_rect(rect: [f32; 4], origin: (f32, f32), rotation: i32) -> [f32; 4] {
+    let (x0, y0) = origin;
+    let to_display = |x: f32, y: f32| match rotation.rem_euclid(4) {
+        0 => (x - x0, y0 - y),
+        1 => (y - y0, x - x0),
+        2 => (x0 - x, y - y0),
+        _ => (y0 - y, x0 - x),
+    };
+    let [left, bottom, right, top] = rect;
+    let (ax, ay) = to_display(left, bottom);
+    let (bx, by) = to_display(right, top);
+    [ax.min(bx), ay.min(by), ax.max(bx), ay.max(by)]
+}
+
+/// Check that `len` bytes with rows `stride` apart can hold a `width` x
+/// `height` bitmap in `format`, returning the stride as PDFium takes it.
+pub(crate) fn check_buffer(
+    format: PixelFormat,
+    width: i32,
+    height: i32,
+    stride: usize,
+    len: usize,
+) -> Result<i32, PdfiumError> {
+    if width <= 0 || height <= 0 {
+        return Err(PdfiumError::InvalidArgument(format!("bitmap size: {}x{}", width, height)));
+    }
+    let min_stride = format.min_stride(width);
+    if stride < min_stride {
+        return Err(PdfiumError::InvalidArgument(format!(
+            "stride {} is less than {} bytes per row",
+            stride, min_stride
+        )));
+    }
+    let stride_raw =
+        i32::try_from(stride).map_err(|_| PdfiumError::InvalidArgument(format!("stride: {}", stride)))?;
+    let needed = stride
+        .checked_mul(height as usize)
+        .ok_or_else(|| PdfiumError::InvalidArgument(format!("bitmap size: {}x{}", width, height)))?;
+    if len < needed {
+        return Err(PdfiumError::InvalidArgument(format!(
+            "buffer is {} bytes, {} needed",
+            len, needed
+        )));
+    }
+    Ok(stride_raw)
+}
+
+/// Multiply the colour channels of 4-byte pixels by their alpha (byte 3).
+pub(crate) fn premultiply(buffer: &mut [u8], width: i32, height: i32, stride: usize) {
+    let row_len = width as usize * 4;
+    for row in buffer.chunks_mut(stride).take(height as usize) {
+        for pixel in row[..row_len].chunks_exact_mut(4) {
+            let alpha = pixel[3] as u32;
+            if alpha == 255 {
+                continue;
+            }
+            for channel in &mut pixel[..3] {
+                *channel = ((*channel as u32 * alpha + 127) / 255) as u8;
+            }
+        }
+    }
+}
+
+/// Pixels rendered at a size the renderer chose, tightly packed in `format`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RenderedImage {
+    pub width: u32,
+    pub height: u32,
+    pub format: PixelFormat,
+    pub data: Vec<u8>,
+}
+
+/// Draw tightly packed straight RGBA over `options`' background and lay it
+/// out, tightly packed, in `options`' format and alpha mode.
+pub(crate) fn from_rgba(rgba: &[u8], options: &RenderOptions) -> Vec<u8> {
+    let format = options.get_format();
+    let bg = options.get_background();
+    let (bg_a, bg_r, bg_g, bg_b) = (bg >> 24, (bg >> 16) & 0xFF, (bg >> 8) & 0xFF, bg & 0xFF);
+
+    let mut out = Vec::with_capacity(rgba.len() / 4 * format.bytes_per_pixel());
+    for px in rgba.chunks_exact(4) {
+        // Source-over in straight alpha.
+        let a = px[3] as u32;
+        let under = bg_a * (255 - a);
+        let out_a = a + (under + 127) / 255;
+        let blend = |c: u8, bg_c: u32| {
+            if out_a == 0 {
+                0
+            } else {
+                ((c as u32 * a * 255 + bg_c * under + out_a * 255 / 2) / (out_a * 255)).min(255) as u8
+            }
+        };
+        let (r, g, b, a) = (blend(px[0], bg_r), blend(px[1], bg_g), blend(px[2], bg_b), out_a as u8);
+        match format {
+            PixelFormat::Gray8 => out.push(((r as u32 * 299 + g as u32 * 587 + b as u32 * 114 + 500) / 1000) as u8),
+            PixelFormat::Bgr => out.extend_from_slice(&[b, g, r]),
+            PixelFormat::Bgrx => out.extend_from_slice(&[b, g, r, 0xFF]),
+            PixelFormat::Bgra => out.extend_from_slice(&[b, g, r, a]),
+            PixelFormat::Rgba => out.extend_from_slice(&[r, g, b, a]),
+        }
+    }
+
+    if format.has_alpha() && options.get_alpha() == AlphaMode::Premultiplied {
+        let width = (rgba.len() / 4) as i32;
+        premultiply(&mut out, width, 1, width as usize * 4);
+    }
+    out
+}
+
+/// Copy a `width` x `height` image in `format`, rows `stride` apart, into
+/// tightly packed RGBA. Formats without alpha become opaque.
+pub(crate) fn to_rgba(buffer: &[u8], width: usize, height: usize, stride: usize, format: PixelFormat) -> Vec<u8> {
+    let bpp = format.bytes_per_pixel();
+    let mut rgba = Vec::with_capacity(width * height * 4);
+    for row in buffer.chunks(stride).take(height) {
+        for px in row[..width * bpp].chunks_exact(bpp) {
+            rgba.extend_from_slice(&match format {
+                PixelFormat::Gray8 => [px[0], px[0], px[0], 255],
+                PixelFormat::Bgr | PixelFormat::Bgrx => [px[2], px[1], px[0], 255],
+                PixelFormat::Bgra => [px[2], px[1], px[0], px[3]],
+                PixelFormat::Rgba => [px[0], px[1], px[2], px[3]],
+            });
+        }
+    }
+    rgba
+}
+
+/// Shrink a tightly packed `width` x `height` RGBA image to `new_width` x
+/// `new_height`, averaging the source pixels each output pixel covers.
+pub(crate) fn downscale_rgba(
+    rgba: &[u8],
+    width: usize,
+    height: usize,
+    new_width: usize,
+    new_height: usize,
+) -> Vec<u8> {
+    let span = |i: usize, from: usize, to: usize| (i * from / to, ((i + 1) * from).div_ceil(to));
+    let mut out = Vec::with_capacity(new_width * new_height * 4);
+    for y in 0..new_height {
+        let (top, bottom) = span(y, height, new_height);
+        for x in 0..new_width {
+            let (left, right) = span(x, width, new_width);
+            let mut sum = [0u32; 4];
+            for row in top..bottom {
+                for px in rgba[(row * width + left) * 4..(row * width + right) * 4].chunks_exact(4) {
+                    sum.iter_mut().zip(px).for_each(|(total, &c)| *total += c as u32);
+                }
+            }
+            let count = ((bottom - top) * (right - left)) as u32;
+            out.extend(sum.map(|total| ((total + count / 2) / count) as u8));
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rgba_is_drawn_over_the_background() {
+        // Opaque red, half-transparent blue, fully transparent.
+        let rgba = [255, 0, 0, 255, 0, 0, 255, 128, 9, 9, 9, 0];
+
+        let white = RenderOptions::new().format(PixelFormat::Bgr);
+        assert_eq!(from_rgba(&rgba, &white), [0, 0, 255, 255, 127, 127, 255, 255, 255]);
+
+        let clear = RenderOptions::new().background(0).alpha(AlphaMode::Premultiplied);
+        assert_eq!(from_rgba(&rgba, &clear), [255, 0, 0, 255, 0, 0, 128, 128, 0, 0, 0, 0]);
+
+        let gray = RenderOptions::new().format(PixelFormat::Gray8);
+        assert_eq!(from_rgba(&rgba[..4], &gray), [76]);
+    }
+
+    #[test]
+    fn bitmaps_convert_to_rgba() {
+        assert_eq!(PixelFormat::from_bitmap_format(FPDFBITMAP_BGRX), Some(PixelFormat::Bgrx));
+        assert_eq!(PixelFormat::from_bitmap_format(0), None);
+
+        // Two BGR pixels per row, padded to a stride of 8.
+        let bgr = [1, 2, 3, 4, 5, 6, 0, 0, 7, 8, 9, 10, 11, 12, 0, 0];
+        assert_eq!(
+            to_rgba(&bgr, 2, 2, 8, PixelFormat::Bgr),
+            [3, 2, 1, 255, 6, 5, 4, 255, 9, 8, 7, 255, 12, 11, 10, 255]
+        );
+        assert_eq!(to_rgba(&[9, 0, 0, 0], 1, 1, 4, PixelFormat::Gray8), [9, 9, 9, 255]);
+    }
+
+    #[test]
+    fn rgba_downscales_by_averaging() {
+        // 2x2 down to 1x1.
+        let square = [0, 0, 0, 255, 255, 255, 255, 255, 100, 0, 0, 255, 0, 100, 0, 255];
+        assert_eq!(downscale_rgba(&square, 2, 2, 1, 1), [89, 89, 64, 255]);
+
+        // 3x1 down to 2x1: the middle pixel counts towards both.
+        let row = [0, 0, 0, 0, 90, 90, 90, 90, 180, 180, 180, 180];
+        assert_eq!(downscale_rgba(&row, 3, 1, 2, 1), [45, 45, 45, 45, 135, 135, 135, 135]);
+    }
+
+    #[test]
+    fn formats_map_to_bitmap_formats_and_byte_order() {
+        assert_eq!(PixelFormat::Gray8.bitmap_format(), FPDFBITMAP_GRAY);
+        assert_eq!(PixelFormat::Rgba.bitmap_format(), FPDFBITMAP_BGRA);
+        assert_eq!(PixelFormat::Rgba.render_flags(0x01), 0x01 | FPDF_REVERSE_BYTE_ORDER);
+        assert_eq!(PixelFormat::Bgra.render_flags(0x01 | FPDF_REVERSE_BYTE_ORDER), 0x01);
+        assert_eq!(PixelFormat::Rgba.fill_colour(0x80112233), 0x80332211);
+        assert_eq!(PixelFormat::Bgr.fill_colour(0x80112233), 0x80112233);
+        assert_eq!("bgrx".parse::<PixelFormat>(), Ok(PixelFormat::Bgrx));
+        assert!("argb".parse::<PixelFormat>().is_err());
+    }
+
+    #[test]
+    fn buffers_are_checked_against_the_stride() {
+        assert_eq!(check_buffer(PixelFormat::Bgr, 10, 2, 32, 64), Ok(32));
+        // Stride shorter than a row.
+        assert!(check_buffer(PixelFormat::Bgr, 11, 2, 32, 64).is_err());
+        // Buffer shorter than `stride * height`.
+        assert!(check_buffer(PixelFormat::Rgba, 8, 2, 32, 63).is_err());
+        assert!(check_buffer(PixelFormat::Gray8, 0, 2, 32, 64).is_err());
+    }
+
+    #[test]
+    fn matrices_compose_in_application_order() {
+        let m = Matrix::viewport(8.0, 100.0, 50.0);
+        // Page point (20, 10) lands at (20 * 8 - 100, 10 * 8 - 50).
+        assert_eq!((m.a * 20.0 + m.c * 10.0 + m.e, m.b * 20.0 + m.d * 10.0 + m.f), (60.0, 30.0));
+
+        // A quarter turn: (x, y) -> (-y, x).
+        let rotate = Matrix::new(0.0, 1.0, -1.0, 0.0, 0.0, 0.0);
+        assert_eq!(Matrix::translate(1.0, 2.0).then(rotate), Matrix::new(0.0, 1.0, -1.0, 0.0, -2.0, 1.0));
+        assert_eq!(rotate.then(Matrix::IDENTITY), rotate);
+
+        // The top-left corner of a 10x20 page moves to the top-right after a
+        // clockwise quarter turn, and to the bottom-left after three.
+        let corner = |m: Matrix| (m.a * 0.0 + m.c * 0.0 + m.e, m.b * 0.0 + m.d * 0.0 + m.f);
+        assert_eq!(corner(Matrix::quarter_turns(1, 10.0, 20.0)), (20.0, 0.0));
+        assert_eq!(corner(Matrix::quarter_turns(2, 10.0, 20.0)), (10.0, 20.0));
+        assert_eq!(corner(Matrix::quarter_turns(-1, 10.0, 20.0)), (0.0, 10.0));
+
+        assert!(Matrix::IDENTITY.is_invertible());
+        assert!(!Matrix::scale(0.0, 1.0).is_invertible());
+        assert!(!Matrix::translate(f32::NAN, 0.0).is_invertible());
+    }
+
+    #[test]
+    fn premultiply_scales_colour_by_alpha_and_skips_padding() {
+        // Two rows of one pixel, with two bytes of padding per row.
+        let mut buffer = [200, 100, 50, 128, 9, 9, 255, 255, 255, 0, 9, 9];
+        premultiply(&mut buffer, 1, 2, 6);
+        assert_eq!(buffer, [100, 50, 25, 128, 9, 9, 0, 0, 0, 0, 9, 9]);
+    }
+
+    #[test]
+    fn page_boxes_land_where_the_page_is_displayed() {
+        let letter = [0.0, 0.0, 612.0, 792.0];
+        assert_eq!(display_rect(letter, (0.0, 792.0), 0), [0.0, 0.0, 612.0, 792.0]);
+        // With a 36pt margin cropped off, the media box reaches past the page.
+        assert_eq!(display_rect(letter, (36.0, 756.0), 0), [-36.0, -36.0, 576.0, 756.0]);
+
+        // The origin is whichever page corner /Rotate turns to the top-left.
+        let rect = [10.0, 20.0, 110.0, 70.0];
+        assert_eq!(display_rect(rect, (0.0, 792.0), 0), [10.0, 722.0, 110.0, 772.0]);
+        assert_eq!(display_rect(rect, (0.0, 0.0), 1), [20.0, 10.0, 70.0, 110.0]);
+        assert_eq!(display_rect(rect, (612.0, 0.0), 2), [502.0, 20.0, 602.0, 70.0]);
+        assert_eq!(display_rect(rect, (612.0, 792.0), 3), [722.0, 502.0, 772.0, 602.0]);
+    }
+
+    #[test]
+    fn page_box_types_round_trip() {
+        for box_type in 0..5 {
+            assert_eq!(PageBox::from_box_type(box_type).unwrap().box_type(), box_type);
+        }
+        assert_eq!(PageBox::from_box_type(5), None);
+    }
+
+    #[test]
+    fn render_flags_combine_into_pdfium_bits() {
+        let mut flags = RenderFlags::ANNOTATIONS | RenderFlags::PRINTING;
+        flags |= RenderFlags::NO_SMOOTH_TEXT;
+        assert_eq!(flags.bits(), 0x01 | 0x800 | 0x1000);
+        assert!(flags.contains(RenderFlags::ANNOTATIONS | RenderFlags::PRINTING));
+        assert!(!flags.contains(RenderFlags::GRAYSCALE));
+        assert_eq!(flags.set(RenderFlags::PRINTING, false).bits(), 0x01 | 0x1000);
+        let lcd = RenderFlags::ANNOTATIONS | RenderFlags::LCD_TEXT;
+        assert!(lcd.contains(RenderFlags::ANNOTATIONS));
+        assert_eq!(lcd.bits(), 0x03);
+
+        let options = RenderOptions::new().render_flags(flags);
+        assert_eq!(options.get_flags(), flags.bits());
+        assert_eq!(options.get_render_flags(), flags);
+        // The pixel format, not the caller, picks the byte order.
+        let reversed = RenderFlags::REVERSE_BYTE_ORDER.bits();
+        assert_eq!(PixelFormat::Bgra.render_flags(reversed), 0);
+    }
+}
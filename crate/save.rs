@@ -0,0 +1,172 @@
+//! Options for saving documents: the `FPDF_SaveAsCopy` flags and the
+//! `FPDF_SaveWithVersion` file version.
+
+use crate::error::PdfiumError;
+
+// FPDF_SaveAsCopy flags (fpdf_save.h)
+const FPDF_SAVE_STANDARD: u32 = 0;
+const FPDF_INCREMENTAL: u32 = 1;
+const FPDF_NO_INCREMENTAL: u32 = 2;
+const FPDF_REMOVE_SECURITY: u32 = 3;
+
+/// How a document is written out.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum SaveMode {
+    /// Write a complete file, copying unchanged objects from the original.
+    #[default]
+    Standard,
+    /// Append only the changes to the original bytes. Existing content, and
+    /// any signatures over it, stay valid.
+    Incremental,
+    /// Write a complete file, re-serialising every object.
+    FullRewrite,
+    /// Write a complete file without the original encryption.
+    RemoveSecurity,
+}
+
+impl SaveMode {
+    pub(crate) fn to_raw(self) -> u32 {
+        match self {
+            SaveMode::Standard => FPDF_SAVE_STANDARD,
+            SaveMode::Incremental => FPDF_INCREMENTAL,
+            SaveMode::FullRewrite => FPDF_NO_INCREMENTAL,
+            SaveMode::RemoveSecurity => FPDF_REMOVE_SECURITY,
+        }
+    }
+
+    /// Decode an `FPDF_SaveAsCopy` flags value.
+    pub fn from_raw(flags: u32) -> Result<Self, PdfiumError> {
+        match flags {
+            FPDF_SAVE_STANDARD => Ok(SaveMode::Standard),
+            FPDF_INCREMENTAL => Ok(SaveMode::Incremental),
+            FPDF_NO_INCREMENTAL => Ok(SaveMode::FullRewrite),
+            FPDF_REMOVE_SECURITY => Ok(SaveMode::RemoveSecurity),
+            other => Err(PdfiumError::InvalidArgument(format!("save flags: {}", other))),
+        }
+    }
+}
+
+/// PDF file version written in the header.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum PdfVersion {
+    V1_0,
+    V1_1,
+    V1_2,
+    V1_3,
+    V1_4,
+    V1_5,
+    V1_6,
+    V1_7,
+    V2_0,
+}
+
+impl PdfVersion {
+    /// PDFium's encoding: major * 10 + minor, e.g. 17 for PDF 1.7.
+    pub(crate) fn to_raw(self) -> i32 {
+        match self {
+            PdfVersion::V1_0 => 10,
+            PdfVersion::V1_1 => 11,
+            PdfVersion::V1_2 => 12,
+            PdfVersion::V1_3 => 13,
+            PdfVersion::V1_4 => 14,
+            PdfVersion::V1_5 => 15,
+            PdfVersion::V1_6 => 16,
+            PdfVersion::V1_7 => 17,
+            PdfVersion::V2_0 => 20,
+        }
+    }
+
+    /// Decode a version in PDFium's encoding, e.g. 17 for PDF 1.7.
+    pub fn from_raw(version: i32) -> Result<Self, PdfiumError> {
+        match version {
+            10 => Ok(PdfVersion::V1_0),
+            11 => Ok(PdfVersion::V1_1),
+            12 => Ok(PdfVersion::V1_2),
+            13 => Ok(PdfVersion::V1_3),
+            14 => Ok(PdfVersion::V1_4),
+            15 => Ok(PdfVersion::V1_5),
+            16 => Ok(PdfVersion::V1_6),
+            17 => Ok(PdfVersion::V1_7),
+            20 => Ok(PdfVersion::V2_0),
+            other => Err(PdfiumError::InvalidArgument(format!("PDF version: {}", other))),
+        }
+    }
+}
+
+/// How `save_to_writer`, `save_to_path` and friends write a document.
+///
+/// ```no_run
+/// # use pdfium::{PdfVersion, PdfiumLibrary, SaveMode, SaveOptions};
+/// # let lib = PdfiumLibrary::load("libpdfium.so")?;
+/// # lib.init_library();
+/// let doc = lib.open_document_from_path("signed.pdf", None)?;
+/// // Keep the existing signatures valid by appending changes.
+/// doc.save_to_path("signed.pdf", &SaveOptions::new().mode(SaveMode::Incremental))?;
+/// // Or rewrite a copy as PDF 1.7.
+/// doc.save_to_path("copy.pdf", &SaveOptions::new().version(PdfVersion::V1_7))?;
+/// # Ok::<(), pdfium::PdfiumError>(())
+/// ```
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct SaveOptions {
+    mode: SaveMode,
+    version: Option<PdfVersion>,
+}
+
+impl SaveOptions {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn mode(mut self, mode: SaveMode) -> Self {
+        self.mode = mode;
+        self
+    }
+
+    /// Write this file version. Left unset, the document keeps its own.
+    pub fn version(mut self, version: PdfVersion) -> Self {
+        self.version = Some(version);
+        self
+    }
+
+    pub fn get_mode(&self) -> SaveMode {
+        self.mode
+    }
+
+    pub fn get_version(&self) -> Option<PdfVersion> {
+        self.version
+    }
+
+    /// Decode the raw `flags` and `version` taken by `save_document`.
+    pub fn from_raw(flags: u32, version: Option<i32>) -> Result<Self, PdfiumError> {
+        Ok(Self {
+            mode: SaveMode::from_raw(flags)?,
+            version: version.map(PdfVersion::from_raw).transpose()?,
+        })
+    }
+
+    pub(crate) fn to_raw(self) -> (u32, Option<i32>) {
+        (self.mode.to_raw(), self.version.map(PdfVersion::to_raw))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn options_round_trip_through_raw_values() {
+        let options = SaveOptions::new().mode(SaveMode::Incremental).version(PdfVersion::V1_7);
+        assert_eq!(options.to_raw(), (1, Some(17)));
+        assert_eq!(SaveOptions::from_raw(1, Some(17)), Ok(options));
+
+        assert_eq!(SaveOptions::new().to_raw(), (0, None));
+        assert_eq!(SaveOptions::from_raw(3, None).unwrap().get_mode(), SaveMode::RemoveSecurity);
+    }
+
+    #[test]
+    fn unknown_raw_values_are_rejected() {
+        assert!(SaveOptions::from_raw(4, None).is_err());
+        assert!(SaveOptions::from_raw(0, Some(18)).is_err());
+        assert_eq!(PdfVersion::from_raw(20), Ok(PdfVersion::V2_0));
+    }
+}
@@ -0,0 +1,410 @@
+//! Thread-safe access to a single `PdfiumLibrary`.
+//!
+//! PDFium keeps global state and is not reentrant, so every call must be
+//! serialised no matter which thread makes it. `SharedPdfium` owns the
+//! library behind a mutex and admits callers through a FIFO ticket queue.
+//! The queue belongs to the loaded binary rather than to the instance, so
+//! separate `SharedPdfium`s on the same binary (say, one per worker thread)
+//! still take turns. Callers run in the order they arrived, and once
+//! `capacity` callers are queued new arrivals either wait for space (`with`)
+//! or are turned away (`try_with`). Callers that must not block for long,
+//! such as a JS event loop, use `try_now` or `try_for`, which only run once
+//! nobody else is queued or running. Capabilities and handle counts are
+//! answered without taking a turn at all.
+
+use crate::error::PdfiumError;
+use crate::handles::HandleStats;
+use crate::init::BinaryKey;
+use crate::library::PdfiumLibrary;
+use crate::symbols::Capabilities;
+use std::path::Path;
+use std::sync::{Arc, Condvar, Mutex, MutexGuard, PoisonError, Weak};
+use std::time::{Duration, Instant};
+
+/// Default maximum number of callers queued or running at once.
+pub const DEFAULT_QUEUE_CAPACITY: usize = 64;
+
+/// Ticket state for the fair queue.
+struct QueueState {
+    next_ticket: u64,
+    now_serving: u64,
+}
+
+/// FIFO admission queue with a bounded number of outstanding tickets.
+struct FairQueue {
+    state: Mutex<QueueState>,
+    changed: Condvar,
+    capacity: usize,
+}
+
+/// Held while a caller has its turn; admits the next ticket on drop.
+struct QueueTurn<'a> {
+    queue: &'a FairQueue,
+}
+
+impl FairQueue {
+    fn new(capacity: usize) -> Self {
+        Self {
+            state: Mutex::new(QueueState { next_ticket: 0, now_serving: 0 }),
+            changed: Condvar::new(),
+            capacity: capacity.max(1),
+        }
+    }
+
+    fn lock(&self) -> MutexGuard<'_, QueueState> {
+        self.state.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    /// Take a ticket and wait for it to be served.
+    ///
+    /// If the queue is full, waits for space when `wait_for_space` is set and
+    /// returns `PdfiumError::Busy` otherwise.
+    fn enter(&self, wait_for_space: bool) -> Result<QueueTurn<'_>, PdfiumError> {
+        let mut state = self.lock();
+        while state.next_ticket - state.now_serving >= self.capacity as u64 {
+            if !wait_for_space {
+                return Err(PdfiumError::Busy);
+            }
+            state = self.changed.wait(state).unwrap_or_else(PoisonError::into_inner);
+        }
+
+        let ticket = state.next_ticket;
+        state.next_ticket += 1;
+        while state.now_serving != ticket {
+            state = self.changed.wait(state).unwrap_or_else(PoisonError::into_inner);
+        }
+
+        Ok(QueueTurn { queue: self })
+    }
+
+    /// Take a ticket once nobody else is queued or running, returning
+    /// `PdfiumError::Busy` if that does not happen within `timeout`.
+    fn enter_when_idle(&self, timeout: Duration) -> Result<QueueTurn<'_>, PdfiumError> {
+        let deadline = Instant::now() + timeout;
+        let mut state = self.lock();
+        while state.next_ticket != state.now_serving {
+            let remaining = deadline.saturating_duration_since(Instant::now());
+            if remaining.is_zero() {
+                return Err(PdfiumError::Busy);
+            }
+            state = self
+                .changed
+                .wait_timeout(state, remaining)
+                .unwrap_or_else(PoisonError::into_inner)
+                .0;
+        }
+        state.next_ticket += 1;
+        Ok(QueueTurn { queue: self })
+    }
+
+    fn len(&self) -> usize {
+        let state = self.lock();
+        (state.next_ticket - state.now_serving) as usize
+    }
+}
+
+impl Drop for QueueTurn<'_> {
+    fn drop(&mut self) {
+        let mut state = self.queue.lock();
+        state.now_serving += 1;
+        drop(state);
+        self.queue.changed.notify_all();
+    }
+}
+
+/// Admission queues by loaded binary, shared by every `SharedPdfium` on it.
+static QUEUES: Mutex<Vec<(BinaryKey, Weak<FairQueue>)>> = Mutex::new(Vec::new());
+
+/// The queue for `binary`, created with `capacity` if no live instance has
+/// one yet.
+fn queue_for(binary: BinaryKey, capacity: usize) -> Arc<FairQueue> {
+    let mut queues = QUEUES.lock().unwrap_or_else(PoisonError::into_inner);
+    queues.retain(|(_, queue)| queue.strong_count() > 0);
+    if let Some(queue) = queues.iter().find(|(key, _)| *key == binary).and_then(|(_, queue)| queue.upgrade()) {
+        return queue;
+    }
+    let queue = Arc::new(FairQueue::new(capacity));
+    queues.push((binary, Arc::downgrade(&queue)));
+    queue
+}
+
+struct Shared {
+    queue: Arc<FairQueue>,
+    lib: Mutex<PdfiumLibrary>,
+    capabilities: Capabilities,
+    /// Handle counts as of the end of the last call.
+    stats: Mutex<HandleStats>,
+}
+
+impl Drop for Shared {
+    fn drop(&mut self) {
+        // Dropping the library closes its handles and may destroy PDFium, so
+        // wait for other instances on the binary to finish their turn first.
+        let _turn = self.queue.enter(true);
+        self.lib.get_mut().unwrap_or_else(PoisonError::into_inner).destroy_library();
+    }
+}
+
+/// A `PdfiumLibrary` that can be shared and called from any thread.
+///
+/// Cloning is cheap and every clone refers to the same library. Instances
+/// created separately on the same binary have their own handles but share
+/// one queue, so they never enter PDFium at the same time. A plain
+/// `PdfiumLibrary` used alongside them bypasses that queue.
+#[derive(Clone)]
+pub struct SharedPdfium {
+    inner: Arc<Shared>,
+}
+
+impl SharedPdfium {
+    /// Wrap an already-loaded library with the default queue capacity.
+    pub fn new(lib: PdfiumLibrary) -> Self {
+        Self::with_capacity(lib, DEFAULT_QUEUE_CAPACITY)
+    }
+
+    /// Wrap an already-loaded library, allowing at most `capacity` callers to
+    /// be queued or running at once.
+    ///
+    /// The capacity belongs to the binary's queue, so it only takes effect
+    /// for the first live `SharedPdfium` on the binary; later ones join the
+    /// existing queue.
+    pub fn with_capacity(lib: PdfiumLibrary, capacity: usize) -> Self {
+        Self {
+            inner: Arc::new(Shared {
+                queue: queue_for(lib.binary_key(), capacity),
+                capabilities: lib.capabilities(),
+                stats: Mutex::new(lib.live_handle_stats()),
+                lib: Mutex::new(lib),
+            }),
+        }
+    }
+
+    /// Load the PDFium shared library from the given path.
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self, PdfiumError> {
+        Ok(Self::new(PdfiumLibrary::load(path)?))
+    }
+
+    /// Run `f` with exclusive access to the library.
+    ///
+    /// Callers are served in arrival order. If the queue is full this blocks
+    /// until a slot frees up.
+    pub fn with<R>(&self, f: impl FnOnce(&PdfiumLibrary) -> R) -> R {
+        match self.run(true, f) {
+            Ok(result) => result,
+            Err(_) => unreachable!("waiting for space never reports Busy"),
+        }
+    }
+
+    /// Like `with`, but returns `PdfiumError::Busy` immediately instead of
+    /// waiting when the queue is full.
+    ///
+    /// This still waits for the callers ahead of it; use `try_now` if the
+    /// calling thread must not block at all.
+    pub fn try_with<R>(&self, f: impl FnOnce(&PdfiumLibrary) -> R) -> Result<R, PdfiumError> {
+        self.run(false, f)
+    }
+
+    /// Run `f` only if the library is idle, returning `PdfiumError::Busy`
+    /// without waiting if any other caller is queued or running.
+    pub fn try_now<R>(&self, f: impl FnOnce(&PdfiumLibrary) -> R) -> Result<R, PdfiumError> {
+        self.try_for(Duration::ZERO, f)
+    }
+
+    /// Like `try_now`, but waits up to `timeout` for the library to become
+    /// idle before giving up with `PdfiumError::Busy`.
+    ///
+    /// Unlike `with` this does not queue behind other callers, so a steady
+    /// stream of them can keep it waiting for the whole timeout.
+    pub fn try_for<R>(&self, timeout: Duration, f: impl FnOnce(&PdfiumLibrary) -> R) -> Result<R, PdfiumError> {
+        let _turn = self.inner.queue.enter_when_idle(timeout)?;
+        Ok(self.call(f))
+    }
+
+    /// Number of callers currently queued or running.
+    pub fn queue_len(&self) -> usize {
+        self.inner.queue.len()
+    }
+
+    /// Optional feature groups supported by the loaded build. Answered
+    /// without waiting for the library.
+    pub fn capabilities(&self) -> Capabilities {
+        self.inner.capabilities
+    }
+
+    /// Live handle counts as of the end of the last call, for leak detection.
+    /// Answered without waiting for the library, so a call still running is
+    /// not reflected yet.
+    pub fn live_handle_stats(&self) -> HandleStats {
+        *self.inner.stats.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    /// The loaded binary this library was opened from.
+    #[cfg(feature = "napi")]
+    pub(crate) fn binary_key(&self) -> BinaryKey {
+        self.inner.lib.lock().unwrap_or_else(PoisonError::into_inner).binary_key()
+    }
+
+    fn run<R>(&self, wait_for_space: bool, f: impl FnOnce(&PdfiumLibrary) -> R) -> Result<R, PdfiumError> {
+        let _turn = self.inner.queue.enter(wait_for_space)?;
+        Ok(self.call(f))
+    }
+
+    fn call<R>(&self, f: impl FnOnce(&PdfiumLibrary) -> R) -> R {
+        // The queue already serialises callers; the mutex only exists to hand
+        // out the reference safely. A panic inside `f` leaves PDFium itself in
+        // a usable state, so recover from poisoning.
+        let lib = self.inner.lib.lock().unwrap_or_else(PoisonError::into_inner);
+        let result = f(&lib);
+        *self.inner.stats.lock().unwrap_or_else(PoisonError::into_inner) = lib.live_handle_stats();
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::thread;
+
+    #[test]
+    fn queue_serves_in_arrival_order() {
+        let queue = Arc::new(FairQueue::new(16));
+        let order = Arc::new(Mutex::new(Vec::new()));
+
+        // Hold the queue so the workers line up behind us.
+        let first = queue.enter(true).unwrap();
+        let mut workers = Vec::new();
+        for i in 0..4 {
+            let worker_queue = Arc::clone(&queue);
+            let order = Arc::clone(&order);
+            workers.push(thread::spawn(move || {
+                let _turn = worker_queue.enter(true).unwrap();
+                order.lock().unwrap().push(i);
+            }));
+            // Wait until worker `i` holds its ticket before spawning the next.
+            while queue.len() < i + 2 {
+                thread::sleep(Duration::from_millis(1));
+            }
+        }
+        drop(first);
+
+        for worker in workers {
+            worker.join().unwrap();
+        }
+        assert_eq!(*order.lock().unwrap(), vec![0, 1, 2, 3]);
+    }
+
+    #[test]
+    fn full_queue_reports_busy() {
+        let queue = FairQueue::new(1);
+        let turn = queue.enter(true).unwrap();
+        assert!(matches!(queue.enter(false), Err(PdfiumError::Busy)));
+        drop(turn);
+        assert!(queue.enter(false).is_ok());
+    }
+
+    #[test]
+    fn busy_queue_refuses_immediate_entry() {
+        let queue = FairQueue::new(16);
+        let turn = queue.enter(true).unwrap();
+        assert!(matches!(queue.enter_when_idle(Duration::ZERO), Err(PdfiumError::Busy)));
+        assert_eq!(queue.len(), 1);
+        drop(turn);
+
+        let turn = queue.enter_when_idle(Duration::ZERO).unwrap();
+        assert_eq!(queue.len(), 1);
+        drop(turn);
+        assert_eq!(queue.len(), 0);
+        assert!(queue.enter(false).is_ok());
+    }
+
+    #[test]
+    fn idle_entry_waits_up_to_its_timeout() {
+        let queue = FairQueue::new(16);
+        let turn = queue.enter(true).unwrap();
+        let started = Instant::now();
+        assert!(matches!(queue.enter_when_idle(Duration::from_millis(20)), Err(PdfiumError::Busy)));
+        assert!(started.elapsed() >= Duration::from_millis(20));
+
+        thread::scope(|scope| {
+            scope.spawn(move || {
+                thread::sleep(Duration::from_millis(20));
+                drop(turn);
+            });
+            assert!(queue.enter_when_idle(Duration::from_secs(10)).is_ok());
+        });
+    }
+
+    #[test]
+    fn waiting_caller_runs_once_space_frees() {
+        let queue = Arc::new(FairQueue::new(1));
+        let ran = Arc::new(AtomicUsize::new(0));
+
+        let turn = queue.enter(true).unwrap();
+        let worker = {
+            let queue = Arc::clone(&queue);
+            let ran = Arc::clone(&ran);
+            thread::spawn(move || {
+                let _turn = queue.enter(true).unwrap();
+                ran.fetch_add(1, Ordering::SeqCst);
+            })
+        };
+
+        thread::sleep(Duration::from_millis(20));
+        assert_eq!(ran.load(Ordering::SeqCst), 0);
+        drop(turn);
+        worker.join().unwrap();
+        assert_eq!(ran.load(Ordering::SeqCst), 1);
+        assert_eq!(queue.len(), 0);
+    }
+
+    #[test]
+    fn queues_are_shared_per_binary() {
+        let a = queue_for(usize::MAX, 3);
+        let b = queue_for(usize::MAX, 16);
+        let other = queue_for(usize::MAX - 1, 16);
+        assert!(Arc::ptr_eq(&a, &b));
+        assert!(!Arc::ptr_eq(&a, &other));
+        assert_eq!(b.capacity, 3);
+
+        // Once every user has gone, the next one starts a fresh queue.
+        drop((a, b));
+        assert_eq!(queue_for(usize::MAX, 16).capacity, 16);
+    }
+
+    #[test]
+    fn instances_on_one_binary_take_turns() {
+        let path = crate::test_support::skip_if_no_lib!();
+        let first = SharedPdfium::load(&path).unwrap();
+        let second = SharedPdfium::load(&path).unwrap();
+
+        first.with(|_| {
+            assert_eq!(second.queue_len(), 1);
+            assert!(matches!(second.try_now(|_| ()), Err(PdfiumError::Busy)));
+        });
+        assert!(second.try_now(|_| ()).is_ok());
+    }
+
+    #[test]
+    fn shared_library_is_usable_across_threads() {
+        let path = crate::test_support::skip_if_no_lib!();
+        let shared = SharedPdfium::load(&path).unwrap();
+        shared.with(|lib| lib.init_library());
+
+        let pdf_data = std::fs::read(crate::test_support::test_pdf_path()).expect("Failed to read test PDF");
+        let doc = shared.with(|lib| lib.load_document(&pdf_data, None)).unwrap();
+
+        let workers: Vec<_> = (0..4)
+            .map(|_| {
+                let shared = shared.clone();
+                thread::spawn(move || shared.with(|lib| lib.get_page_count(doc)).unwrap())
+            })
+            .collect();
+        for worker in workers {
+            assert!(worker.join().unwrap() > 0);
+        }
+
+        shared.with(|lib| lib.close_document(doc)).unwrap();
+        shared.with(|lib| lib.destroy_library());
+    }
+}
@@ -0,0 +1,83 @@
+//! Optional PDFium exports resolved on first use.
+//!
+//! The symbols every caller needs are loaded eagerly by `PdfiumLibrary::load`.
+//! Everything else (signatures, attachments, annotation editing, …) varies
+//! between PDFium builds, so it is looked up lazily: a build that lacks a
+//! feature still loads, and only the methods that need it fail with
+//! `PdfiumError::SymbolMissing`.
+
+use crate::error::PdfiumError;
+use libloading::Library;
+use std::cell::OnceCell;
+
+/// An optional PDFium export, resolved on first use and cached.
+pub(crate) struct LazySymbol<T> {
+    name: &'static str,
+    resolved: OnceCell<Option<T>>,
+}
+
+impl<T: Copy> LazySymbol<T> {
+    pub(crate) const fn new(name: &'static str) -> Self {
+        Self {
+            name,
+            resolved: OnceCell::new(),
+        }
+    }
+
+    /// Resolve the symbol, returning `None` if this build does not export it.
+    ///
+    /// `T` must be the function pointer type of the export, and `lib` must be
+    /// the library every call passes in; the pointer is cached.
+    pub(crate) fn resolve(&self, lib: &Library) -> Option<T> {
+        *self.resolved.get_or_init(|| {
+            // SAFETY: `T` is the declared C signature of `name`.
+            unsafe { lib.get::<T>(self.name.as_bytes()) }.ok().map(|sym| *sym)
+        })
+    }
+
+    /// Resolve the symbol or report it as missing.
+    pub(crate) fn get(&self, lib: &Library) -> Result<T, PdfiumError> {
+        self.resolve(lib).ok_or(PdfiumError::SymbolMissing(self.name))
+    }
+}
+
+/// Which optional feature groups the loaded PDFium build supports.
+///
+/// A group is available when every export it relies on is present.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Capabilities {
+    /// Per-character font size, weight and name.
+    pub text_font_info: bool,
+    /// Per-character unicode, geometry, colours and matrix.
+    pub text_char_details: bool,
+    pub text_search: bool,
+    /// Text rectangles and bounded text extraction.
+    pub text_rects: bool,
+    /// Reading and writing media/crop/bleed/trim/art boxes.
+    pub page_boxes: bool,
+    /// Rotation, transparency, flattening and content generation.
+    pub page_editing: bool,
+    pub coordinate_conversion: bool,
+    /// `FPDFAvail` progressive loading of linearized files.
+    pub progressive_loading: bool,
+    /// Pausable rendering, used by `render_page_cancellable`.
+    pub progressive_rendering: bool,
+    pub signatures: bool,
+    pub attachments: bool,
+    /// Importing pages between documents and N-up layout.
+    pub page_import: bool,
+    pub bookmarks: bool,
+    pub links: bool,
+    pub annotations: bool,
+    pub annotation_editing: bool,
+    /// `render_page_object` and `render_annotation`.
+    pub object_rendering: bool,
+    /// Reading embedded page thumbnails.
+    pub thumbnails: bool,
+    /// `RenderOptions::color_scheme`.
+    pub color_schemes: bool,
+    /// Drawing interactive form fields and setting their highlight.
+    pub forms: bool,
+    /// The build was compiled with Skia and accepts `RendererType::Skia`.
+    pub skia_renderer: bool,
+}
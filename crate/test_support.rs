@@ -0,0 +1,48 @@
+//! Shared helpers for tests that need a real PDFium binary.
+
+use std::path::PathBuf;
+
+/// Resolve the path to libpdfium for testing.
+/// Uses PDFIUM_LIB_PATH env var, or falls back to npm/darwin-arm64/libpdfium.dylib.
+pub(crate) fn lib_path() -> Option<PathBuf> {
+    if let Ok(path) = std::env::var("PDFIUM_LIB_PATH") {
+        return Some(PathBuf::from(path));
+    }
+
+    // Fall back to local dev path relative to crate root
+    let candidates = [
+        "npm/darwin-arm64/libpdfium.dylib",
+        "npm/darwin-x64/libpdfium.dylib",
+        "npm/linux-x64-gnu/libpdfium.so",
+        "npm/linux-arm64-gnu/libpdfium.so",
+        "npm/linux-x64-musl/libpdfium.so",
+        "npm/win32-x64-msvc/pdfium.dll",
+    ];
+
+    for candidate in &candidates {
+        let path = PathBuf::from(candidate);
+        if path.exists() {
+            return Some(path);
+        }
+    }
+
+    None
+}
+
+pub(crate) fn test_pdf_path() -> PathBuf {
+    PathBuf::from("test/fixtures/test_1.pdf")
+}
+
+macro_rules! skip_if_no_lib {
+    () => {
+        match $crate::test_support::lib_path() {
+            Some(p) => p,
+            None => {
+                eprintln!("Skipping test: no libpdfium available");
+                return;
+            }
+        }
+    };
+}
+
+pub(crate) use skip_if_no_lib;
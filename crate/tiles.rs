@@ -0,0 +1,338 @@
+//! Deep-zoom tile pyramids: a page rendered as Deep Zoom (DZI) style levels,
+//! each half the size of the next and cut into square tiles.
+//!
+//! Level `max_level` is the page at the requested zoom; every level below
+//! halves it (rounding up) down to level 0, a single pixel. Tiles are
+//! rendered with `render_region`, so no level is ever rendered whole.
+
+use crate::error::PdfiumError;
+use crate::library::PdfiumLibrary;
+use crate::render::{ClipRect, Matrix, PixelFormat, RenderOptions};
+
+/// Size of one pyramid level.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PyramidLevel {
+    pub level: u32,
+    pub width: u32,
+    pub height: u32,
+    pub columns: u32,
+    pub rows: u32,
+}
+
+/// One rendered tile, tightly packed in `format`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Tile {
+    pub level: u32,
+    pub column: u32,
+    pub row: u32,
+    /// Position of the tile's top-left pixel within its level, overlap included.
+    pub x: u32,
+    pub y: u32,
+    pub width: u32,
+    pub height: u32,
+    pub format: PixelFormat,
+    pub data: Vec<u8>,
+}
+
+/// Renders the tiles of one page's pyramid, all at once or on demand.
+///
+/// ```no_run
+/// # use pdfium::{PdfiumLibrary, RenderOptions};
+/// # let lib = PdfiumLibrary::load("libpdfium.so")?;
+/// # lib.init_library();
+/// # let doc = lib.load_document_from_path("input.pdf", None)?;
+/// # let page = lib.load_page(doc, 0)?;
+/// let pyramid = lib.tile_pyramid(page, 256, 4.0, &RenderOptions::new())?.overlap(1);
+/// std::fs::write("page.dzi", pyramid.dzi_manifest("png"))?;
+/// for tile in pyramid.tiles() {
+///     let tile = tile?;
+///     // Store tile.data as `page_files/{level}/{column}_{row}`.
+/// }
+/// # Ok::<(), Box<dyn std::error::Error>>(())
+/// ```
+pub struct TilePyramid<'lib> {
+    lib: &'lib PdfiumLibrary,
+    page_handle: u32,
+    page_width: f32,
+    page_height: f32,
+    tile_size: u32,
+    overlap: u32,
+    options: RenderOptions,
+    levels: Vec<PyramidLevel>,
+}
+
+impl PdfiumLibrary {
+    /// Describe the pyramid of `page_handle` rendered at up to `max_zoom`
+    /// (1.0 = 72 DPI) in tiles of `tile_size` pixels.
+    ///
+    /// The page is laid out as displayed, through its CropBox and `/Rotate`;
+    /// the rotation in `options` turns it further.
+    pub fn tile_pyramid(
+        &self,
+        page_handle: u32,
+        tile_size: u32,
+        max_zoom: f32,
+        options: &RenderOptions,
+    ) -> Result<TilePyramid<'_>, PdfiumError> {
+        if tile_size == 0 {
+            return Err(PdfiumError::InvalidArgument("tile size: 0".into()));
+        }
+        if !(max_zoom.is_finite() && max_zoom > 0.0) {
+            return Err(PdfiumError::InvalidArgument(format!("zoom: {}", max_zoom)));
+        }
+        let page_width = self.get_page_width(page_handle)? as f32;
+        let page_height = self.get_page_height(page_handle)? as f32;
+        let (turned_width, turned_height) = if options.get_rotation().rem_euclid(2) == 1 {
+            (page_height, page_width)
+        } else {
+            (page_width, page_height)
+        };
+
+        let full_width = (turned_width * max_zoom).ceil();
+        let full_height = (turned_height * max_zoom).ceil();
+        if !(1.0..=i32::MAX as f32).contains(&full_width) || !(1.0..=i32::MAX as f32).contains(&full_height) {
+            return Err(PdfiumError::InvalidArgument(format!(
+                "pyramid size: {}x{}",
+                full_width, full_height
+            )));
+        }
+
+        Ok(TilePyramid {
+            lib: self,
+            page_handle,
+            page_width,
+            page_height,
+            tile_size,
+            overlap: 0,
+            options: *options,
+            levels: pyramid_levels(full_width as u32, full_height as u32, tile_size),
+        })
+    }
+}
+
+impl TilePyramid<'_> {
+    /// Pixels each tile shares with its neighbours on every inner edge.
+    pub fn overlap(mut self, overlap: u32) -> Self {
+        self.overlap = overlap;
+        self
+    }
+
+    /// Size of the full-resolution (highest) level.
+    pub fn width(&self) -> u32 {
+        self.max_level().width
+    }
+
+    pub fn height(&self) -> u32 {
+        self.max_level().height
+    }
+
+    pub fn tile_size(&self) -> u32 {
+        self.tile_size
+    }
+
+    pub fn get_overlap(&self) -> u32 {
+        self.overlap
+    }
+
+    pub fn format(&self) -> PixelFormat {
+        self.options.get_format()
+    }
+
+    /// Every level, from the single pixel at level 0 up to full resolution.
+    pub fn levels(&self) -> &[PyramidLevel] {
+        &self.levels
+    }
+
+    /// The `.dzi` descriptor for tiles stored with file extension `format`.
+    pub fn dzi_manifest(&self, format: &str) -> String {
+        format!(
+            "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n\
+             <Image xmlns=\"http://schemas.microsoft.com/deepzoom/2008\" Format=\"{}\" Overlap=\"{}\" TileSize=\"{}\">\n  \
+             <Size Width=\"{}\" Height=\"{}\"/>\n\
+             </Image>\n",
+            format,
+            self.overlap,
+            self.tile_size,
+            self.width(),
+            self.height()
+        )
+    }
+
+    /// Render one tile. Edge tiles are cut short at the level's edge.
+    pub fn render_tile(&self, level: u32, column: u32, row: u32) -> Result<Tile, PdfiumError> {
+        let info = self
+            .levels
+            .get(level as usize)
+            .ok_or_else(|| PdfiumError::InvalidArgument(format!("pyramid level: {}", level)))?;
+        let (x, y, width, height) = tile_rect(info, self.tile_size, self.overlap, column, row).ok_or_else(|| {
+            PdfiumError::InvalidArgument(format!("tile {},{} of level {}", column, row, level))
+        })?;
+
+        let turns = self.options.get_rotation();
+        let turned = Matrix::quarter_turns(turns, self.page_width, self.page_height);
+        let (turned_width, turned_height) = if turns.rem_euclid(2) == 1 {
+            (self.page_height, self.page_width)
+        } else {
+            (self.page_width, self.page_height)
+        };
+        // Scale each axis separately so the page exactly fills the level.
+        let matrix = turned
+            .then(Matrix::scale(
+                info.width as f32 / turned_width,
+                info.height as f32 / turned_height,
+            ))
+            .then(Matrix::translate(-(x as f32), -(y as f32)));
+
+        let options = self.options.rotation(0);
+        let format = options.get_format();
+        let stride = format.min_stride(width as i32);
+        let mut data = vec![0u8; stride * height as usize];
+        self.lib.render_region(
+            self.page_handle,
+            width as i32,
+            height as i32,
+            &matrix,
+            ClipRect::bitmap(width as i32, height as i32),
+            &options,
+            &mut data,
+            stride,
+        )?;
+
+        Ok(Tile {
+            level,
+            column,
+            row,
+            x,
+            y,
+            width,
+            height,
+            format,
+            data,
+        })
+    }
+
+    /// Render every tile of every level, lowest level first.
+    pub fn tiles(&self) -> impl Iterator<Item = Result<Tile, PdfiumError>> + '_ {
+        self.levels.iter().flat_map(move |info| {
+            (0..info.rows).flat_map(move |row| {
+                (0..info.columns).map(move |column| self.render_tile(info.level, column, row))
+            })
+        })
+    }
+
+    fn max_level(&self) -> &PyramidLevel {
+        self.levels.last().expect("a pyramid has at least one level")
+    }
+}
+
+/// The levels of a `width` x `height` image: enough halvings to reach one
+/// pixel, each level rounding up.
+fn pyramid_levels(width: u32, height: u32, tile_size: u32) -> Vec<PyramidLevel> {
+    let largest = width.max(height);
+    let mut max_level = 0;
+    while (1u64 << max_level) < largest as u64 {
+        max_level += 1;
+    }
+
+    (0..=max_level)
+        .map(|level| {
+            let shift = max_level - level;
+            let width = width.div_ceil(1 << shift).max(1);
+            let height = height.div_ceil(1 << shift).max(1);
+            PyramidLevel {
+                level,
+                width,
+                height,
+                columns: width.div_ceil(tile_size),
+                rows: height.div_ceil(tile_size),
+            }
+        })
+        .collect()
+}
+
+/// Pixel bounds `(x, y, width, height)` of a tile within its level, widened
+/// by `overlap` towards each neighbour and clipped at the level's edges.
+fn tile_rect(level: &PyramidLevel, tile_size: u32, overlap: u32, column: u32, row: u32) -> Option<(u32, u32, u32, u32)> {
+    if column >= level.columns || row >= level.rows {
+        return None;
+    }
+    let span = |index: u32, extent: u32| {
+        let start = index * tile_size;
+        let first = start.saturating_sub(overlap);
+        let last = (start + tile_size).saturating_add(overlap).min(extent);
+        (first, last - first)
+    };
+    let (x, width) = span(column, level.width);
+    let (y, height) = span(row, level.height);
+    Some((x, y, width, height))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{skip_if_no_lib, test_pdf_path};
+
+    #[test]
+    fn levels_halve_down_to_one_pixel() {
+        let levels = pyramid_levels(1000, 600, 256);
+        assert_eq!(levels.len(), 11);
+        assert_eq!(
+            levels[10],
+            PyramidLevel {
+                level: 10,
+                width: 1000,
+                height: 600,
+                columns: 4,
+                rows: 3,
+            }
+        );
+        assert_eq!((levels[9].width, levels[9].height), (500, 300));
+        assert_eq!((levels[8].width, levels[8].height), (250, 150));
+        assert_eq!((levels[1].width, levels[1].height), (2, 2));
+        assert_eq!((levels[0].width, levels[0].height, levels[0].columns), (1, 1, 1));
+    }
+
+    #[test]
+    fn edge_tiles_are_clipped_and_inner_edges_overlap() {
+        let level = pyramid_levels(1000, 600, 256)[10];
+        assert_eq!(tile_rect(&level, 256, 0, 0, 0), Some((0, 0, 256, 256)));
+        assert_eq!(tile_rect(&level, 256, 0, 3, 2), Some((768, 512, 232, 88)));
+        assert_eq!(tile_rect(&level, 256, 1, 0, 0), Some((0, 0, 257, 257)));
+        assert_eq!(tile_rect(&level, 256, 1, 1, 1), Some((255, 255, 258, 258)));
+        assert_eq!(tile_rect(&level, 256, 1, 3, 2), Some((767, 511, 233, 89)));
+        assert_eq!(tile_rect(&level, 256, 0, 4, 0), None);
+    }
+
+    #[test]
+    fn tiles_cover_every_level() {
+        let path = skip_if_no_lib!();
+        let lib = PdfiumLibrary::load(&path).unwrap();
+        lib.init_library();
+
+        let doc = lib.load_document_from_path(test_pdf_path(), None).unwrap();
+        let page = lib.load_page(doc, 0).unwrap();
+        let options = RenderOptions::new().rotation(1);
+        let pyramid = lib.tile_pyramid(page, 128, 0.5, &options).unwrap().overlap(1);
+
+        // A quarter turn swaps the page's width and height.
+        let page_width = lib.get_page_width(page).unwrap();
+        assert_eq!(pyramid.height(), (page_width as f32 * 0.5).ceil() as u32);
+        assert!(pyramid.dzi_manifest("png").contains("TileSize=\"128\""));
+
+        let expected: u32 = pyramid.levels().iter().map(|l| l.columns * l.rows).sum();
+        let mut count = 0;
+        for tile in pyramid.tiles() {
+            let tile = tile.unwrap();
+            assert_eq!(tile.data.len(), (tile.width * tile.height * 4) as usize);
+            count += 1;
+        }
+        assert_eq!(count, expected);
+
+        assert!(pyramid.render_tile(99, 0, 0).is_err());
+        lib.close_page(page).unwrap();
+        assert!(pyramid.render_tile(0, 0, 0).is_err());
+
+        lib.close_document(doc).unwrap();
+        lib.destroy_library();
+    }
+}
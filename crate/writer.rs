@@ -0,0 +1,237 @@
+//! `FPDF_FILEWRITE` over a Rust `Write`, used by the save methods.
+//!
+//! PDFium calls `WriteBlock` synchronously from within the save call, so a
+//! writer error is reported back as a failed write and surfaced once the save
+//! returns; a panic is caught in the callback and resumed after PDFium has
+//! unwound its own stack.
+
+use crate::bindings::FPDF_FILEWRITE;
+use crate::error::PdfiumError;
+use std::any::Any;
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, BufWriter, Write};
+use std::os::raw::{c_int, c_ulong, c_void};
+use std::panic::{self, AssertUnwindSafe};
+use std::path::{Path, PathBuf};
+use std::process;
+use std::sync::atomic::{AtomicU32, Ordering};
+
+/// An `FPDF_FILEWRITE` that forwards each block to `writer`.
+#[repr(C)]
+pub(crate) struct WriterContext<'a> {
+    // Must stay first: PDFium hands the callback a pointer to this field.
+    file_write: FPDF_FILEWRITE,
+    writer: &'a mut dyn Write,
+    error: Option<io::Error>,
+    panic: Option<Box<dyn Any + Send>>,
+}
+
+impl<'a> WriterContext<'a> {
+    pub(crate) fn new(writer: &'a mut dyn Write) -> Self {
+        Self {
+            file_write: FPDF_FILEWRITE {
+                version: 1,
+                WriteBlock: Some(write_block),
+            },
+            writer,
+            error: None,
+            panic: None,
+        }
+    }
+
+    /// Pointer to pass to `FPDF_SaveAsCopy` / `FPDF_SaveWithVersion`.
+    pub(crate) fn as_ptr(&mut self) -> *mut FPDF_FILEWRITE {
+        self as *mut Self as *mut FPDF_FILEWRITE
+    }
+
+    /// Turn the save call's result into ours, resuming a writer panic and
+    /// preferring the writer's own error over PDFium's generic failure.
+    pub(crate) fn finish(self, ok: bool) -> Result<(), PdfiumError> {
+        if let Some(payload) = self.panic {
+            panic::resume_unwind(payload);
+        }
+        if let Some(err) = self.error {
+            return Err(PdfiumError::Io(err.to_string()));
+        }
+        if !ok {
+            return Err(PdfiumError::Save);
+        }
+        self.writer.flush().map_err(|e| PdfiumError::Io(e.to_string()))
+    }
+}
+
+unsafe extern "C" fn write_block(this: *mut FPDF_FILEWRITE, data: *const c_void, size: c_ulong) -> c_int {
+    // SAFETY: `this` points to the start of a live `WriterContext`.
+    let ctx = &mut *(this as *mut WriterContext<'_>);
+    if ctx.error.is_some() || ctx.panic.is_some() {
+        return 0;
+    }
+    if size == 0 {
+        return 1;
+    }
+    if data.is_null() {
+        return 0;
+    }
+    // SAFETY: PDFium passes `size` readable bytes.
+    let data = std::slice::from_raw_parts(data as *const u8, size as usize);
+
+    match panic::catch_unwind(AssertUnwindSafe(|| ctx.writer.write_all(data))) {
+        Ok(Ok(())) => 1,
+        Ok(Err(err)) => {
+            ctx.error = Some(err);
+            0
+        }
+        Err(payload) => {
+            ctx.panic = Some(payload);
+            0
+        }
+    }
+}
+
+/// Write `path` by filling a temporary file next to it and renaming it into
+/// place, so readers never see a partially written file.
+pub(crate) fn write_atomically(
+    path: &Path,
+    write: impl FnOnce(&mut dyn Write) -> Result<(), PdfiumError>,
+) -> Result<(), PdfiumError> {
+    let mut temp = TempFile::new(path)?;
+    let mut out = BufWriter::new(temp.file.take().expect("file is open until committed"));
+    write(&mut out)?;
+    let file = out.into_inner().map_err(|e| io_error(e.into_error()))?;
+    file.sync_all().map_err(io_error)?;
+    drop(file);
+    temp.commit(path)
+}
+
+/// A temporary file that is removed unless it is renamed into place.
+struct TempFile {
+    path: PathBuf,
+    file: Option<File>,
+    committed: bool,
+}
+
+impl TempFile {
+    fn new(target: &Path) -> Result<Self, PdfiumError> {
+        static COUNTER: AtomicU32 = AtomicU32::new(0);
+
+        let name = target
+            .file_name()
+            .ok_or_else(|| PdfiumError::InvalidArgument(format!("path: {}", target.display())))?;
+        let path = target.with_file_name(format!(
+            ".{}.{}-{}.tmp",
+            name.to_string_lossy(),
+            process::id(),
+            COUNTER.fetch_add(1, Ordering::Relaxed)
+        ));
+        let file = OpenOptions::new().write(true).create_new(true).open(&path).map_err(io_error)?;
+        Ok(Self {
+            path,
+            file: Some(file),
+            committed: false,
+        })
+    }
+
+    fn commit(mut self, target: &Path) -> Result<(), PdfiumError> {
+        fs::rename(&self.path, target).map_err(io_error)?;
+        self.committed = true;
+        Ok(())
+    }
+}
+
+impl Drop for TempFile {
+    fn drop(&mut self) {
+        if !self.committed {
+            drop(self.file.take());
+            let _ = fs::remove_file(&self.path);
+        }
+    }
+}
+
+fn io_error(err: io::Error) -> PdfiumError {
+    PdfiumError::Io(err.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn write(ctx: &mut WriterContext<'_>, data: &[u8]) -> c_int {
+        let raw = ctx.as_ptr();
+        unsafe { ((*raw).WriteBlock.unwrap())(raw, data.as_ptr() as *const c_void, data.len() as c_ulong) }
+    }
+
+    #[test]
+    fn blocks_are_forwarded_to_the_writer() {
+        let mut out = Vec::new();
+        let mut ctx = WriterContext::new(&mut out);
+        assert_eq!(write(&mut ctx, b"%PDF"), 1);
+        assert_eq!(write(&mut ctx, b"-1.7"), 1);
+        ctx.finish(true).unwrap();
+        assert_eq!(out, b"%PDF-1.7");
+    }
+
+    struct FailingWriter;
+
+    impl Write for FailingWriter {
+        fn write(&mut self, _: &[u8]) -> io::Result<usize> {
+            Err(io::Error::other("disk full"))
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn writer_errors_fail_the_write() {
+        let mut out = FailingWriter;
+        let mut ctx = WriterContext::new(&mut out);
+        assert_eq!(write(&mut ctx, b"%PDF"), 0);
+        // Later blocks are refused without touching the writer again.
+        assert_eq!(write(&mut ctx, b"-1.7"), 0);
+        assert_eq!(ctx.finish(false), Err(PdfiumError::Io("disk full".into())));
+    }
+
+    struct PanickingWriter;
+
+    impl Write for PanickingWriter {
+        fn write(&mut self, _: &[u8]) -> io::Result<usize> {
+            panic!("boom");
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn writer_panics_resume_after_the_save() {
+        let mut out = PanickingWriter;
+        let mut ctx = WriterContext::new(&mut out);
+        assert_eq!(write(&mut ctx, b"%PDF"), 0);
+        let result = panic::catch_unwind(AssertUnwindSafe(|| ctx.finish(false)));
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn atomic_write_replaces_the_target() {
+        let dir = std::env::temp_dir().join(format!("pdfium-writer-{}", process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let target = dir.join("out.pdf");
+        fs::write(&target, b"old").unwrap();
+
+        let failed = write_atomically(&target, |out| {
+            out.write_all(b"partial").unwrap();
+            Err(PdfiumError::Save)
+        });
+        assert_eq!(failed, Err(PdfiumError::Save));
+        assert_eq!(fs::read(&target).unwrap(), b"old");
+
+        write_atomically(&target, |out| out.write_all(b"new").map_err(io_error)).unwrap();
+        assert_eq!(fs::read(&target).unwrap(), b"new");
+        // No temporary files are left behind.
+        assert_eq!(fs::read_dir(&dir).unwrap().count(), 1);
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -8,6 +8,17 @@ export interface NativePdfiumBinding {
   NativePdfium: {
     load(libraryPath: string): NativePdfium;
   };
+  NativeCancelToken: new () => NativeCancelToken;
+  NativeBitmap: new (width: number, height: number, format?: NativePixelFormat) => NativeBitmap;
+}
+
+/**
+ * Stops the renders it is passed to at their next pause point. Wire it to an
+ * `AbortSignal` with `signal.addEventListener('abort', () => token.cancel())`.
+ */
+export interface NativeCancelToken {
+  cancel(): void;
+  readonly cancelled: boolean;
 }
 
 export interface NativeBookmark {
@@ -52,12 +63,169 @@ export interface NativeLink {
   zoom: number;
 }
 
+export interface NativeInitOptions {
+  /** Extra directories to search for fonts. */
+  fontPaths?: string[];
+  /** 0=AGG, 1=Skia. Omit to use the build default. */
+  rendererType?: number;
+}
+
+export interface NativeCapabilities {
+  textFontInfo: boolean;
+  textCharDetails: boolean;
+  textSearch: boolean;
+  textRects: boolean;
+  pageBoxes: boolean;
+  pageEditing: boolean;
+  coordinateConversion: boolean;
+  progressiveLoading: boolean;
+  progressiveRendering: boolean;
+  signatures: boolean;
+  attachments: boolean;
+  pageImport: boolean;
+  bookmarks: boolean;
+  links: boolean;
+  annotations: boolean;
+  annotationEditing: boolean;
+  objectRendering: boolean;
+  thumbnails: boolean;
+  colorSchemes: boolean;
+  forms: boolean;
+  skiaRenderer: boolean;
+}
+
+export interface NativeRenderedImage {
+  width: number;
+  height: number;
+  /** RGBA pixels. */
+  data: Buffer;
+}
+
+export interface NativeThumbnail {
+  width: number;
+  height: number;
+  /** RGBA pixels. */
+  data: Buffer;
+  source: 'embedded' | 'rendered';
+}
+
+/** Colours, as 0xAARRGGBB, that replace those of a page's paths and text. */
+export interface NativeColorScheme {
+  pathFill: number;
+  pathStroke: number;
+  textFill: number;
+  textStroke: number;
+}
+
+/** Maps page point (x, y) to (ax + cy + e, bx + dy + f). */
+export interface NativeMatrix {
+  a: number;
+  b: number;
+  c: number;
+  d: number;
+  e: number;
+  f: number;
+}
+
+export interface NativeTileOptions {
+  tileSize: number;
+  /** Zoom of the highest level; 1 = 72 DPI. */
+  maxZoom: number;
+  overlap?: number;
+  /** Clockwise quarter turns on top of the page's own rotation. */
+  rotation?: number;
+  flags?: number;
+  bgColour?: number;
+  /** Tile encoding, default `'raw'` (RGBA pixels). */
+  format?: 'raw' | 'png' | 'jpeg' | 'webp';
+  /** JPEG quality 1–100, default 85. */
+  quality?: number;
+}
+
+export interface NativeTilePyramid {
+  width: number;
+  height: number;
+  tileSize: number;
+  overlap: number;
+  /** From level 0 (one pixel) up to full resolution. */
+  levels: { width: number; height: number; columns: number; rows: number }[];
+  /** The `.dzi` descriptor for the requested tile format; absent for raw tiles. */
+  dzi?: string;
+}
+
+export interface NativeTile {
+  x: number;
+  y: number;
+  width: number;
+  height: number;
+  /** RGBA pixels, or the encoded image if a tile format was requested. */
+  data: Buffer;
+}
+
+/** Byte layout for `renderPageInto`. */
+export type NativePixelFormat = 'gray8' | 'bgr' | 'bgrx' | 'bgra' | 'rgba';
+
+/** Named `FPDF_RenderPageBitmap` flags; unset means off. */
+export interface NativeRenderFlags {
+  annotations?: boolean;
+  lcdText?: boolean;
+  noNativeText?: boolean;
+  grayscale?: boolean;
+  printing?: boolean;
+  noSmoothText?: boolean;
+  noSmoothImage?: boolean;
+  noSmoothPath?: boolean;
+  forceHalftone?: boolean;
+  /** Ignored by renders that take a pixel format, which picks the byte order. */
+  reverseByteOrder?: boolean;
+  convertFillToStroke?: boolean;
+}
+
+export interface NativeBitmapRenderOptions {
+  /** Clockwise quarter turns. */
+  rotation?: number;
+  flags?: NativeRenderFlags;
+  bgColour?: number;
+  drawForms?: boolean;
+  premultiplied?: boolean;
+}
+
+/** Pixels that `renderPageToBitmap` draws into again and again. */
+export interface NativeBitmap {
+  readonly width: number;
+  readonly height: number;
+  /** Bytes from the start of one row to the next. */
+  readonly stride: number;
+  readonly format: NativePixelFormat;
+  /** The pixels as last rendered: the bitmap's own memory, updated in place by later renders. */
+  readonly data: Buffer;
+}
+
+export interface NativeHandleStats {
+  documents: number;
+  pages: number;
+  textPages: number;
+}
+
+/**
+ * While async work is queued or running, synchronous methods wait up to
+ * 250ms for it and then throw with code `ERR_PDFIUM_BUSY` rather than block
+ * the event loop for longer. Retry once the pending promises settle.
+ * `capabilities` and `liveHandleStats` never wait, and `destroyLibrary`
+ * always waits for async work already started.
+ */
 export interface NativePdfium {
-  initLibrary(): void;
+  initLibrary(options?: NativeInitOptions): void;
+  /** Blocks until async calls already started have finished. */
   destroyLibrary(): void;
   getLastError(): number;
+  capabilities(): NativeCapabilities;
+  /** Counts as of the last settled call; async calls still running are not included. */
+  liveHandleStats(): NativeHandleStats;
 
+  /** Loads from a copy of `data`. Use `loadDocumentFromPath` to avoid holding a second copy in memory. */
   loadDocument(data: Buffer, password?: string): number;
+  loadDocumentFromPath(path: string, password?: string): number;
   closeDocument(handle: number): void;
   getPageCount(docHandle: number): number;
 
@@ -78,7 +246,98 @@ export interface NativePdfium {
     rotation: number,
     flags: number,
     bgColour: number,
+    drawForms?: boolean,
+    colorScheme?: NativeColorScheme,
   ): Buffer;
+  countPageObjects(pageHandle: number): number;
+  /** 1=Text, 2=Path, 3=Image, 4=Shading, 5=Form (0 if unknown). */
+  getPageObjectType(pageHandle: number, index: number): number;
+  /** Text is drawn at `scale` pixels per point; images at their own size. */
+  renderPageObject(pageHandle: number, index: number, scale: number, bgColour: number): NativeRenderedImage;
+  /** One annotation's appearance alone, at `scale` pixels per point. Copies the whole page on every call. */
+  renderAnnotation(
+    pageHandle: number,
+    index: number,
+    scale: number,
+    flags: number,
+    bgColour: number,
+  ): NativeRenderedImage;
+  /** `boxType` as for `getPageBox`; the size follows from the box and the page's /Rotate. */
+  renderAtDpi(
+    pageHandle: number,
+    dpi: number,
+    boxType: number,
+    rotation: number,
+    flags: number,
+    bgColour: number,
+  ): NativeRenderedImage;
+  /** Pack named flags into the `flags` integer the other render methods take. */
+  renderFlags(flags: NativeRenderFlags): number;
+  createBitmap(width: number, height: number, format?: NativePixelFormat): NativeBitmap;
+  /** Render to fill `bitmap` in its own format, replacing what it held. */
+  renderPageToBitmap(pageHandle: number, bitmap: NativeBitmap, options?: NativeBitmapRenderOptions): void;
+  /** The page as large as fits within `maxWidth` x `maxHeight`, aspect ratio kept. */
+  renderFit(
+    pageHandle: number,
+    maxWidth: number,
+    maxHeight: number,
+    rotation: number,
+    flags: number,
+    bgColour: number,
+  ): NativeRenderedImage;
+  /** A preview no larger than `maxEdge`, embedded if one fits, otherwise rendered. */
+  getThumbnail(pageHandle: number, maxEdge: number): NativeThumbnail;
+  getDecodedThumbnailData(pageHandle: number): Buffer | null;
+  getRawThumbnailData(pageHandle: number): Buffer | null;
+  /** Renders straight to PNG; `dpi` is recorded in the file. */
+  renderPageToPng(
+    pageHandle: number,
+    width: number,
+    height: number,
+    rotation: number,
+    flags: number,
+    bgColour: number,
+    dpi?: number,
+  ): Buffer;
+  /** Renders straight to JPEG at `quality` 1–100. */
+  renderPageToJpeg(
+    pageHandle: number,
+    width: number,
+    height: number,
+    rotation: number,
+    flags: number,
+    bgColour: number,
+    quality: number,
+    dpi?: number,
+  ): Buffer;
+  /** Renders into `buffer`, whose rows start `stride` bytes apart. */
+  renderPageInto(
+    pageHandle: number,
+    width: number,
+    height: number,
+    rotation: number,
+    flags: number,
+    bgColour: number,
+    format: NativePixelFormat,
+    premultiplied: boolean,
+    buffer: Buffer,
+    stride: number,
+  ): void;
+  /**
+   * Renders the part of the page `matrix` maps into `clip` (bitmap pixels) to
+   * an RGBA buffer. Page space is the unrotated page in points, origin top-left.
+   */
+  renderRegion(
+    pageHandle: number,
+    width: number,
+    height: number,
+    matrix: NativeMatrix,
+    clip: { left: number; top: number; right: number; bottom: number },
+    flags: number,
+    bgColour: number,
+  ): Buffer;
+  getTilePyramid(pageHandle: number, options: NativeTileOptions): NativeTilePyramid;
+  renderTile(pageHandle: number, options: NativeTileOptions, level: number, column: number, row: number): NativeTile;
 
   // Metadata / Document info
   getMetaText(docHandle: number, tag: string): string | null;
@@ -162,6 +421,15 @@ export interface NativePdfium {
 
   // Save / export
   saveDocument(docHandle: number, flags: number, version: number | null): Buffer;
+  /** Writes to a temporary file next to `path`, then renames it into place. */
+  saveDocumentToPath(docHandle: number, path: string, flags: number, version: number | null): void;
+  /** True once any edit has been made since the document was loaded or last saved. */
+  isDocumentDirty(docHandle: number): boolean;
+
+  // Forms
+  /** Colour is 0xRRGGBB; field type 0 applies to every field. */
+  setFormFieldHighlightColour(docHandle: number, fieldType: number, colour: number): void;
+  setFormFieldHighlightAlpha(docHandle: number, alpha: number): void;
 
   // Attachments
   getAttachmentCount(docHandle: number): number;
@@ -260,4 +528,33 @@ export interface NativePdfium {
   ): number;
   movePages(docHandle: number, pageIndices: number[], destPageIndex: number): void;
   copyViewerPreferences(destHandle: number, srcHandle: number): boolean;
+
+  // Async variants (run on a dedicated PDFium thread per loaded binary, serialised with other PDFium calls)
+  loadDocumentAsync(data: Buffer, password?: string): Promise<number>;
+  loadDocumentFromPathAsync(path: string, password?: string): Promise<number>;
+  getFullTextAsync(textPageHandle: number): Promise<string>;
+  renderPageAsync(
+    pageHandle: number,
+    width: number,
+    height: number,
+    rotation: number,
+    flags: number,
+    bgColour: number,
+    drawForms?: boolean,
+    colorScheme?: NativeColorScheme,
+  ): Promise<Buffer>;
+  createCancelToken(): NativeCancelToken;
+  /** Rejects with code `ERR_PDFIUM_CANCELLED` once `token` is cancelled or `timeoutMs` passes. */
+  renderPageCancellableAsync(
+    pageHandle: number,
+    width: number,
+    height: number,
+    rotation: number,
+    flags: number,
+    bgColour: number,
+    token: NativeCancelToken,
+    timeoutMs?: number | null,
+  ): Promise<Buffer>;
+  saveDocumentAsync(docHandle: number, flags: number, version: number | null): Promise<Buffer>;
+  saveDocumentToPathAsync(docHandle: number, path: string, flags: number, version: number | null): Promise<void>;
 }
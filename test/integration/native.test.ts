@@ -6,7 +6,9 @@
  * Tests skip gracefully when the native .node binary is not built.
  */
 
-import { readFile } from 'node:fs/promises';
+import { mkdtemp, readdir, readFile, rm } from 'node:fs/promises';
+import { tmpdir } from 'node:os';
+import { join } from 'node:path';
 import { describe, expect, test } from 'vitest';
 import { AnnotationType } from '../../src/core/types.js';
 import { NativePDFiumInstance } from '../../src/document/native-instance.js';
@@ -56,6 +58,23 @@ describe.skipIf(!hasNative)('native PDFium binding (low-level)', () => {
       }
     });
 
+    test('loads a document from a file path', async () => {
+      binding.initLibrary();
+      try {
+        const docHandle = binding.loadDocumentFromPath('test/fixtures/test_1.pdf');
+        expect(binding.getPageCount(docHandle)).toBeGreaterThan(0);
+        binding.closeDocument(docHandle);
+
+        const asyncHandle = await binding.loadDocumentFromPathAsync('test/fixtures/test_1.pdf');
+        expect(binding.getPageCount(asyncHandle)).toBeGreaterThan(0);
+        binding.closeDocument(asyncHandle);
+
+        expect(() => binding.loadDocumentFromPath('test/fixtures/missing.pdf')).toThrow();
+      } finally {
+        binding.destroyLibrary();
+      }
+    });
+
     test('loads a page and gets dimensions', async () => {
       binding.initLibrary();
       try {
@@ -120,6 +139,335 @@ describe.skipIf(!hasNative)('native PDFium binding (low-level)', () => {
       }
     });
 
+    test('renders into a caller-supplied buffer', async () => {
+      binding.initLibrary();
+      try {
+        const pdfData = await readFile('test/fixtures/test_1.pdf');
+        const docHandle = binding.loadDocument(Buffer.from(pdfData));
+        const pageHandle = binding.loadPage(docHandle, 0);
+
+        const rgba = binding.renderPage(pageHandle, 50, 50, 0, 0, 0xffffffff);
+        const stride = 50 * 4 + 8;
+        const bgra = Buffer.alloc(stride * 50);
+        binding.renderPageInto(pageHandle, 50, 50, 0, 0, 0xffffffff, 'bgra', false, bgra, stride);
+        expect([bgra[2], bgra[1], bgra[0], bgra[3]]).toEqual([rgba[0], rgba[1], rgba[2], rgba[3]]);
+
+        expect(() =>
+          binding.renderPageInto(pageHandle, 50, 50, 0, 0, 0xffffffff, 'bgra', false, Buffer.alloc(10), stride),
+        ).toThrow();
+
+        binding.closePage(pageHandle);
+        binding.closeDocument(docHandle);
+      } finally {
+        binding.destroyLibrary();
+      }
+    });
+
+    test('draws form fields when asked', async () => {
+      binding.initLibrary();
+      try {
+        const pdfData = await readFile('test/fixtures/test_6_with_form.pdf');
+        const docHandle = binding.loadDocument(Buffer.from(pdfData));
+        const pageHandle = binding.loadPage(docHandle, 0);
+
+        binding.setFormFieldHighlightColour(docHandle, 0, 0xff0000);
+        binding.setFormFieldHighlightAlpha(docHandle, 128);
+        const plain = binding.renderPage(pageHandle, 200, 200, 0, 0, 0xffffffff);
+        const withForms = binding.renderPage(pageHandle, 200, 200, 0, 0, 0xffffffff, true);
+        expect(withForms.length).toBe(plain.length);
+        expect(withForms.equals(plain)).toBe(false);
+        expect(() => binding.setFormFieldHighlightAlpha(docHandle, 256)).toThrow();
+
+        binding.closePage(pageHandle);
+        binding.closeDocument(docHandle);
+      } finally {
+        binding.destroyLibrary();
+      }
+    });
+
+    test('renders with a custom colour scheme', async () => {
+      binding.initLibrary();
+      try {
+        const pdfData = await readFile('test/fixtures/test_1.pdf');
+        const docHandle = binding.loadDocument(Buffer.from(pdfData));
+        const pageHandle = binding.loadPage(docHandle, 0);
+
+        const dark = { pathFill: 0xff303030, pathStroke: 0xffc0c0c0, textFill: 0xffe0e0e0, textStroke: 0xffe0e0e0 };
+        const plain = binding.renderPage(pageHandle, 100, 100, 0, 0, 0xff000000);
+        const themed = binding.renderPage(pageHandle, 100, 100, 0, 0, 0xff000000, false, dark);
+        expect(themed.length).toBe(plain.length);
+        expect(themed.equals(plain)).toBe(false);
+
+        binding.closePage(pageHandle);
+        binding.closeDocument(docHandle);
+      } finally {
+        binding.destroyLibrary();
+      }
+    });
+
+    test('renders straight to PNG and JPEG', async () => {
+      binding.initLibrary();
+      try {
+        const pdfData = await readFile('test/fixtures/test_1.pdf');
+        const docHandle = binding.loadDocument(Buffer.from(pdfData));
+        const pageHandle = binding.loadPage(docHandle, 0);
+
+        const png = binding.renderPageToPng(pageHandle, 100, 130, 0, 0, 0xffffffff, 144);
+        expect(png.subarray(0, 8)).toEqual(Buffer.from([0x89, 0x50, 0x4e, 0x47, 0x0d, 0x0a, 0x1a, 0x0a]));
+        expect(png.readUInt32BE(16)).toBe(100);
+        expect(png.readUInt32BE(20)).toBe(130);
+
+        const jpeg = binding.renderPageToJpeg(pageHandle, 100, 130, 0, 0, 0xffffffff, 80);
+        expect([jpeg[0], jpeg[1]]).toEqual([0xff, 0xd8]);
+        expect(() => binding.renderPageToJpeg(pageHandle, 100, 130, 0, 0, 0xffffffff, 0)).toThrow();
+
+        binding.closePage(pageHandle);
+        binding.closeDocument(docHandle);
+      } finally {
+        binding.destroyLibrary();
+      }
+    });
+
+    test('falls back to rendering pages without an embedded thumbnail', async () => {
+      binding.initLibrary();
+      try {
+        const pdfData = await readFile('test/fixtures/test_1.pdf');
+        const docHandle = binding.loadDocument(Buffer.from(pdfData));
+        const pageHandle = binding.loadPage(docHandle, 0);
+
+        expect(binding.getRawThumbnailData(pageHandle)).toBeNull();
+        const thumbnail = binding.getThumbnail(pageHandle, 128);
+        expect(thumbnail.source).toBe('rendered');
+        expect(Math.max(thumbnail.width, thumbnail.height)).toBe(128);
+        expect(thumbnail.data.length).toBe(thumbnail.width * thumbnail.height * 4);
+
+        binding.closePage(pageHandle);
+        binding.closeDocument(docHandle);
+      } finally {
+        binding.destroyLibrary();
+      }
+    });
+
+    test('renders a single page object and annotation', async () => {
+      binding.initLibrary();
+      try {
+        const pdfData = await readFile('test/fixtures/test_3_with_images.pdf');
+        const docHandle = binding.loadDocument(Buffer.from(pdfData));
+        const pageHandle = binding.loadPage(docHandle, 0);
+
+        const count = binding.countPageObjects(pageHandle);
+        const types = Array.from({ length: count }, (_, i) => binding.getPageObjectType(pageHandle, i));
+        const image = binding.renderPageObject(pageHandle, types.indexOf(3), 1, 0xffffffff);
+        expect(image.data.length).toBe(image.width * image.height * 4);
+
+        // A square annotation (subtype 5), 100 x 50 points.
+        const index = binding.createAnnotation(pageHandle, 5);
+        expect(binding.setAnnotationRect(pageHandle, index, 100, 600, 200, 550)).toBe(true);
+        const annotation = binding.renderAnnotation(pageHandle, index, 2, 0, 0);
+        expect([annotation.width, annotation.height]).toEqual([200, 100]);
+        expect(() => binding.renderAnnotation(pageHandle, index + 1, 1, 0, 0)).toThrow();
+
+        binding.closePage(pageHandle);
+        binding.closeDocument(docHandle);
+      } finally {
+        binding.destroyLibrary();
+      }
+    });
+
+    test('renders sized from the page box and rotation', async () => {
+      binding.initLibrary();
+      try {
+        const pdfData = await readFile('test/fixtures/test_1.pdf');
+        const docHandle = binding.loadDocument(Buffer.from(pdfData));
+        const pageHandle = binding.loadPage(docHandle, 0);
+        const width = binding.getPageWidth(pageHandle);
+        const height = binding.getPageHeight(pageHandle);
+
+        const image = binding.renderAtDpi(pageHandle, 144, 1, 0, 0, 0xffffffff);
+        expect([image.width, image.height]).toEqual([Math.round(width * 2), Math.round(height * 2)]);
+        expect(image.data.length).toBe(image.width * image.height * 4);
+        const turned = binding.renderAtDpi(pageHandle, 144, 1, 1, 0, 0xffffffff);
+        expect([turned.width, turned.height]).toEqual([image.height, image.width]);
+        expect(() => binding.renderAtDpi(pageHandle, 144, 9, 0, 0, 0xffffffff)).toThrow();
+
+        const fitted = binding.renderFit(pageHandle, 200, 200, 0, 0, 0xffffffff);
+        expect(Math.max(fitted.width, fitted.height)).toBe(200);
+
+        binding.closePage(pageHandle);
+        binding.closeDocument(docHandle);
+      } finally {
+        binding.destroyLibrary();
+      }
+    });
+
+    test('renders into a reusable bitmap with named flags', async () => {
+      binding.initLibrary();
+      try {
+        const pdfData = await readFile('test/fixtures/test_1.pdf');
+        const docHandle = binding.loadDocument(Buffer.from(pdfData));
+        const pageHandle = binding.loadPage(docHandle, 0);
+
+        expect(binding.renderFlags({ annotations: true, printing: true })).toBe(0x801);
+        expect(binding.renderFlags({})).toBe(0);
+
+        const bitmap = binding.createBitmap(101, 80, 'bgr');
+        expect([bitmap.width, bitmap.height, bitmap.stride, bitmap.format]).toEqual([101, 80, 303, 'bgr']);
+        binding.renderPageToBitmap(pageHandle, bitmap, { bgColour: 0xff0000ff });
+        const pixels = bitmap.data;
+        expect(pixels.length).toBe(303 * 80);
+        const first = Buffer.from(pixels);
+        binding.renderPageToBitmap(pageHandle, bitmap, { bgColour: 0xffff0000, flags: { grayscale: true } });
+        // The same memory, redrawn in place.
+        expect(bitmap.data).toBe(pixels);
+        expect(pixels.equals(first)).toBe(false);
+
+        expect(() => binding.createBitmap(0, 80)).toThrow();
+
+        binding.closePage(pageHandle);
+        binding.closeDocument(docHandle);
+      } finally {
+        binding.destroyLibrary();
+      }
+    });
+
+    test('renders a zoomed region of a page', async () => {
+      binding.initLibrary();
+      try {
+        const pdfData = await readFile('test/fixtures/test_1.pdf');
+        const docHandle = binding.loadDocument(Buffer.from(pdfData));
+        const pageHandle = binding.loadPage(docHandle, 0);
+
+        const matrix = { a: 8, b: 0, c: 0, d: 8, e: -1200, f: -1600 };
+        const clip = { left: 0, top: 0, right: 256, bottom: 128 };
+        const tile = binding.renderRegion(pageHandle, 256, 128, matrix, clip, 0, 0xffffffff);
+        expect(tile.length).toBe(256 * 128 * 4);
+
+        expect(() =>
+          binding.renderRegion(pageHandle, 256, 128, { ...matrix, a: 0, d: 0 }, clip, 0, 0xffffffff),
+        ).toThrow();
+
+        binding.closePage(pageHandle);
+        binding.closeDocument(docHandle);
+      } finally {
+        binding.destroyLibrary();
+      }
+    });
+
+    test('renders deep-zoom tiles on demand', async () => {
+      binding.initLibrary();
+      try {
+        const pdfData = await readFile('test/fixtures/test_1.pdf');
+        const docHandle = binding.loadDocument(Buffer.from(pdfData));
+        const pageHandle = binding.loadPage(docHandle, 0);
+
+        const options = { tileSize: 256, maxZoom: 2, overlap: 1 };
+        const pyramid = binding.getTilePyramid(pageHandle, options);
+        const top = pyramid.levels[pyramid.levels.length - 1];
+        expect(top.width).toBe(pyramid.width);
+        expect(pyramid.dzi).toBeUndefined();
+
+        const tile = binding.renderTile(pageHandle, options, pyramid.levels.length - 1, top.columns - 1, 0);
+        expect(tile.x + tile.width).toBe(top.width);
+        expect(tile.data.length).toBe(tile.width * tile.height * 4);
+
+        const pngOptions = { ...options, format: 'png' as const };
+        expect(binding.getTilePyramid(pageHandle, pngOptions).dzi).toContain('Format="png" Overlap="1"');
+        const png = binding.renderTile(pageHandle, pngOptions, 0, 0, 0);
+        expect([...png.data.subarray(0, 4)]).toEqual([0x89, 0x50, 0x4e, 0x47]);
+        expect(() => binding.getTilePyramid(pageHandle, { ...options, format: 'gif' as 'png' })).toThrow();
+
+        binding.closePage(pageHandle);
+        binding.closeDocument(docHandle);
+      } finally {
+        binding.destroyLibrary();
+      }
+    });
+
+    test('cancels a progressive render', async () => {
+      binding.initLibrary();
+      try {
+        const pdfData = await readFile('test/fixtures/test_1.pdf');
+        const docHandle = binding.loadDocument(Buffer.from(pdfData));
+        const pageHandle = binding.loadPage(docHandle, 0);
+
+        const token = binding.createCancelToken();
+        const pixels = await binding.renderPageCancellableAsync(pageHandle, 100, 100, 0, 0, 0xffffffff, token);
+        expect(pixels.length).toBe(100 * 100 * 4);
+
+        const controller = new AbortController();
+        controller.signal.addEventListener('abort', () => token.cancel());
+        controller.abort();
+        expect(token.cancelled).toBe(true);
+        await expect(
+          binding.renderPageCancellableAsync(pageHandle, 100, 100, 0, 0, 0xffffffff, token),
+        ).rejects.toMatchObject({ code: 'ERR_PDFIUM_CANCELLED' });
+
+        binding.closePage(pageHandle);
+        binding.closeDocument(docHandle);
+      } finally {
+        binding.destroyLibrary();
+      }
+    });
+
+    test('runs heavy operations asynchronously', async () => {
+      binding.initLibrary();
+      try {
+        const pdfData = await readFile('test/fixtures/test_1.pdf');
+        const docHandle = await binding.loadDocumentAsync(Buffer.from(pdfData));
+        const pageHandle = binding.loadPage(docHandle, 0);
+        const textPageHandle = binding.loadTextPage(pageHandle);
+
+        const [pixelData, text, saved] = await Promise.all([
+          binding.renderPageAsync(pageHandle, 100, 100, 0, 0, 0xffffffff),
+          binding.getFullTextAsync(textPageHandle),
+          binding.saveDocumentAsync(docHandle, 0, null),
+        ]);
+        expect(pixelData.length).toBe(100 * 100 * 4);
+        expect(text).toBe(binding.getFullText(textPageHandle));
+        expect(saved.subarray(0, 5).toString()).toBe('%PDF-');
+
+        binding.closeTextPage(textPageHandle);
+        binding.closePage(pageHandle);
+        binding.closeDocument(docHandle);
+      } finally {
+        binding.destroyLibrary();
+      }
+    });
+
+    test('saves a document straight to a file', async () => {
+      binding.initLibrary();
+      const dir = await mkdtemp(join(tmpdir(), 'pdfium-save-'));
+      try {
+        const docHandle = binding.loadDocumentFromPath('test/fixtures/test_1.pdf');
+        const expected = binding.saveDocument(docHandle, 0, null);
+
+        const outPath = join(dir, 'out.pdf');
+        binding.saveDocumentToPath(docHandle, outPath, 0, null);
+        expect(await readFile(outPath)).toEqual(expected);
+
+        await binding.saveDocumentToPathAsync(docHandle, outPath, 0, null);
+        expect(await readFile(outPath)).toEqual(expected);
+        // The temporary file was renamed into place.
+        expect(await readdir(dir)).toEqual(['out.pdf']);
+
+        binding.closeDocument(docHandle);
+      } finally {
+        binding.destroyLibrary();
+        await rm(dir, { recursive: true, force: true });
+      }
+    });
+
+    test('async load rejects with a PDFium error code', async () => {
+      binding.initLibrary();
+      try {
+        await expect(binding.loadDocumentAsync(Buffer.from('not a pdf'))).rejects.toMatchObject({
+          code: 'ERR_PDFIUM_FORMAT',
+        });
+      } finally {
+        binding.destroyLibrary();
+      }
+    });
+
     test('reads document metadata via low-level binding', async () => {
       binding.initLibrary();
       try {